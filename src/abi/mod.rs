@@ -0,0 +1,610 @@
+use crate::primitives::{
+    decode_hex, encode_hex, keccak256, negate_word, parse_address, parse_word, to_checksum_address,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+/*//////////////////////////////////////////////////////////////
+                        ABI TYPES & VALUES
+////////////////////////////////////////////////////////////// */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiType {
+    Uint(usize),
+    Int(usize),
+    Address,
+    Bool,
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<AbiType>),
+    FixedArray(Box<AbiType>, usize),
+    Tuple(Vec<AbiType>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiValue {
+    Uint(usize, [u8; 32]),
+    Int(usize, [u8; 32]),
+    Address([u8; 20]),
+    Bool(bool),
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(AbiType, Vec<AbiValue>),
+    FixedArray(AbiType, Vec<AbiValue>),
+    Tuple(Vec<AbiValue>),
+}
+
+impl AbiType {
+    /// The canonical type name used in function signatures, e.g. `uint256[]` or `(address,bool)`.
+    pub fn canonical(&self) -> String {
+        match self {
+            AbiType::Uint(bits) => format!("uint{bits}"),
+            AbiType::Int(bits) => format!("int{bits}"),
+            AbiType::Address => "address".to_string(),
+            AbiType::Bool => "bool".to_string(),
+            AbiType::FixedBytes(size) => format!("bytes{size}"),
+            AbiType::Bytes => "bytes".to_string(),
+            AbiType::String => "string".to_string(),
+            AbiType::Array(inner) => format!("{}[]", inner.canonical()),
+            AbiType::FixedArray(inner, size) => format!("{}[{size}]", inner.canonical()),
+            AbiType::Tuple(types) => format!(
+                "({})",
+                types
+                    .iter()
+                    .map(AbiType::canonical)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            AbiType::Bytes | AbiType::String | AbiType::Array(_) => true,
+            AbiType::FixedArray(inner, _) => inner.is_dynamic(),
+            AbiType::Tuple(types) => types.iter().any(AbiType::is_dynamic),
+            _ => false,
+        }
+    }
+}
+
+impl AbiValue {
+    pub fn abi_type(&self) -> AbiType {
+        match self {
+            AbiValue::Uint(bits, _) => AbiType::Uint(*bits),
+            AbiValue::Int(bits, _) => AbiType::Int(*bits),
+            AbiValue::Address(_) => AbiType::Address,
+            AbiValue::Bool(_) => AbiType::Bool,
+            AbiValue::FixedBytes(bytes) => AbiType::FixedBytes(bytes.len()),
+            AbiValue::Bytes(_) => AbiType::Bytes,
+            AbiValue::String(_) => AbiType::String,
+            AbiValue::Array(inner, _) => AbiType::Array(Box::new(inner.clone())),
+            AbiValue::FixedArray(inner, values) => {
+                AbiType::FixedArray(Box::new(inner.clone()), values.len())
+            }
+            AbiValue::Tuple(values) => {
+                AbiType::Tuple(values.iter().map(AbiValue::abi_type).collect())
+            }
+        }
+    }
+
+    /// Human readable rendering of the value, close to how it would be written in Solidity.
+    pub fn display(&self) -> String {
+        match self {
+            AbiValue::Uint(_, word) => word_to_display(word, false),
+            AbiValue::Int(_, word) => word_to_display(word, true),
+            AbiValue::Address(address) => {
+                to_checksum_address(&encode_hex(address)).unwrap_or_else(|_| encode_hex(address))
+            }
+            AbiValue::Bool(value) => value.to_string(),
+            AbiValue::FixedBytes(bytes) | AbiValue::Bytes(bytes) => encode_hex(bytes),
+            AbiValue::String(value) => format!("{value:?}"),
+            AbiValue::Array(_, values) | AbiValue::FixedArray(_, values) => format!(
+                "[{}]",
+                values
+                    .iter()
+                    .map(AbiValue::display)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            AbiValue::Tuple(values) => format!(
+                "({})",
+                values
+                    .iter()
+                    .map(AbiValue::display)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+fn word_to_display(word: &[u8; 32], signed: bool) -> String {
+    let negative = signed && word[0] & 0x80 != 0;
+    let mut magnitude = if negative { negate_word(*word) } else { *word };
+    let mut digits = vec![];
+    while magnitude.iter().any(|byte| *byte != 0) {
+        let mut remainder = 0u32;
+        for byte in magnitude.iter_mut() {
+            let current = (remainder << 8) | *byte as u32;
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(char::from(b'0' + remainder as u8));
+    }
+    if digits.is_empty() {
+        digits.push('0');
+    }
+    if negative {
+        digits.push('-');
+    }
+    digits.iter().rev().collect()
+}
+
+/*//////////////////////////////////////////////////////////////
+                        JSON ABI DEFINITIONS
+////////////////////////////////////////////////////////////// */
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AbiEntry {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Empty for constructors, fallback and receive functions.
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub inputs: Vec<AbiParam>,
+    /// Anonymous events have no signature topic.
+    #[serde(default)]
+    pub anonymous: bool,
+}
+
+impl AbiEntry {
+    pub fn input_types(&self) -> Result<Vec<AbiType>, String> {
+        self.inputs.iter().map(AbiParam::abi_type).collect()
+    }
+
+    /// Canonical signature, e.g. `setNumber(uint256)`.
+    pub fn signature(&self) -> Result<String, String> {
+        let types: Vec<String> = self.input_types()?.iter().map(AbiType::canonical).collect();
+        Ok(format!("{}({})", self.name, types.join(",")))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AbiParam {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub components: Vec<AbiParam>,
+    /// Whether an event parameter is stored in the topics instead of the data.
+    #[serde(default)]
+    pub indexed: bool,
+}
+
+impl AbiParam {
+    pub fn abi_type(&self) -> Result<AbiType, String> {
+        parse_type(&self.kind, &self.components)
+    }
+}
+
+/// Parses a type name from a JSON ABI, e.g. `uint256[2][]`. Tuples take their members from
+/// `components`.
+pub fn parse_type(kind: &str, components: &[AbiParam]) -> Result<AbiType, String> {
+    if let Some(inner) = kind.strip_suffix(']') {
+        let (inner, size) = inner
+            .rsplit_once('[')
+            .ok_or(format!("Invalid ABI type: {kind}"))?;
+        let inner = Box::new(parse_type(inner, components)?);
+        return Ok(match size {
+            "" => AbiType::Array(inner),
+            size => AbiType::FixedArray(
+                inner,
+                size.parse()
+                    .map_err(|_| format!("Invalid ABI array size: {kind}"))?,
+            ),
+        });
+    }
+    let bits = |prefix: &str| -> Result<usize, String> {
+        match &kind[prefix.len()..] {
+            "" => Ok(256),
+            bits => bits
+                .parse()
+                .map_err(|_| format!("Invalid ABI type: {kind}")),
+        }
+    };
+    Ok(match kind {
+        "address" => AbiType::Address,
+        "bool" => AbiType::Bool,
+        "bytes" => AbiType::Bytes,
+        "string" => AbiType::String,
+        "function" => AbiType::FixedBytes(24),
+        "tuple" => AbiType::Tuple(
+            components
+                .iter()
+                .map(AbiParam::abi_type)
+                .collect::<Result<_, _>>()?,
+        ),
+        _ if kind.starts_with("uint") => AbiType::Uint(bits("uint")?),
+        _ if kind.starts_with("int") => AbiType::Int(bits("int")?),
+        _ if kind.starts_with("bytes") => AbiType::FixedBytes(
+            kind[5..]
+                .parse()
+                .map_err(|_| format!("Invalid ABI type: {kind}"))?,
+        ),
+        _ => return Err(format!("Unsupported ABI type: {kind}")),
+    })
+}
+
+/// Converts a JSON argument, as stored by deployment tools, to an ABI value of the given type.
+/// Numbers may be JSON numbers, decimal or hex strings, or ethers' `BigNumber` objects.
+pub fn abi_value_from_json(value: &Value, abi_type: &AbiType) -> Result<AbiValue, String> {
+    let invalid = || format!("Invalid {} argument: {value}", abi_type.canonical());
+    Ok(match abi_type {
+        AbiType::Uint(bits) | AbiType::Int(bits) => {
+            let number = match value {
+                Value::Number(number) => number.to_string(),
+                Value::String(number) => number.clone(),
+                Value::Object(object) => object
+                    .get("hex")
+                    .or(object.get("_hex"))
+                    .and_then(Value::as_str)
+                    .ok_or_else(invalid)?
+                    .to_string(),
+                _ => return Err(invalid()),
+            };
+            let word = parse_word(&number)?;
+            match abi_type {
+                AbiType::Uint(_) => AbiValue::Uint(*bits, word),
+                _ => AbiValue::Int(*bits, word),
+            }
+        }
+        AbiType::Address => AbiValue::Address(parse_address(value.as_str().ok_or_else(invalid)?)?),
+        AbiType::Bool => AbiValue::Bool(match value {
+            Value::Bool(value) => *value,
+            Value::String(value) => value == "true",
+            _ => return Err(invalid()),
+        }),
+        AbiType::FixedBytes(_) => {
+            AbiValue::FixedBytes(decode_hex(value.as_str().ok_or_else(invalid)?)?)
+        }
+        AbiType::Bytes => AbiValue::Bytes(decode_hex(value.as_str().ok_or_else(invalid)?)?),
+        AbiType::String => AbiValue::String(value.as_str().ok_or_else(invalid)?.to_string()),
+        AbiType::Array(_) | AbiType::FixedArray(_, _) | AbiType::Tuple(_) => {
+            let values = value.as_array().ok_or_else(invalid)?;
+            let types: Vec<&AbiType> = match abi_type {
+                AbiType::Array(inner) | AbiType::FixedArray(inner, _) => {
+                    vec![inner.as_ref(); values.len()]
+                }
+                AbiType::Tuple(types) => types.iter().collect(),
+                _ => unreachable!(),
+            };
+            if types.len() != values.len() {
+                return Err(invalid());
+            }
+            let values = values
+                .iter()
+                .zip(types)
+                .map(|(value, abi_type)| abi_value_from_json(value, abi_type))
+                .collect::<Result<Vec<_>, _>>()?;
+            match abi_type {
+                AbiType::Array(inner) => AbiValue::Array(*inner.clone(), values),
+                AbiType::FixedArray(inner, _) => AbiValue::FixedArray(*inner.clone(), values),
+                _ => AbiValue::Tuple(values),
+            }
+        }
+    })
+}
+
+/*//////////////////////////////////////////////////////////////
+                            ENCODING
+////////////////////////////////////////////////////////////// */
+pub fn function_selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Builds the calldata of a call to `name` with the given arguments.
+/// Returns the canonical signature together with the encoded calldata.
+pub fn encode_function_call(name: &str, arguments: &[AbiValue]) -> (String, Vec<u8>) {
+    let types: Vec<String> = arguments
+        .iter()
+        .map(|argument| argument.abi_type().canonical())
+        .collect();
+    let signature = format!("{name}({})", types.join(","));
+    let mut calldata = function_selector(&signature).to_vec();
+    calldata.extend(encode(arguments));
+    (signature, calldata)
+}
+
+/// ABI encodes the values as if they were the members of a tuple.
+pub fn encode(values: &[AbiValue]) -> Vec<u8> {
+    let head_size: usize = values.iter().map(head_size).sum();
+    let mut head = vec![];
+    let mut tail = vec![];
+    for value in values {
+        if value.abi_type().is_dynamic() {
+            head.extend(usize_to_word(head_size + tail.len()));
+            tail.extend(encode_value(value));
+        } else {
+            head.extend(encode_value(value));
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+fn head_size(value: &AbiValue) -> usize {
+    if value.abi_type().is_dynamic() {
+        return 32;
+    }
+    match value {
+        AbiValue::FixedArray(_, values) | AbiValue::Tuple(values) => {
+            values.iter().map(head_size).sum()
+        }
+        _ => 32,
+    }
+}
+
+fn encode_value(value: &AbiValue) -> Vec<u8> {
+    match value {
+        AbiValue::Uint(_, word) | AbiValue::Int(_, word) => word.to_vec(),
+        AbiValue::Address(address) => {
+            let mut word = vec![0u8; 12];
+            word.extend(address);
+            word
+        }
+        AbiValue::Bool(value) => usize_to_word(*value as usize).to_vec(),
+        AbiValue::FixedBytes(bytes) => pad_right(bytes),
+        AbiValue::Bytes(bytes) => encode_dynamic_bytes(bytes),
+        AbiValue::String(value) => encode_dynamic_bytes(value.as_bytes()),
+        AbiValue::Array(_, values) => {
+            let mut encoded = usize_to_word(values.len()).to_vec();
+            encoded.extend(encode(values));
+            encoded
+        }
+        AbiValue::FixedArray(_, values) | AbiValue::Tuple(values) => encode(values),
+    }
+}
+
+fn encode_dynamic_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = usize_to_word(bytes.len()).to_vec();
+    encoded.extend(pad_right(bytes));
+    encoded
+}
+
+fn pad_right(bytes: &[u8]) -> Vec<u8> {
+    let mut padded = bytes.to_vec();
+    padded.resize(bytes.len().div_ceil(32) * 32, 0);
+    padded
+}
+
+fn usize_to_word(value: usize) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+/*//////////////////////////////////////////////////////////////
+                            DECODING
+////////////////////////////////////////////////////////////// */
+/// ABI decodes `data` as the members of a tuple of the given types.
+pub fn decode(types: &[AbiType], data: &[u8]) -> Result<Vec<AbiValue>, String> {
+    decode_tuple(types, data, 0)
+}
+
+fn decode_tuple(types: &[AbiType], data: &[u8], base: usize) -> Result<Vec<AbiValue>, String> {
+    let mut head = base;
+    let mut values = vec![];
+    for abi_type in types {
+        if abi_type.is_dynamic() {
+            let offset = read_usize(data, head)?;
+            let at = base
+                .checked_add(offset)
+                .filter(|at| *at < data.len())
+                .ok_or(format!("Invalid ABI offset {offset} at offset {head}"))?;
+            values.push(decode_value(abi_type, data, at)?);
+            head += 32;
+        } else {
+            values.push(decode_value(abi_type, data, head)?);
+            head = head.saturating_add(static_size(abi_type));
+        }
+    }
+    Ok(values)
+}
+
+fn decode_value(abi_type: &AbiType, data: &[u8], at: usize) -> Result<AbiValue, String> {
+    Ok(match abi_type {
+        AbiType::Uint(bits) => AbiValue::Uint(*bits, read_word(data, at)?),
+        AbiType::Int(bits) => AbiValue::Int(*bits, read_word(data, at)?),
+        AbiType::Address => {
+            let mut address = [0u8; 20];
+            address.copy_from_slice(&read_word(data, at)?[12..]);
+            AbiValue::Address(address)
+        }
+        AbiType::Bool => AbiValue::Bool(read_word(data, at)?[31] != 0),
+        AbiType::FixedBytes(size) => {
+            AbiValue::FixedBytes(read_word(data, at)?[..(*size).min(32)].to_vec())
+        }
+        AbiType::Bytes => AbiValue::Bytes(read_dynamic_bytes(data, at)?.to_vec()),
+        AbiType::String => {
+            AbiValue::String(String::from_utf8_lossy(read_dynamic_bytes(data, at)?).into_owned())
+        }
+        AbiType::Array(inner) => {
+            let length = read_usize(data, at)?;
+            // Every element takes at least a word, which bounds lengths of malformed data.
+            if length > data.len() / 32 {
+                return Err(format!("Invalid array length {length} at offset {at}"));
+            }
+            let types = vec![*inner.clone(); length];
+            AbiValue::Array(*inner.clone(), decode_tuple(&types, data, at + 32)?)
+        }
+        AbiType::FixedArray(inner, size) => {
+            // Like array lengths, sizes larger than the remaining data can't be decoded.
+            if *size > data.len().saturating_sub(at) / 32 {
+                return Err(format!(
+                    "Fixed array of {size} elements exceeds the data at offset {at}"
+                ));
+            }
+            let types = vec![*inner.clone(); *size];
+            AbiValue::FixedArray(*inner.clone(), decode_tuple(&types, data, at)?)
+        }
+        AbiType::Tuple(types) => AbiValue::Tuple(decode_tuple(types, data, at)?),
+    })
+}
+
+fn static_size(abi_type: &AbiType) -> usize {
+    match abi_type {
+        AbiType::FixedArray(inner, size) => size.saturating_mul(static_size(inner)),
+        AbiType::Tuple(types) => types.iter().map(static_size).sum(),
+        _ => 32,
+    }
+}
+
+fn read_word(data: &[u8], at: usize) -> Result<[u8; 32], String> {
+    data.get(at..at.saturating_add(32))
+        .and_then(|word| word.try_into().ok())
+        .ok_or(format!("ABI data too short to read a word at offset {at}"))
+}
+
+fn read_usize(data: &[u8], at: usize) -> Result<usize, String> {
+    let word = read_word(data, at)?;
+    if word[..24].iter().any(|byte| *byte != 0) {
+        return Err(format!("Invalid ABI offset or length at offset {at}"));
+    }
+    usize::try_from(u64::from_be_bytes(
+        word[24..].try_into().unwrap_or_default(),
+    ))
+    .map_err(|err| err.to_string())
+}
+
+fn read_dynamic_bytes(data: &[u8], at: usize) -> Result<&[u8], String> {
+    let length = read_usize(data, at)?;
+    data.get(at + 32..(at + 32).saturating_add(length))
+        .ok_or(format!(
+            "ABI data too short to read {length} bytes at offset {at}"
+        ))
+}
+
+#[cfg(test)]
+mod abi_tests {
+    use super::*;
+    use crate::primitives::{decode_hex, parse_word};
+
+    #[test]
+    fn it_should_encode_static_function_call() {
+        let (signature, calldata) = encode_function_call(
+            "setNumber",
+            &[AbiValue::Uint(256, parse_word("42").unwrap())],
+        );
+        assert_eq!("setNumber(uint256)", signature);
+        assert_eq!(
+            "0x3fb5c1cb000000000000000000000000000000000000000000000000000000000000002a",
+            encode_hex(&calldata)
+        );
+    }
+
+    #[test]
+    fn it_should_encode_dynamic_arguments() {
+        let values = [
+            AbiValue::String("hi".to_string()),
+            AbiValue::Array(
+                AbiType::Uint(8),
+                vec![AbiValue::Uint(8, parse_word("1").unwrap())],
+            ),
+        ];
+        let expected = decode_hex(concat!(
+            "0000000000000000000000000000000000000000000000000000000000000040",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "6869000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        ))
+        .unwrap();
+        assert_eq!(expected, encode(&values));
+    }
+
+    #[test]
+    fn it_should_display_signed_and_unsigned_values() {
+        assert_eq!(
+            "-5",
+            AbiValue::Int(256, parse_word("-5").unwrap()).display()
+        );
+        assert_eq!(
+            "1000000000000000000",
+            AbiValue::Uint(256, parse_word("0xde0b6b3a7640000").unwrap()).display()
+        );
+    }
+
+    #[test]
+    fn it_should_parse_nested_json_abi_types() {
+        let param: AbiParam = serde_json::from_str(
+            r#"{"type": "tuple[2][]", "components": [{"type": "address"}, {"type": "uint"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            "(address,uint256)[2][]",
+            param.abi_type().unwrap().canonical()
+        );
+    }
+
+    #[test]
+    fn it_should_convert_json_arguments_to_abi_values() {
+        let value = abi_value_from_json(
+            &serde_json::json!({"type": "BigNumber", "hex": "0x2a"}),
+            &AbiType::Uint(256),
+        )
+        .unwrap();
+        assert_eq!(AbiValue::Uint(256, parse_word("42").unwrap()), value);
+
+        let value = abi_value_from_json(
+            &serde_json::json!(["0x90f79bf6eb2c4f870365e785982e1f101e93b906", 7]),
+            &AbiType::Tuple(vec![AbiType::Address, AbiType::Uint(8)]),
+        )
+        .unwrap();
+        assert_eq!(
+            "(0x90F79bf6EB2c4f870365E785982E1f101E93b906, 7)",
+            value.display()
+        );
+    }
+
+    #[test]
+    fn it_should_decode_encoded_values() {
+        let values = vec![
+            AbiValue::Address(parse_address("0x90f79bf6eb2c4f870365e785982e1f101e93b906").unwrap()),
+            AbiValue::String("hi".to_string()),
+            AbiValue::Array(
+                AbiType::Tuple(vec![AbiType::Bool, AbiType::Bytes]),
+                vec![AbiValue::Tuple(vec![
+                    AbiValue::Bool(true),
+                    AbiValue::Bytes(vec![0x60, 0x80]),
+                ])],
+            ),
+            AbiValue::FixedArray(
+                AbiType::Int(8),
+                vec![
+                    AbiValue::Int(8, parse_word("-1").unwrap()),
+                    AbiValue::Int(8, parse_word("2").unwrap()),
+                ],
+            ),
+        ];
+        let types: Vec<AbiType> = values.iter().map(AbiValue::abi_type).collect();
+
+        assert_eq!(values, decode(&types, &encode(&values)).unwrap());
+        assert!(decode(&types, &encode(&values)[..64]).is_err());
+
+        // A huge offset and a fixed array larger than the data are rejected, not allocated.
+        let mut huge_offset = [0u8; 32];
+        huge_offset[24..].copy_from_slice(&u64::MAX.to_be_bytes());
+        assert!(decode(&[AbiType::Bytes], &huge_offset).is_err());
+        assert!(decode(
+            &[AbiType::FixedArray(
+                Box::new(AbiType::Uint(256)),
+                usize::MAX
+            )],
+            &[0u8; 64]
+        )
+        .is_err());
+    }
+}
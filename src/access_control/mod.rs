@@ -0,0 +1,365 @@
+use crate::abi::{decode, AbiEntry, AbiType, AbiValue};
+use crate::artifacts::ContractArtifact;
+use crate::data_model::{Broadcast, Log};
+use crate::primitives::{
+    decode_hex, encode_hex, keccak256, normalize_address, to_checksum_address, ZERO_ADDRESS,
+};
+use crate::registry::ContractRegistry;
+
+/*//////////////////////////////////////////////////////////////
+                    ACCESS CONTROL SNAPSHOT
+////////////////////////////////////////////////////////////// */
+/// Events of OpenZeppelin's `Ownable`, `AccessControl` and `ERC1967Upgrade`, decoded even
+/// without artifacts.
+const WELL_KNOWN_EVENTS: &str = r#"[
+    {"type": "event", "name": "OwnershipTransferred", "inputs": [
+        {"type": "address", "indexed": true}, {"type": "address", "indexed": true}]},
+    {"type": "event", "name": "RoleGranted", "inputs": [
+        {"type": "bytes32", "indexed": true}, {"type": "address", "indexed": true},
+        {"type": "address", "indexed": true}]},
+    {"type": "event", "name": "RoleRevoked", "inputs": [
+        {"type": "bytes32", "indexed": true}, {"type": "address", "indexed": true},
+        {"type": "address", "indexed": true}]},
+    {"type": "event", "name": "AdminChanged", "inputs": [
+        {"type": "address"}, {"type": "address"}]}
+]"#;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ContractAccess {
+    /// EIP-55 checksummed address.
+    pub address: String,
+    pub name: Option<String>,
+    /// The zero address if ownership was renounced.
+    pub owner: Option<String>,
+    /// Proxy admin set by `AdminChanged`.
+    pub admin: Option<String>,
+    /// Role names, or role ids if the name is unknown, with their holders in grant order.
+    pub roles: Vec<(String, Vec<String>)>,
+    /// Other events decoded with the artifact ABIs, e.g. `Transfer(0x..., 0x..., 1)`.
+    pub events: Vec<String>,
+    /// Logs that match no known event.
+    pub undecoded_events: usize,
+}
+
+/// Owners, role holders and admins of the contracts after the broadcast, from its receipt logs.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AccessControlSnapshot {
+    /// Contracts that emitted events, in the order of their first event.
+    pub contracts: Vec<ContractAccess>,
+}
+
+impl AccessControlSnapshot {
+    /// Replays the logs of the broadcast's receipts.
+    ///
+    /// Roles are named after the `*_ROLE` constants of the artifact ABIs, assuming they are
+    /// defined as `keccak256("<NAME>")`. Events of the artifacts that aren't about access control
+    /// are listed as they are.
+    pub fn from_broadcast(
+        broadcast: &Broadcast,
+        artifacts: &[ContractArtifact],
+    ) -> Result<Self, String> {
+        let registry = ContractRegistry::from_broadcast(broadcast)?;
+        let well_known_events: Vec<AbiEntry> = serde_json::from_str(WELL_KNOWN_EVENTS)
+            .map_err(|err| format!("Invalid well-known events: {err}"))?;
+        let mut role_names = vec![([0u8; 32], "DEFAULT_ADMIN_ROLE".to_string())];
+        for entry in artifacts.iter().flat_map(|artifact| &artifact.abi) {
+            if entry.kind == "function" && entry.inputs.is_empty() && entry.name.ends_with("_ROLE")
+            {
+                role_names.push((keccak256(entry.name.as_bytes()), entry.name.clone()));
+            }
+        }
+
+        let mut snapshot = Self::default();
+        for log in broadcast.receipts.iter().flat_map(|receipt| &receipt.logs) {
+            let address = to_checksum_address(&log.address)?;
+            let index = match snapshot
+                .contracts
+                .iter()
+                .position(|contract| contract.address == address)
+            {
+                Some(index) => index,
+                None => {
+                    let name = registry
+                        .contracts
+                        .iter()
+                        .find(|contract| contract.address == address)
+                        .and_then(|contract| contract.name.clone());
+                    snapshot.contracts.push(ContractAccess {
+                        address,
+                        name,
+                        owner: None,
+                        admin: None,
+                        roles: vec![],
+                        events: vec![],
+                        undecoded_events: 0,
+                    });
+                    snapshot.contracts.len() - 1
+                }
+            };
+            let contract = &mut snapshot.contracts[index];
+
+            // Artifacts of the emitting contract are tried first, since event signatures may
+            // be shared with different parameter names or indexing.
+            let candidates = artifacts
+                .iter()
+                .filter(|artifact| contract.name.as_deref() == Some(artifact.name.as_str()))
+                .chain(artifacts.iter())
+                .flat_map(|artifact| &artifact.abi)
+                .chain(&well_known_events);
+            let mut decoded = None;
+            for entry in candidates {
+                if let Some(values) = decode_log(entry, log)? {
+                    decoded = Some((entry.signature()?, entry.name.clone(), values));
+                    break;
+                }
+            }
+            let Some((signature, name, values)) = decoded else {
+                contract.undecoded_events += 1;
+                continue;
+            };
+
+            let role_name = |role: &AbiValue| match role {
+                AbiValue::FixedBytes(id) => role_names
+                    .iter()
+                    .find(|(hash, _)| hash[..] == id[..])
+                    .map_or(encode_hex(id), |(_, name)| name.clone()),
+                role => role.display(),
+            };
+            match (signature.as_str(), &values[..]) {
+                ("OwnershipTransferred(address,address)", [_, new_owner]) => {
+                    contract.owner = Some(new_owner.display());
+                }
+                ("AdminChanged(address,address)", [_, new_admin]) => {
+                    contract.admin = Some(new_admin.display());
+                }
+                ("RoleGranted(bytes32,address,address)", [role, account, _]) => {
+                    let role = role_name(role);
+                    let account = account.display();
+                    match contract.roles.iter_mut().find(|(name, _)| *name == role) {
+                        Some((_, holders)) if !holders.contains(&account) => holders.push(account),
+                        Some(_) => {}
+                        None => contract.roles.push((role, vec![account])),
+                    }
+                }
+                ("RoleRevoked(bytes32,address,address)", [role, account, _]) => {
+                    let role = role_name(role);
+                    let account = account.display();
+                    for (_, holders) in contract.roles.iter_mut().filter(|(name, _)| *name == role)
+                    {
+                        holders.retain(|holder| *holder != account);
+                    }
+                    contract.roles.retain(|(_, holders)| !holders.is_empty());
+                }
+                _ => contract.events.push(format!(
+                    "{name}({})",
+                    values
+                        .iter()
+                        .map(AbiValue::display)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Human readable report, one block per contract.
+    pub fn report(&self) -> String {
+        let mut lines = vec![];
+        for contract in &self.contracts {
+            lines.push(format!(
+                "{} {}",
+                contract.name.as_deref().unwrap_or("<unnamed>"),
+                contract.address
+            ));
+            if let Some(owner) = &contract.owner {
+                let renounced = normalize_address(owner).as_deref() == Ok(ZERO_ADDRESS);
+                lines.push(format!(
+                    "  owner: {owner}{}",
+                    if renounced { " (renounced)" } else { "" }
+                ));
+            }
+            if let Some(admin) = &contract.admin {
+                lines.push(format!("  admin: {admin}"));
+            }
+            for (role, holders) in &contract.roles {
+                lines.push(format!("  role {role}: {}", holders.join(", ")));
+            }
+            for event in &contract.events {
+                lines.push(format!("  event {event}"));
+            }
+            if contract.undecoded_events > 0 {
+                lines.push(format!("  undecoded events: {}", contract.undecoded_events));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Decodes the log if it was emitted by the event `entry`. Indexed strings, bytes, arrays and
+/// tuples are only stored as hashes, so they are returned as `bytes32`.
+fn decode_log(entry: &AbiEntry, log: &Log) -> Result<Option<Vec<AbiValue>>, String> {
+    if entry.kind != "event" || entry.anonymous {
+        return Ok(None);
+    }
+    let Ok(types) = entry.input_types() else {
+        return Ok(None);
+    };
+    let topics = log
+        .topics
+        .iter()
+        .map(|topic| decode_hex(topic))
+        .collect::<Result<Vec<_>, _>>()?;
+    let indexed_count = entry.inputs.iter().filter(|input| input.indexed).count();
+    let signature_topic = keccak256(entry.signature()?.as_bytes());
+    if topics.first().map(Vec::as_slice) != Some(&signature_topic[..])
+        || topics.len() != indexed_count + 1
+    {
+        return Ok(None);
+    }
+
+    let data_types: Vec<AbiType> = entry
+        .inputs
+        .iter()
+        .zip(&types)
+        .filter(|(input, _)| !input.indexed)
+        .map(|(_, abi_type)| abi_type.clone())
+        .collect();
+    let Ok(data_values) = decode(&data_types, &decode_hex(&log.data)?) else {
+        return Ok(None);
+    };
+    let mut data_values = data_values.into_iter();
+    let mut indexed_topics = topics[1..].iter();
+    let mut values = vec![];
+    for (input, abi_type) in entry.inputs.iter().zip(&types) {
+        let value = if input.indexed {
+            let topic = indexed_topics.next().cloned().unwrap_or_default();
+            match abi_type {
+                AbiType::String
+                | AbiType::Bytes
+                | AbiType::Array(_)
+                | AbiType::FixedArray(_, _)
+                | AbiType::Tuple(_) => AbiValue::FixedBytes(topic),
+                abi_type => match decode(std::slice::from_ref(abi_type), &topic) {
+                    Ok(mut value) => value.remove(0),
+                    Err(_) => return Ok(None),
+                },
+            }
+        } else {
+            match data_values.next() {
+                Some(value) => value,
+                None => return Ok(None),
+            }
+        };
+        values.push(value);
+    }
+    Ok(Some(values))
+}
+
+#[cfg(test)]
+mod access_control_tests {
+    use super::*;
+    use crate::deserialization::deserialize_broadcast;
+    use crate::file_handling::read_broadcast_file;
+
+    const ACCOUNT: &str = "0x00000000000000000000000090f79bf6eb2c4f870365e785982e1f101e93b906";
+    const OTHER_ACCOUNT: &str =
+        "0x00000000000000000000000015d34aaf54267db7d7c367839aaf71a00a2c6a65";
+
+    fn topic(signature: &str) -> String {
+        encode_hex(&keccak256(signature.as_bytes()))
+    }
+
+    #[test]
+    fn it_should_report_owners_and_artifact_events_of_broadcast() {
+        let broadcast = deserialize_broadcast(
+            &read_broadcast_file("test_json_files/test_broadcast.json").unwrap(),
+        )
+        .unwrap();
+        let artifacts = [ContractArtifact {
+            name: "Ethernaut".to_string(),
+            abi: serde_json::from_str(
+                r#"[{"type": "event", "name": "LevelInstanceCreatedLog", "inputs": [
+                    {"type": "address", "indexed": true}, {"type": "address"}]}]"#,
+            )
+            .unwrap(),
+            bytecode: String::new(),
+        }];
+
+        assert_eq!(
+            "Ethernaut 0x057ef64E23666F000b34aE31332854aCBd1c8544\n  owner: 0x90F79bf6EB2c4f870365E785982E1f101E93b906\n  undecoded events: 1\nPrivacyFactory 0x261D8c5e9742e6f7f1076Fa1F560894524e19cad\n  owner: 0x90F79bf6EB2c4f870365E785982E1f101E93b906",
+            AccessControlSnapshot::from_broadcast(&broadcast, &[])
+                .unwrap()
+                .report()
+        );
+        assert_eq!(
+            vec!["LevelInstanceCreatedLog(0x90F79bf6EB2c4f870365E785982E1f101E93b906, 0xF3dfB0A70010735B0A14B4A69aFC242b19600049)".to_string()],
+            AccessControlSnapshot::from_broadcast(&broadcast, &artifacts)
+                .unwrap()
+                .contracts[0]
+                .events
+        );
+    }
+
+    #[test]
+    fn it_should_track_granted_and_revoked_roles_and_admins() {
+        let minter_role = topic("MINTER_ROLE");
+        let logs = [
+            (
+                topic("RoleGranted(bytes32,bytes32,address,address)"),
+                vec![],
+            ),
+            (
+                topic("RoleGranted(bytes32,address,address)"),
+                vec![minter_role.as_str(), ACCOUNT, ACCOUNT],
+            ),
+            (
+                topic("RoleGranted(bytes32,address,address)"),
+                vec![minter_role.as_str(), OTHER_ACCOUNT, ACCOUNT],
+            ),
+            (
+                topic("RoleRevoked(bytes32,address,address)"),
+                vec![minter_role.as_str(), ACCOUNT, ACCOUNT],
+            ),
+        ]
+        .iter()
+        .map(|(signature, topics)| {
+            let topics: Vec<String> = [signature.as_str()]
+                .iter()
+                .chain(topics)
+                .map(|topic| format!("\"{topic}\""))
+                .collect();
+            format!(
+                r#"{{"address": "0x057ef64e23666f000b34ae31332854acbd1c8544", "topics": [{}], "data": "0x"}}"#,
+                topics.join(", ")
+            )
+        })
+        .chain([format!(
+            r#"{{"address": "0x057ef64e23666f000b34ae31332854acbd1c8544", "topics": ["{}"], "data": "0x{}{}"}}"#,
+            topic("AdminChanged(address,address)"),
+            &ACCOUNT[2..],
+            &OTHER_ACCOUNT[2..]
+        )])
+        .collect::<Vec<_>>();
+        let broadcast = deserialize_broadcast(&format!(
+            r#"{{"transactions": [], "receipts": [{{"gasUsed": "0x1", "effectiveGasPrice": "0x1", "logs": [{}]}}]}}"#,
+            logs.join(", ")
+        ))
+        .unwrap();
+        let artifacts = [ContractArtifact {
+            name: "Token".to_string(),
+            abi: serde_json::from_str(
+                r#"[{"type": "function", "name": "MINTER_ROLE", "inputs": []}]"#,
+            )
+            .unwrap(),
+            bytecode: String::new(),
+        }];
+
+        let snapshot = AccessControlSnapshot::from_broadcast(&broadcast, &artifacts).unwrap();
+
+        assert_eq!(
+            "<unnamed> 0x057ef64E23666F000b34aE31332854aCBd1c8544\n  admin: 0x15d34AAf54267DB7D7c367839AAf71A00a2C6A65\n  role MINTER_ROLE: 0x15d34AAf54267DB7D7c367839AAf71A00a2C6A65\n  undecoded events: 1",
+            snapshot.report()
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use crate::proxy_map::ProxyRecord;
+use crate::registry::ContractRegistry;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/*//////////////////////////////////////////////////////////////
+                        ADDRESS BOOK
+////////////////////////////////////////////////////////////// */
+/// Deployed addresses keyed by contract name, for tools that can't read a broadcast.
+#[derive(Debug, PartialEq, Eq)]
+pub struct AddressBook {
+    pub chain_id: Option<u64>,
+    pub commit: Option<String>,
+    pub timestamp: Option<u64>,
+    /// Unique contract names and their checksummed addresses, in deployment order.
+    pub contracts: Vec<(String, String)>,
+    /// Proxies by their name in `contracts`, pointing at their implementation and admin.
+    pub proxies: Vec<(String, ProxyRecord)>,
+}
+
+impl AddressBook {
+    /// Names every contract of the registry. Repeated names get a `_2`, `_3`... suffix, and
+    /// unnamed factory children are called after their factory, e.g. `PrivacyFactoryChild`.
+    /// Proxies that were not deployed by the broadcast, but only upgraded, are called `Proxy`.
+    pub fn new(
+        registry: &ContractRegistry,
+        proxies: &[ProxyRecord],
+        chain_id: Option<u64>,
+        commit: Option<String>,
+        timestamp: Option<u64>,
+    ) -> Self {
+        // Children are always registered after their factory, so the factory is already named.
+        let mut names_by_address: HashMap<&str, String> = HashMap::new();
+        let mut name_counts: HashMap<String, usize> = HashMap::new();
+        let mut contracts = vec![];
+        let mut unique_name = |base_name: String| {
+            let count = name_counts.entry(base_name.clone()).or_default();
+            *count += 1;
+            if *count == 1 {
+                base_name
+            } else {
+                format!("{base_name}_{count}")
+            }
+        };
+        for contract in &registry.contracts {
+            let base_name = match &contract.name {
+                Some(name) => name.clone(),
+                None => match names_by_address.get(contract.deployer.as_str()) {
+                    Some(factory) => format!("{}Child", unsuffixed(factory)),
+                    None => "Contract".to_string(),
+                },
+            };
+            let name = unique_name(base_name);
+            names_by_address.insert(&contract.address, name.clone());
+            contracts.push((name, contract.address.clone()));
+        }
+        let proxies = proxies
+            .iter()
+            .map(|proxy| {
+                let name = match names_by_address.get(proxy.address.as_str()) {
+                    Some(name) => name.clone(),
+                    None => {
+                        let name = unique_name("Proxy".to_string());
+                        contracts.push((name.clone(), proxy.address.clone()));
+                        name
+                    }
+                };
+                (name, proxy.clone())
+            })
+            .collect();
+
+        Self {
+            chain_id,
+            commit,
+            timestamp,
+            contracts,
+            proxies,
+        }
+    }
+
+    /// Implementation, admin and beacon addresses of a proxy, keyed like `implementation`.
+    fn proxy_targets(proxy: &ProxyRecord) -> Vec<(&'static str, &String)> {
+        [
+            ("implementation", &proxy.implementation),
+            ("admin", &proxy.admin),
+            ("beacon", &proxy.beacon),
+        ]
+        .into_iter()
+        .filter_map(|(key, address)| Some((key, address.as_ref()?)))
+        .collect()
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        let contracts: Map<String, Value> = self
+            .contracts
+            .iter()
+            .map(|(name, address)| (name.clone(), json!(address)))
+            .collect();
+        let proxies: Map<String, Value> = self
+            .proxies
+            .iter()
+            .map(|(name, proxy)| {
+                let mut targets: Map<String, Value> = Self::proxy_targets(proxy)
+                    .into_iter()
+                    .map(|(key, address)| (key.to_string(), json!(address)))
+                    .collect();
+                targets.insert("address".to_string(), json!(proxy.address));
+                if let Some(implementation_name) = &proxy.implementation_name {
+                    targets.insert("implementationName".to_string(), json!(implementation_name));
+                }
+                (name.clone(), Value::Object(targets))
+            })
+            .collect();
+        serde_json::to_string_pretty(&json!({
+            "chainId": self.chain_id,
+            "commit": self.commit,
+            "timestamp": self.timestamp,
+            "contracts": contracts,
+            "proxies": proxies,
+        }))
+        .map_err(|err| format!("Failed to serialize address book: {err}"))
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut lines = vec![];
+        if let Some(chain_id) = self.chain_id {
+            lines.push(format!("chain_id = {chain_id}"));
+        }
+        if let Some(commit) = &self.commit {
+            lines.push(format!("commit = {}", toml_string(commit)));
+        }
+        if let Some(timestamp) = self.timestamp {
+            lines.push(format!("timestamp = {timestamp}"));
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push("[contracts]".to_string());
+        for (name, address) in &self.contracts {
+            lines.push(format!("{name} = {}", toml_string(address)));
+        }
+        for (name, proxy) in &self.proxies {
+            lines.push(String::new());
+            lines.push(format!("[proxies.{name}]"));
+            lines.push(format!("address = {}", toml_string(&proxy.address)));
+            for (key, address) in Self::proxy_targets(proxy) {
+                lines.push(format!("{key} = {}", toml_string(address)));
+            }
+            if let Some(implementation_name) = &proxy.implementation_name {
+                lines.push(format!(
+                    "implementation_name = {}",
+                    toml_string(implementation_name)
+                ));
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+
+    /// `.env` file with one `<NAME>_ADDRESS` variable per contract, e.g. `COUNTER_ADDRESS=0x...`,
+    /// and `<NAME>_IMPLEMENTATION_ADDRESS` like variables per proxy.
+    pub fn to_dotenv(&self) -> String {
+        let mut lines = vec![];
+        if let Some(chain_id) = self.chain_id {
+            lines.push(format!("CHAIN_ID={chain_id}"));
+        }
+        if let Some(commit) = &self.commit {
+            lines.push(format!("COMMIT={commit}"));
+        }
+        if let Some(timestamp) = self.timestamp {
+            lines.push(format!("TIMESTAMP={timestamp}"));
+        }
+        for (name, address) in &self.contracts {
+            lines.push(format!("{}_ADDRESS={address}", screaming_snake_case(name)));
+        }
+        for (name, proxy) in &self.proxies {
+            for (key, address) in Self::proxy_targets(proxy) {
+                lines.push(format!(
+                    "{}_{}_ADDRESS={address}",
+                    screaming_snake_case(name),
+                    key.to_uppercase()
+                ));
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+
+    /// Solidity library of address constants for Echidna harnesses, e.g.
+    /// `address internal constant COUNTER = 0x...;`. Proxies are documented with the contract to
+    /// cast them to.
+    pub fn to_solidity(&self) -> String {
+        let mut lines = vec![
+            "// SPDX-License-Identifier: UNLICENSED".to_string(),
+            "pragma solidity >=0.6.2;".to_string(),
+            String::new(),
+        ];
+        if let Some(commit) = &self.commit {
+            lines.push(format!("/// Deployed at commit {commit}."));
+        }
+        lines.push("library Addresses {".to_string());
+        if let Some(chain_id) = self.chain_id {
+            lines.push(format!(
+                "    uint256 internal constant CHAIN_ID = {chain_id};"
+            ));
+        }
+        if let Some(timestamp) = self.timestamp {
+            lines.push(format!(
+                "    uint256 internal constant TIMESTAMP = {timestamp};"
+            ));
+        }
+        for (name, address) in &self.contracts {
+            if let Some((_, proxy)) = self.proxies.iter().find(|(proxy, _)| proxy == name) {
+                if let Some(implementation_name) = &proxy.implementation_name {
+                    lines.push(format!(
+                        "    /// Proxy of {implementation_name}, use it as `{implementation_name}({})`.",
+                        screaming_snake_case(name)
+                    ));
+                }
+            }
+            lines.push(format!(
+                "    address internal constant {} = {address};",
+                screaming_snake_case(name)
+            ));
+        }
+        for (name, proxy) in &self.proxies {
+            for (key, address) in Self::proxy_targets(proxy) {
+                lines.push(format!(
+                    "    address internal constant {}_{} = {address};",
+                    screaming_snake_case(name),
+                    key.to_uppercase()
+                ));
+            }
+        }
+        lines.push("}".to_string());
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Strips the `_2` like suffix of repeated names.
+fn unsuffixed(name: &str) -> &str {
+    match name.rsplit_once('_') {
+        Some((base, suffix)) if suffix.parse::<usize>().is_ok() => base,
+        _ => name,
+    }
+}
+
+fn toml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// `PrivacyFactory` -> `PRIVACY_FACTORY`, `ERC20Token` -> `ERC20_TOKEN`.
+fn screaming_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::new();
+    for (i, &char) in chars.iter().enumerate() {
+        if i > 0 && char.is_ascii_uppercase() {
+            let previous = chars[i - 1];
+            let next_is_lowercase = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+            if previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next_is_lowercase)
+            {
+                result.push('_');
+            }
+        }
+        result.push(char.to_ascii_uppercase());
+    }
+    result
+}
+
+#[cfg(test)]
+mod address_book_tests {
+    use super::*;
+    use crate::deserialization::deserialize_broadcast;
+    use crate::file_handling::read_broadcast_file;
+
+    fn address_book() -> AddressBook {
+        address_book_with_proxies(&[])
+    }
+
+    fn address_book_with_proxies(proxies: &[ProxyRecord]) -> AddressBook {
+        let broadcast = deserialize_broadcast(
+            &read_broadcast_file("test_json_files/test_broadcast.json").unwrap(),
+        )
+        .unwrap();
+        let registry = ContractRegistry::from_broadcast(&broadcast).unwrap();
+        AddressBook::new(
+            &registry,
+            proxies,
+            Some(31337),
+            broadcast.commit,
+            broadcast.timestamp,
+        )
+    }
+
+    #[test]
+    fn it_should_name_contracts_and_factory_children() {
+        assert_eq!(
+            vec![
+                (
+                    "Ethernaut".to_string(),
+                    "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string()
+                ),
+                (
+                    "PrivacyFactory".to_string(),
+                    "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad".to_string()
+                ),
+                (
+                    "EthernautChild".to_string(),
+                    "0xF3dfB0A70010735B0A14B4A69aFC242b19600049".to_string()
+                ),
+            ],
+            address_book().contracts
+        );
+    }
+
+    #[test]
+    fn it_should_export_toml_and_dotenv() {
+        let address_book = address_book();
+
+        assert_eq!(
+            "chain_id = 31337\ncommit = \"cba0070\"\ntimestamp = 1668342002\n\n[contracts]\nEthernaut = \"0x057ef64E23666F000b34aE31332854aCBd1c8544\"\nPrivacyFactory = \"0x261D8c5e9742e6f7f1076Fa1F560894524e19cad\"\nEthernautChild = \"0xF3dfB0A70010735B0A14B4A69aFC242b19600049\"\n",
+            address_book.to_toml()
+        );
+        assert_eq!(
+            "CHAIN_ID=31337\nCOMMIT=cba0070\nTIMESTAMP=1668342002\nETHERNAUT_ADDRESS=0x057ef64E23666F000b34aE31332854aCBd1c8544\nPRIVACY_FACTORY_ADDRESS=0x261D8c5e9742e6f7f1076Fa1F560894524e19cad\nETHERNAUT_CHILD_ADDRESS=0xF3dfB0A70010735B0A14B4A69aFC242b19600049\n",
+            address_book.to_dotenv()
+        );
+    }
+
+    #[test]
+    fn it_should_export_proxies_as_solidity_constants() {
+        let address_book = address_book_with_proxies(&[
+            ProxyRecord {
+                address: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+                implementation: Some("0x261D8c5e9742e6f7f1076Fa1F560894524e19cad".to_string()),
+                implementation_name: Some("PrivacyFactory".to_string()),
+                admin: Some("0x90F79bf6EB2c4f870365E785982E1f101E93b906".to_string()),
+                beacon: None,
+            },
+            ProxyRecord {
+                address: "0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string(),
+                implementation: None,
+                implementation_name: None,
+                admin: None,
+                beacon: Some("0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512".to_string()),
+            },
+        ]);
+
+        assert_eq!(
+            r#"// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=0.6.2;
+
+/// Deployed at commit cba0070.
+library Addresses {
+    uint256 internal constant CHAIN_ID = 31337;
+    uint256 internal constant TIMESTAMP = 1668342002;
+    /// Proxy of PrivacyFactory, use it as `PrivacyFactory(ETHERNAUT)`.
+    address internal constant ETHERNAUT = 0x057ef64E23666F000b34aE31332854aCBd1c8544;
+    address internal constant PRIVACY_FACTORY = 0x261D8c5e9742e6f7f1076Fa1F560894524e19cad;
+    address internal constant ETHERNAUT_CHILD = 0xF3dfB0A70010735B0A14B4A69aFC242b19600049;
+    address internal constant PROXY = 0x5FbDB2315678afecb367f032d93F642f64180aa3;
+    address internal constant ETHERNAUT_IMPLEMENTATION = 0x261D8c5e9742e6f7f1076Fa1F560894524e19cad;
+    address internal constant ETHERNAUT_ADMIN = 0x90F79bf6EB2c4f870365E785982E1f101E93b906;
+    address internal constant PROXY_BEACON = 0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512;
+}
+"#,
+            address_book.to_solidity()
+        );
+        assert!(address_book
+            .to_dotenv()
+            .ends_with("ETHERNAUT_IMPLEMENTATION_ADDRESS=0x261D8c5e9742e6f7f1076Fa1F560894524e19cad\nETHERNAUT_ADMIN_ADDRESS=0x90F79bf6EB2c4f870365E785982E1f101E93b906\nPROXY_BEACON_ADDRESS=0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512\n"));
+    }
+
+    #[test]
+    fn it_should_suffix_repeated_names_and_convert_acronyms() {
+        assert_eq!("ERC20_TOKEN", screaming_snake_case("ERC20Token"));
+        assert_eq!("WETH9", screaming_snake_case("WETH9"));
+        assert_eq!("COUNTER_2", screaming_snake_case("Counter_2"));
+        assert_eq!("Counter", unsuffixed("Counter_2"));
+    }
+}
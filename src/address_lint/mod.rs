@@ -0,0 +1,306 @@
+use crate::primitives::normalize_address;
+use crate::registry::ContractRegistry;
+use glob::glob;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::read_to_string;
+use std::path::Path;
+
+/*//////////////////////////////////////////////////////////////
+                    HARD-CODED ADDRESS LINT
+////////////////////////////////////////////////////////////// */
+/// Addresses that test contracts legitimately hard-code without a deployment behind them:
+/// the hevm cheatcodes, Foundry's console and the deterministic deployment proxy.
+const WELL_KNOWN_ADDRESSES: [&str; 3] = [
+    "0x7109709ecfa91a80626ff3989d68f67f5b1dd12d",
+    "0x000000000000000000636f6e736f6c652e6c6f67",
+    "0x4e59b44847b379578588920ca78fbf26c0b4956c",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AddressFinding {
+    pub path: String,
+    pub line: usize,
+    pub message: String,
+    /// Whether the finding may be legitimate, like a cast to an interface of the deployed contract.
+    pub warning: bool,
+}
+
+impl fmt::Display for AddressFinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = if self.warning { "warning: " } else { "" };
+        write!(f, "{}:{}: {severity}{}", self.path, self.line, self.message)
+    }
+}
+
+/// Lints every `.sol` file below the given directories. Missing directories are skipped.
+///
+/// `accounts` are addresses without code that may appear as plain literals, like senders.
+/// Returns the number of linted files and the findings.
+pub fn lint_test_dirs(
+    dirs: &[String],
+    registry: &ContractRegistry,
+    accounts: &HashSet<String>,
+) -> Result<(usize, Vec<AddressFinding>), String> {
+    let mut linted_files = 0;
+    let mut findings = vec![];
+    for dir in dirs {
+        if !Path::new(dir).is_dir() {
+            continue;
+        }
+        let pattern = format!("{}/**/*.sol", dir.trim_end_matches('/'));
+        for path in glob(&pattern).map_err(|err| format!("Invalid test directory {dir}: {err}"))? {
+            let path = path.map_err(|err| err.to_string())?;
+            let source = read_to_string(&path)
+                .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+            findings.extend(lint_source(
+                &path.to_string_lossy(),
+                &source,
+                registry,
+                accounts,
+            )?);
+            linted_files += 1;
+        }
+    }
+    Ok((linted_files, findings))
+}
+
+/// Reports address literals that match no deployment, and casts like `Counter(0x...)` whose
+/// type doesn't match the contract deployed at the address. A cast to `ICounter` is accepted
+/// for a `Counter` deployment. Casts to another type the broadcast deploys are errors, casts to
+/// types it doesn't deploy are warnings, as they may be interfaces or bases of the contract.
+pub fn lint_source(
+    path: &str,
+    source: &str,
+    registry: &ContractRegistry,
+    accounts: &HashSet<String>,
+) -> Result<Vec<AddressFinding>, String> {
+    let code = strip_comments(source);
+    let mut findings = vec![];
+    for (offset, literal) in address_literals(&code) {
+        let line = code[..offset].matches('\n').count() + 1;
+        let address = normalize_address(literal)?;
+        let cast = cast_type(&code[..offset]);
+        let deployment = registry
+            .contracts
+            .iter()
+            .find(|contract| normalize_address(&contract.address).as_ref() == Ok(&address));
+
+        let mut warning = false;
+        let message = match (deployment, cast) {
+            (None, None)
+                if accounts.contains(&address)
+                    || WELL_KNOWN_ADDRESSES.contains(&address.as_str()) =>
+            {
+                continue
+            }
+            (None, cast) => {
+                let mut message = format!("{literal} is not deployed by the broadcast");
+                if let Some(cast) = cast {
+                    let current_addresses: Vec<&str> = registry
+                        .contracts
+                        .iter()
+                        .filter(|contract| {
+                            contract
+                                .name
+                                .as_deref()
+                                .is_some_and(|name| casts_match(cast, name))
+                        })
+                        .map(|contract| contract.address.as_str())
+                        .collect();
+                    if !current_addresses.is_empty() {
+                        message.push_str(&format!(
+                            ", {cast} is deployed at {}",
+                            current_addresses.join(", ")
+                        ));
+                    }
+                }
+                message
+            }
+            (Some(contract), Some(cast)) => match &contract.name {
+                Some(name) if !casts_match(cast, name) => {
+                    let deployed_type = registry
+                        .contracts
+                        .iter()
+                        .any(|contract| contract.name.as_deref() == Some(cast));
+                    if deployed_type {
+                        format!("{literal} is cast to {cast}, but {name} is deployed there")
+                    } else {
+                        warning = true;
+                        format!("{literal} is cast to {cast}, check that it is an interface or base of {name}, which is deployed there")
+                    }
+                }
+                _ => continue,
+            },
+            (Some(_), None) => continue,
+        };
+        findings.push(AddressFinding {
+            path: path.to_string(),
+            line,
+            message,
+            warning,
+        });
+    }
+    Ok(findings)
+}
+
+fn casts_match(cast: &str, name: &str) -> bool {
+    cast == name || cast.strip_prefix('I') == Some(name)
+}
+
+/// Replaces comments with spaces, keeping the line breaks so that line numbers don't change.
+/// String literals are kept, so `"https://..."` doesn't start a comment.
+fn strip_comments(source: &str) -> String {
+    let mut code = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(char) = chars.next() {
+        match (char, chars.peek()) {
+            ('"', _) | ('\'', _) => {
+                code.push(char);
+                while let Some(next) = chars.next() {
+                    code.push(next);
+                    if next == '\\' {
+                        code.extend(chars.next());
+                    } else if next == char || next == '\n' {
+                        break;
+                    }
+                }
+            }
+            ('/', Some('/')) => {
+                code.push(' ');
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    code.push(' ');
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                code.push(' ');
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    code.push(if next == '\n' { '\n' } else { ' ' });
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            _ => code.push(char),
+        }
+    }
+    code
+}
+
+fn is_identifier_char(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == '_' || char == '$'
+}
+
+/// Byte offsets and text of the 20 byte hex literals in the code.
+fn address_literals(code: &str) -> Vec<(usize, &str)> {
+    let bytes = code.as_bytes();
+    let mut literals = vec![];
+    let mut i = 0;
+    while let Some(found) = code[i..].find("0x") {
+        let start = i + found;
+        let digits = code[start + 2..]
+            .chars()
+            .take_while(char::is_ascii_hexdigit)
+            .count();
+        let end = start + 2 + digits;
+        let standalone = (start == 0 || !is_identifier_char(bytes[start - 1] as char))
+            && code[end..]
+                .chars()
+                .next()
+                .is_none_or(|next| !is_identifier_char(next));
+        if digits == 40 && standalone {
+            literals.push((start, &code[start..end]));
+        }
+        i = end;
+    }
+    literals
+}
+
+/// The contract type of a cast ending right before a literal, like `Counter(` or
+/// `Counter(address(`. Function calls like `vm.prank(` are not casts.
+fn cast_type(code_before: &str) -> Option<&str> {
+    let (rest, identifier) = preceding_call(code_before)?;
+    let (rest, identifier) = if identifier == "address" || identifier == "payable" {
+        preceding_call(rest)?
+    } else {
+        (rest, identifier)
+    };
+    let is_member_call = rest.trim_end().ends_with('.');
+    let is_type = identifier.starts_with(|char: char| char.is_ascii_uppercase());
+    (is_type && !is_member_call).then_some(identifier)
+}
+
+/// Splits `...Name(` into the code before `Name` and `Name`.
+fn preceding_call(code: &str) -> Option<(&str, &str)> {
+    let code = code.trim_end().strip_suffix('(')?.trim_end();
+    let start = code
+        .rfind(|char: char| !is_identifier_char(char))
+        .map_or(0, |index| index + 1);
+    let identifier = &code[start..];
+    (!identifier.is_empty()).then_some((&code[..start], identifier))
+}
+
+#[cfg(test)]
+mod address_lint_tests {
+    use super::*;
+    use crate::deserialization::deserialize_broadcast;
+    use crate::file_handling::read_broadcast_file;
+
+    const SOURCE: &str = r#"
+contract EchidnaTest {
+    Ethernaut ethernaut = Ethernaut(0x057ef64E23666F000b34aE31332854aCBd1c8544);
+    // Counter old = Counter(0x0000000000000000000000000000000000000001);
+    IPrivacyFactory factory = IPrivacyFactory(payable(0x261D8c5e9742e6f7f1076Fa1F560894524e19cad));
+    PrivacyFactory wrong = PrivacyFactory(0x057ef64E23666F000b34aE31332854aCBd1c8544);
+    Ethernaut stale = Ethernaut(address(0x5FbDB2315678afecb367f032d93F642f64180aa3));
+
+    function echidna_test() public returns (bool) {
+        hevm.prank(0x90F79bf6EB2c4f870365E785982E1f101E93b906);
+        hevm.prank(0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512);
+        emit log("https://sepolia.etherscan.io/\"//"); IOwnable(0x057ef64E23666F000b34aE31332854aCBd1c8544).owner();
+        return true;
+    }
+}
+"#;
+
+    fn lint() -> Vec<AddressFinding> {
+        let broadcast = deserialize_broadcast(
+            &read_broadcast_file("test_json_files/test_broadcast.json").unwrap(),
+        )
+        .unwrap();
+        let registry = ContractRegistry::from_broadcast(&broadcast).unwrap();
+        let accounts = HashSet::from(["0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string()]);
+        lint_source("test/EchidnaTest.sol", SOURCE, &registry, &accounts).unwrap()
+    }
+
+    #[test]
+    fn it_should_report_stale_literals_and_mismatching_casts() {
+        assert_eq!(
+            vec![
+                "test/EchidnaTest.sol:6: 0x057ef64E23666F000b34aE31332854aCBd1c8544 is cast to PrivacyFactory, but Ethernaut is deployed there",
+                "test/EchidnaTest.sol:7: 0x5FbDB2315678afecb367f032d93F642f64180aa3 is not deployed by the broadcast, Ethernaut is deployed at 0x057ef64E23666F000b34aE31332854aCBd1c8544",
+                "test/EchidnaTest.sol:11: 0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512 is not deployed by the broadcast",
+                "test/EchidnaTest.sol:12: warning: 0x057ef64E23666F000b34aE31332854aCBd1c8544 is cast to IOwnable, check that it is an interface or base of Ethernaut, which is deployed there",
+            ],
+            lint()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_should_find_casts_before_literals() {
+        assert_eq!(Some("Counter"), cast_type("x = Counter("));
+        assert_eq!(Some("Counter"), cast_type("x = Counter(payable( "));
+        assert_eq!(None, cast_type("vm.Prank("));
+        assert_eq!(None, cast_type("foo("));
+        assert_eq!(None, cast_type("x = "));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::abi::AbiEntry;
+use glob::glob;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs::read_to_string;
+use std::path::Path;
+
+/*//////////////////////////////////////////////////////////////
+                    FOUNDRY ARTIFACT STRUCTS
+////////////////////////////////////////////////////////////// */
+#[derive(Deserialize, Debug)]
+struct FoundryArtifact {
+    abi: Vec<AbiEntry>,
+    bytecode: Option<FoundryBytecode>,
+    /// Solc metadata, whose `settings.compilationTarget` maps the source file to the contract name.
+    metadata: Option<Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FoundryBytecode {
+    /// Hex init code. Unlinked libraries appear as `__$...$__` placeholders of an address' length.
+    object: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractArtifact {
+    pub name: String,
+    pub abi: Vec<AbiEntry>,
+    /// Lowercase hex init code without `0x`, unlinked libraries keeping their placeholders.
+    pub bytecode: String,
+}
+
+impl ContractArtifact {
+    /// Length of the init code in bytes, which is where the constructor arguments start.
+    pub fn bytecode_length(&self) -> usize {
+        self.bytecode.len() / 2
+    }
+
+    /// Whether hex `init_code` starts with the artifact's bytecode, whatever addresses the
+    /// libraries are linked to.
+    pub fn matches_init_code(&self, init_code: &str) -> bool {
+        let bytecode = self.bytecode.as_bytes();
+        let init_code = init_code.trim_start_matches("0x").as_bytes();
+        if bytecode.is_empty() || init_code.len() < bytecode.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < bytecode.len() {
+            if bytecode[i..].starts_with(b"__") {
+                i += 40;
+            } else if bytecode[i] == init_code[i].to_ascii_lowercase() {
+                i += 1;
+            } else {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/*//////////////////////////////////////////////////////////////
+                    FOUNDRY ARTIFACT LOADING
+////////////////////////////////////////////////////////////// */
+/// Reads the contract artifacts of a Foundry `out` directory, e.g. `out/Counter.sol/Counter.json`.
+///
+/// Build info and other JSON files that aren't contract artifacts are skipped.
+pub fn read_foundry_artifacts(out_dir: &Path) -> Result<Vec<ContractArtifact>, String> {
+    if !out_dir.is_dir() {
+        return Err(format!(
+            "Artifacts directory {} does not exist, run `forge build` first",
+            out_dir.display()
+        ));
+    }
+    let pattern = format!("{}/**/*.json", out_dir.display());
+    let mut artifacts = vec![];
+    for path in glob(&pattern).map_err(|err| format!("Invalid artifacts directory: {err}"))? {
+        let path = path.map_err(|err| err.to_string())?;
+        if path
+            .components()
+            .any(|component| component.as_os_str() == "build-info")
+        {
+            continue;
+        }
+        let content = read_to_string(&path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        let Ok(artifact) = serde_json::from_str::<FoundryArtifact>(&content) else {
+            continue;
+        };
+        // Contracts compiled with several solc versions are written to `Counter.0.8.19.json`,
+        // and contract names can't contain a dot.
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default();
+        let name = artifact
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata["settings"]["compilationTarget"].as_object())
+            .and_then(|targets| targets.values().next())
+            .and_then(Value::as_str)
+            .unwrap_or(stem.split('.').next().unwrap_or_default())
+            .to_string();
+        let bytecode = artifact.bytecode.map_or(String::new(), |bytecode| {
+            bytecode.object.trim_start_matches("0x").to_lowercase()
+        });
+        artifacts.push(ContractArtifact {
+            name,
+            abi: artifact.abi,
+            bytecode,
+        });
+    }
+    Ok(artifacts)
+}
+
+#[cfg(test)]
+mod artifacts_tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    #[test]
+    fn it_should_name_artifacts_of_several_solc_versions_by_their_contract() {
+        let dir = std::env::temp_dir().join(format!(
+            "foundry2echidna_artifacts_test_{}",
+            std::process::id()
+        ));
+        let _ = remove_dir_all(&dir);
+        create_dir_all(dir.join("Counter.sol")).unwrap();
+        write(
+            dir.join("Counter.sol/Counter.0.8.19.json"),
+            r#"{"abi": [], "bytecode": {"object": "0x6080"}}"#,
+        )
+        .unwrap();
+        write(
+            dir.join("Counter.sol/Counter.0.8.20.json"),
+            r#"{"abi": [], "bytecode": {"object": "0x608060"}, "metadata": {"settings": {"compilationTarget": {"src/Counter.sol": "Counter"}}}}"#,
+        )
+        .unwrap();
+
+        let mut artifacts = read_foundry_artifacts(&dir).unwrap();
+        artifacts.sort_by_key(ContractArtifact::bytecode_length);
+
+        assert_eq!(
+            vec![("Counter", 2), ("Counter", 3)],
+            artifacts
+                .iter()
+                .map(|artifact| (artifact.name.as_str(), artifact.bytecode_length()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
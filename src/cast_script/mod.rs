@@ -0,0 +1,197 @@
+use crate::data_model::EthenoEvent;
+use crate::primitives::{decode_hex, encode_hex, normalize_address, validated_quantity};
+use crate::serialization::ANVIL_ACCOUNT_BALANCE;
+use std::collections::{HashMap, HashSet};
+
+/*//////////////////////////////////////////////////////////////
+                    CAST REPLAY SCRIPT EXPORT
+////////////////////////////////////////////////////////////// */
+const SCRIPT_HEADER: &str = r#"#!/usr/bin/env bash
+# Generated by foundry2echidna. Replays the deployment on any anvil-compatible node:
+#   RPC_URL=http://127.0.0.1:8545 bash replay.sh
+set -euo pipefail
+
+RPC_URL="${RPC_URL:-http://127.0.0.1:8545}"
+
+check_address() {
+    if [ "${1,,}" != "${2,,}" ]; then
+        echo "Address mismatch: expected $1, got $2" >&2
+        exit 1
+    fi
+}
+
+check_code() {
+    if [ "$(cast code --rpc-url "$RPC_URL" "$1")" = "0x" ]; then
+        echo "No code at $1" >&2
+        exit 1
+    fi
+}
+
+prepare_sender() {
+    cast rpc --rpc-url "$RPC_URL" anvil_impersonateAccount "$1" > /dev/null
+    if [ "$(cast balance --rpc-url "$RPC_URL" "$1")" = "0" ]; then
+        cast rpc --rpc-url "$RPC_URL" anvil_setBalance "$1" "$2" > /dev/null
+    fi
+}
+
+next_address() {
+    cast compute-address --rpc-url "$RPC_URL" "$1" | awk '{print $NF}'
+}
+"#;
+
+pub fn generate_cast_script(events: &[EthenoEvent]) -> Result<String, String> {
+    let mut script = SCRIPT_HEADER.to_string();
+    let mut prepared_senders = HashSet::new();
+    let mut created_contracts = HashSet::new();
+    let mut balances = HashMap::new();
+
+    for (i, event) in events.iter().enumerate() {
+        match event {
+            EthenoEvent::AccountCreated { address, balance } => {
+                let address = normalize_address(address)?;
+                if let Some(balance) = balance {
+                    balances.insert(address, validated_quantity(balance)?);
+                }
+            }
+            EthenoEvent::ContractCreated {
+                from,
+                contract_address,
+                data,
+                value,
+                ..
+            } => {
+                let from = normalize_address(from)?;
+                let contract_address = normalize_address(contract_address)?;
+                script.push_str(&format!("\n# [{i}] ContractCreated {contract_address}\n"));
+                script.push_str(&prepare_sender(&from, &mut prepared_senders, &balances));
+                script.push_str(&format!(
+                    "check_address {contract_address} \"$(next_address {from})\"\n"
+                ));
+                script.push_str(&format!(
+                    "cast send --rpc-url \"$RPC_URL\" --unlocked --from {from} --value {} --create {} > /dev/null\n",
+                    validated_quantity(value)?,
+                    validated_hex(data)?
+                ));
+                script.push_str(&format!("check_code {contract_address}\n"));
+                created_contracts.insert(contract_address);
+            }
+            EthenoEvent::FunctionCall {
+                from,
+                to,
+                data,
+                value,
+                ..
+            } => {
+                let from = normalize_address(from)?;
+                let to = normalize_address(to)?;
+                script.push_str(&format!("\n# [{i}] FunctionCall {to}\n"));
+                script.push_str(&prepare_sender(&from, &mut prepared_senders, &balances));
+                if created_contracts.contains(&to) {
+                    script.push_str(&format!("check_code {to}\n"));
+                }
+                script.push_str(&format!(
+                    "cast send --rpc-url \"$RPC_URL\" --unlocked --from {from} --value {} {to} {} > /dev/null\n",
+                    validated_quantity(value)?,
+                    validated_hex(data)?
+                ));
+            }
+            EthenoEvent::BlockMined { number, timestamp } => {
+                script.push_str(&format!(
+                    "\n# [{i}] BlockMined {number} at {timestamp} (blocks are mined on every send)\n"
+                ));
+            }
+        }
+    }
+    script.push_str("\necho \"Replayed deployment successfully!\"\n");
+    Ok(script)
+}
+
+fn prepare_sender(
+    sender: &str,
+    prepared_senders: &mut HashSet<String>,
+    balances: &HashMap<String, String>,
+) -> String {
+    if !prepared_senders.insert(sender.to_string()) {
+        return String::new();
+    }
+    let balance = balances
+        .get(sender)
+        .map(String::as_str)
+        .unwrap_or(ANVIL_ACCOUNT_BALANCE);
+    format!("prepare_sender {sender} {balance}\n")
+}
+
+fn validated_hex(data: &str) -> Result<String, String> {
+    Ok(encode_hex(&decode_hex(data)?))
+}
+
+#[cfg(test)]
+mod cast_script_tests {
+    use super::*;
+
+    fn creation_and_call() -> Vec<EthenoEvent> {
+        vec![
+            EthenoEvent::AccountCreated {
+                address: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+                balance: None,
+            },
+            EthenoEvent::ContractCreated {
+                from: "0x90F79bf6EB2c4f870365E785982E1f101E93b906".to_string(),
+                contract_address: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+                gas_used: "0x6e675".to_string(),
+                gas_price: "0xe0fed783".to_string(),
+                data: "0x6080".to_string(),
+                value: "0x0".to_string(),
+            },
+            EthenoEvent::FunctionCall {
+                from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+                to: "0x057ef64e23666f000b34ae31332854acbd1c8544".to_string(),
+                gas_used: "0xb3bd".to_string(),
+                gas_price: "0xe0fed783".to_string(),
+                data: "0x202023".to_string(),
+                value: "0x0".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn it_should_generate_checked_cast_commands() {
+        let script = generate_cast_script(&creation_and_call()).unwrap();
+
+        let expected_commands = "
+# [1] ContractCreated 0x057ef64e23666f000b34ae31332854acbd1c8544
+prepare_sender 0x90f79bf6eb2c4f870365e785982e1f101e93b906 0x21e19e0c9bab2400000
+check_address 0x057ef64e23666f000b34ae31332854acbd1c8544 \"$(next_address 0x90f79bf6eb2c4f870365e785982e1f101e93b906)\"
+cast send --rpc-url \"$RPC_URL\" --unlocked --from 0x90f79bf6eb2c4f870365e785982e1f101e93b906 --value 0x0 --create 0x6080 > /dev/null
+check_code 0x057ef64e23666f000b34ae31332854acbd1c8544
+
+# [2] FunctionCall 0x057ef64e23666f000b34ae31332854acbd1c8544
+check_code 0x057ef64e23666f000b34ae31332854acbd1c8544
+cast send --rpc-url \"$RPC_URL\" --unlocked --from 0x90f79bf6eb2c4f870365e785982e1f101e93b906 --value 0x0 0x057ef64e23666f000b34ae31332854acbd1c8544 0x202023 > /dev/null
+";
+        assert!(script.starts_with("#!/usr/bin/env bash"));
+        assert!(script.contains(expected_commands));
+    }
+
+    #[test]
+    fn it_should_reject_odd_length_data_and_unparsed_quantities() {
+        let mut events = creation_and_call();
+        if let EthenoEvent::ContractCreated { data, .. } = &mut events[1] {
+            *data = "0x6080604".to_string();
+        }
+        assert!(generate_cast_script(&events).is_err());
+
+        let mut events = creation_and_call();
+        if let EthenoEvent::FunctionCall { value, .. } = &mut events[2] {
+            *value = "0$(touch /tmp/pwned)".to_string();
+        }
+        assert!(generate_cast_script(&events).is_err());
+
+        let mut events = creation_and_call();
+        events[0] = EthenoEvent::AccountCreated {
+            address: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+            balance: Some("1; rm -rf ~".to_string()),
+        };
+        assert!(generate_cast_script(&events).is_err());
+    }
+}
@@ -1,9 +1,54 @@
-use crate::deserialization::deserialize_broadcast;
-use crate::file_handling::{read_broadcast_file, write_transformed_broadcast_to_file};
-use crate::serialization::{add_account_created_events, serialize_broadcast};
-use clap::Parser;
+use crate::access_control::AccessControlSnapshot;
+use crate::address_book::AddressBook;
+use crate::address_lint::lint_test_dirs;
+use crate::artifacts::read_foundry_artifacts;
+use crate::cast_script::generate_cast_script;
+use crate::collisions::{collisions_report, find_collisions, EchidnaAccounts};
+use crate::data_model::{Broadcast, EthenoEvent};
+use crate::decoder::{annotate_events, decode_broadcast};
+use crate::deserialization::{
+    deserialize_broadcast, deserialize_etheno_events, etheno_events_from_values,
+};
+use crate::diff::{broadcast_from_etheno_events, diff_broadcasts};
+use crate::echidna::{
+    DEFAULT_ECHIDNA_CONTRACT_ADDRESS, DEFAULT_ECHIDNA_DEPLOYER, DEFAULT_ECHIDNA_SENDERS,
+};
+use crate::echidna_config::{admin_only_functions, call_inventory, echidna_config};
+use crate::evm::U256;
+use crate::file_handling::{
+    read_broadcast_file, write_to_file, write_transformed_broadcast_to_file,
+};
+use crate::funding::{funding_analysis, funding_report};
+use crate::hardhat_deploy::read_hardhat_deployments;
+use crate::ignition::read_ignition_deployment;
+use crate::libraries::{
+    compile_libraries_argument, parse_linked_libraries, undeployed_libraries_warning, LinkedLibrary,
+};
+use crate::minimize::{minimize_events, Probe};
+use crate::primitives::{normalize_address, pad_short_address, parse_address, parse_word};
+use crate::proxy::RecordingProxy;
+use crate::proxy_map::detect_proxies;
+use crate::registry::ContractRegistry;
+use crate::replay::replay_events;
+use crate::reproducer::{deserialize_reproducer, generate_foundry_test, ReproducerTestConfig};
+use crate::rpc::RpcClient;
+use crate::rpc_dump::{broadcast_from_rpc_dump, parse_rpc_dump};
+use crate::rpc_import::import_events_from_node;
+use crate::schema::{broadcast_schema, etheno_events_schema};
+use crate::sender_remap::{remap_senders, sender_map};
+use crate::serialization::{
+    add_account_created_events, serialize_broadcast, ANVIL_ACCOUNTS, ANVIL_ACCOUNT_BALANCE,
+};
+use crate::slicer::slice_broadcast;
+use crate::token_balances::{balances_report, token_balances};
+use crate::validation::{validate_etheno_events, ValidationIssue};
+use clap::{Parser, Subcommand, ValueEnum};
 use glob::glob;
-use std::{fs::create_dir_all, path::Path};
+use std::collections::HashSet;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::{fs::create_dir_all, io::stdin, path::Path, thread};
 
 /// Transforms a Foundry broadcast file into a format that is compatible with Echidna.
 ///
@@ -14,6 +59,9 @@ use std::{fs::create_dir_all, path::Path};
 /// If `input_path` is not provided, the default path is `broadcast/*.s.sol/31337/run-latest.json`.
 /// If `output_path` is not provided, the default path is `src/crytic/init.json`.
 ///
+/// The CLI also prints the crytic-compile argument of the linked libraries, if any, and a
+/// warning about the linked libraries the broadcast doesn't deploy.
+///
 /// # Examples
 ///
 /// ```
@@ -21,17 +69,664 @@ use std::{fs::create_dir_all, path::Path};
 /// transform_broadcast("tests/data/input.json", "tests/data/output.json").unwrap();
 /// ```
 pub fn transform_broadcast(input_path: &str, output_path: &str) -> Result<(), String> {
-    let broadcast_to_deserialize = read_broadcast_file(input_path)?;
-    let broadcast = deserialize_broadcast(&broadcast_to_deserialize)?;
+    transform_broadcast_file(input_path, output_path, None).map(|_| ())
+}
+
+/// Transforms a Foundry broadcast like [`transform_broadcast`], as if other accounts had sent it.
+///
+/// `sender_pairs` maps senders as `OLD=NEW`. Without pairs, the senders of the broadcast become
+/// Echidna's default senders (`0x10000`, `0x20000` and `0x30000`) in the order they first appear.
+/// Remapped addresses in the calldata are replaced, and the created contracts get the addresses
+/// of the new senders' nonces. The new senders get `AccountCreated` events.
+///
+/// Returns a report of the remapped addresses, followed by the crytic-compile argument of the
+/// linked libraries, if any, with their new addresses.
+pub fn transform_broadcast_with_remapped_senders(
+    input_path: &str,
+    output_path: &str,
+    sender_pairs: &[String],
+) -> Result<String, String> {
+    transform_broadcast_file(input_path, output_path, Some(sender_pairs))
+}
+
+/// Deserializes the broadcast, remaps its senders if `sender_pairs` is set, and writes it with
+/// the `AccountCreated` events. Returns the report of the remapped addresses and libraries.
+fn transform_broadcast_file(
+    input_path: &str,
+    output_path: &str,
+    sender_pairs: Option<&[String]>,
+) -> Result<String, String> {
+    let mut broadcast = deserialize_broadcast(&read_broadcast_file(input_path)?)?;
+    let mut lines: Vec<String> = vec![];
+    if let Some(sender_pairs) = sender_pairs {
+        let senders = sender_map(&broadcast, sender_pairs)?;
+        let remapped = remap_senders(&mut broadcast, &senders)?;
+        lines.extend(
+            remapped
+                .iter()
+                .map(|(old, new)| format!("Remapped {old} to {new}")),
+        );
+    }
+    let libraries = parse_linked_libraries(&broadcast.libraries)?;
+    lines.extend(libraries_report(&libraries, &broadcast)?);
     let broadcast = serialize_broadcast(broadcast)?;
     let etheno_like_broadcast = add_account_created_events(broadcast)?;
     write_transformed_broadcast_to_file(&etheno_like_broadcast, output_path)?;
-    Ok(())
+    Ok(lines.join("\n"))
+}
+
+/// The `cryticArgs` to add for the linked libraries, and the warning about undeployed ones.
+fn libraries_report(
+    libraries: &[LinkedLibrary],
+    broadcast: &Broadcast,
+) -> Result<Vec<String>, String> {
+    let mut lines = vec![];
+    if let Some(argument) = compile_libraries_argument(libraries) {
+        lines.push(format!(
+            "The broadcast links libraries, add this to your Echidna config:\ncryticArgs: [\"{argument}\"]"
+        ));
+    }
+    lines.extend(undeployed_libraries_warning(libraries, broadcast)?);
+    Ok(lines)
+}
+
+/// Turns an Echidna reproducer into a Foundry regression test.
+///
+/// The generated test replays the initialization from `init_path` (a Foundry broadcast or an
+/// Etheno `init.json`), deploys the Echidna test contract where Echidna deployed it, replays the
+/// reproducer's calls and finally checks the falsified property.
+pub fn generate_reproducer_test(args: &ReproducerArgs) -> Result<(), String> {
+    let init_events = read_etheno_events(&args.init_path)?;
+    let reproducer = deserialize_reproducer(&read_broadcast_file(&args.reproducer_path)?)?;
+    let harness_name = args
+        .contract
+        .rsplit(':')
+        .next()
+        .unwrap_or(&args.contract)
+        .to_string();
+    let config = ReproducerTestConfig {
+        test_name: args
+            .test_name
+            .clone()
+            .unwrap_or(format!("{harness_name}ReproducerTest")),
+        harness: args.contract.clone(),
+        harness_address: args.contract_address.clone(),
+        property: args.property.clone(),
+    };
+    let test = generate_foundry_test(&init_events, &reproducer, &config)?;
+    create_parent_dir(&args.output_path)?;
+    write_to_file(&test, &args.output_path)
+}
+
+/// Exports the deployment as a shell script of `cast` commands that rebuilds the same state on
+/// a fresh anvil-compatible node, checking every created address on the way.
+///
+/// # Arguments
+/// * `input_path` - Path to the Foundry broadcast or the Etheno `init.json`.
+/// * `output_path` - Path to a file where you want to save the script.
+pub fn export_cast_script(input_path: &str, output_path: &str) -> Result<(), String> {
+    let events = read_etheno_events(input_path)?;
+    let script = generate_cast_script(&events)?;
+    create_parent_dir(output_path)?;
+    write_to_file(&script, output_path)
+}
+
+/// Sends the events to a JSON-RPC node as `eth_sendTransaction` calls and checks that every
+/// contract lands at the address recorded in the Etheno file.
+///
+/// Senders the node holds no key for are impersonated, and senders without balance are funded.
+/// Returns the number of sent transactions.
+pub fn replay_on_node(input_path: &str, rpc_url: &str) -> Result<usize, String> {
+    let events = read_etheno_events(input_path)?;
+    let client = RpcClient::new(rpc_url)?;
+    let report = replay_events(&client, &events)?;
+    if !report.mismatches.is_empty() {
+        return Err(format!(
+            "Replay did not reproduce the Etheno file:\n{}",
+            report.mismatches.join("\n")
+        ));
+    }
+    Ok(report.sent_transactions)
+}
+
+/// Records the transactions of a running node's blocks as Etheno events, without Etheno.
+///
+/// # Arguments
+/// * `rpc_url` - JSON-RPC endpoint of the node, e.g. a local anvil.
+/// * `from_block` - First block to import.
+/// * `to_block` - Last block to import. If not provided, the latest block is used.
+/// * `output_path` - Path to a file where you want to save the Etheno events.
+pub fn import_from_node(
+    rpc_url: &str,
+    from_block: u64,
+    to_block: Option<u64>,
+    output_path: &str,
+) -> Result<(), String> {
+    let client = RpcClient::new(rpc_url)?;
+    let events = import_events_from_node(&client, from_block, to_block)?;
+    let etheno_like_broadcast = add_account_created_events(events)?;
+    create_parent_dir(output_path)?;
+    write_transformed_broadcast_to_file(&etheno_like_broadcast, output_path)
+}
+
+/// Runs a recording JSON-RPC proxy in front of `upstream_url`, a drop-in replacement for
+/// Etheno's proxy mode. Every transaction sent through the proxy is recorded, and the Etheno
+/// events are written to `output_path` once the user presses Enter.
+pub fn run_recording_proxy(
+    listen_address: &str,
+    upstream_url: &str,
+    output_path: &str,
+) -> Result<usize, String> {
+    let listener = TcpListener::bind(listen_address)
+        .map_err(|err| format!("Failed to listen on {listen_address}: {err}"))?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let stdin_stop = stop.clone();
+    thread::spawn(move || {
+        let mut line = String::new();
+        // Without a terminal (`&`, `</dev/null`, CI) stdin is closed, and only Ctrl-C stops it.
+        if matches!(stdin().read_line(&mut line), Ok(read) if read > 0) {
+            stdin_stop.store(true, Ordering::SeqCst);
+        }
+    });
+    println!("Recording transactions on http://{listen_address}, forwarding to {upstream_url}. Press Enter or Ctrl-C to stop, they are saved to {output_path} as they are sent.");
+
+    let mut proxy = RecordingProxy::new(RpcClient::new(upstream_url)?);
+    let mut saved_transactions = 0;
+    proxy.serve(&listener, &stop, &mut |proxy, outcome| {
+        if let Err(err) = outcome {
+            eprintln!("{err}");
+        }
+        if proxy.sent_transactions() == saved_transactions {
+            return;
+        }
+        match save_recorded_events(proxy, output_path) {
+            Ok(_) => {
+                saved_transactions = proxy.sent_transactions() - proxy.pending_transactions().len()
+            }
+            Err(err) => eprintln!("Failed to save the recorded transactions: {err}"),
+        }
+    })?;
+    let recorded_transactions = save_recorded_events(&mut proxy, output_path)?;
+    if let Some(hash) = proxy.pending_transactions().first() {
+        return Err(format!("Transaction {hash} has not been mined"));
+    }
+    Ok(recorded_transactions)
+}
+
+/// Writes the events recorded by the proxy so far and returns how many there are.
+fn save_recorded_events(proxy: &mut RecordingProxy, output_path: &str) -> Result<usize, String> {
+    let events = proxy.recorded_events()?;
+    let recorded_transactions = events.len();
+    let etheno_like_broadcast = add_account_created_events(events)?;
+    create_parent_dir(output_path)?;
+    write_transformed_broadcast_to_file(&etheno_like_broadcast, output_path)?;
+    Ok(recorded_transactions)
+}
+
+/// Transforms a hardhat-deploy `deployments/<network>` folder into a format that is compatible
+/// with Echidna, just like a Foundry broadcast.
+///
+/// Returns the chain id stored in the folder's `.chainId` file, if present.
+pub fn transform_hardhat_deployments(
+    deployments_dir: &str,
+    output_path: &str,
+) -> Result<Option<u64>, String> {
+    let broadcast = read_hardhat_deployments(Path::new(deployments_dir))?;
+    let chain_id = broadcast.chain;
+    write_broadcast_as_etheno_events(broadcast, output_path)?;
+    Ok(chain_id)
+}
+
+/// Transforms a Hardhat Ignition `ignition/deployments/<deployment id>` folder into a format
+/// that is compatible with Echidna, just like a Foundry broadcast.
+///
+/// Returns the chain id recorded in the deployment's journal.
+pub fn transform_ignition_deployment(
+    deployment_dir: &str,
+    output_path: &str,
+) -> Result<Option<u64>, String> {
+    let broadcast = read_ignition_deployment(Path::new(deployment_dir))?;
+    let chain_id = broadcast.chain;
+    write_broadcast_as_etheno_events(broadcast, output_path)?;
+    Ok(chain_id)
+}
+
+/// Transforms dumps of `eth_getTransactionByHash` and `eth_getTransactionReceipt` results
+/// (JSON arrays or NDJSON, split across any number of files) into a format that is compatible
+/// with Echidna, just like a Foundry broadcast.
+///
+/// Returns the number of transactions written.
+pub fn transform_rpc_dump(input_paths: &[String], output_path: &str) -> Result<usize, String> {
+    let mut objects = vec![];
+    for input_path in input_paths {
+        objects.extend(parse_rpc_dump(&read_broadcast_file(input_path)?)?);
+    }
+    let broadcast = broadcast_from_rpc_dump(objects)?;
+    let transactions = broadcast.transactions.len();
+    write_broadcast_as_etheno_events(broadcast, output_path)?;
+    Ok(transactions)
+}
+
+/// Lists every contract created by a Foundry broadcast, including the ones created by factories.
+pub fn list_broadcast_contracts(input_path: &str) -> Result<String, String> {
+    let broadcast = deserialize_broadcast(&read_broadcast_file(input_path)?)?;
+    Ok(ContractRegistry::from_broadcast(&broadcast)?.report())
+}
+
+/// Exports the addresses of the contracts created by a Foundry broadcast, keyed by contract name,
+/// together with the implementation and admin of each proxy.
+///
+/// Without `format`, it is inferred from the extension of `output_path` and defaults to JSON.
+/// The chain id is taken from the broadcast, or from its `broadcast/<script>/<chain id>/` folder.
+pub fn export_address_book(
+    input_path: &str,
+    output_path: &str,
+    format: Option<AddressBookFormat>,
+) -> Result<(), String> {
+    let broadcast = deserialize_broadcast(&read_broadcast_file(input_path)?)?;
+    let chain_id = broadcast.chain.or_else(|| {
+        Path::new(input_path)
+            .parent()
+            .and_then(Path::file_name)
+            .and_then(|folder| folder.to_str()?.parse().ok())
+    });
+    let registry = ContractRegistry::from_broadcast(&broadcast)?;
+    let proxies = detect_proxies(&broadcast, &registry)?;
+    let address_book = AddressBook::new(
+        &registry,
+        &proxies,
+        chain_id,
+        broadcast.commit,
+        broadcast.timestamp,
+    );
+    let format = format.unwrap_or(match Path::new(output_path).extension() {
+        Some(extension) if extension == "toml" => AddressBookFormat::Toml,
+        Some(extension) if extension == "env" => AddressBookFormat::Env,
+        Some(extension) if extension == "sol" => AddressBookFormat::Sol,
+        _ if output_path.ends_with(".env") => AddressBookFormat::Env,
+        _ => AddressBookFormat::Json,
+    });
+    let content = match format {
+        AddressBookFormat::Json => address_book.to_json()?,
+        AddressBookFormat::Toml => address_book.to_toml(),
+        AddressBookFormat::Env => address_book.to_dotenv(),
+        AddressBookFormat::Sol => address_book.to_solidity(),
+    };
+    create_parent_dir(output_path)?;
+    write_to_file(&content, output_path)
+}
+
+/// Checks the address literals of the `.sol` files in `test_dirs` against the contracts
+/// deployed by a Foundry broadcast.
+///
+/// Fails with the list of findings if a literal is stale or cast to the wrong contract type.
+/// Returns the number of linted files and the warnings otherwise.
+pub fn lint_hardcoded_addresses(
+    input_path: &str,
+    test_dirs: &[String],
+) -> Result<(usize, Vec<String>), String> {
+    let broadcast = deserialize_broadcast(&read_broadcast_file(input_path)?)?;
+    let registry = ContractRegistry::from_broadcast(&broadcast)?;
+    let accounts = ANVIL_ACCOUNTS
+        .iter()
+        .copied()
+        .chain([DEFAULT_ECHIDNA_CONTRACT_ADDRESS])
+        .chain(
+            broadcast
+                .transactions
+                .iter()
+                .map(|transaction| transaction.transaction.from.as_str()),
+        )
+        .map(normalize_address)
+        .collect::<Result<HashSet<_>, _>>()?;
+    let (linted_files, findings) = lint_test_dirs(test_dirs, &registry, &accounts)?;
+    let errors = findings.iter().filter(|finding| !finding.warning).count();
+    let findings: Vec<String> = findings.iter().map(ToString::to_string).collect();
+    if errors > 0 {
+        return Err(format!(
+            "{}\nFound {errors} stale or mismatching addresses",
+            findings.join("\n")
+        ));
+    }
+    Ok((linted_files, findings))
+}
+
+/// Decodes the calldata and constructor arguments of a Foundry broadcast with the ABIs of the
+/// artifacts in `artifacts_dir`, falling back to the `function` and `arguments` of the broadcast.
+///
+/// Returns a human readable listing, one transaction per line. If `output_path` is provided, the
+/// Etheno events are also written there, each with a `decoded` field next to the raw data.
+pub fn decode_broadcast_calldata(
+    input_path: &str,
+    artifacts_dir: &str,
+    output_path: Option<&str>,
+) -> Result<String, String> {
+    let broadcast = deserialize_broadcast(&read_broadcast_file(input_path)?)?;
+    let artifacts = read_foundry_artifacts(Path::new(artifacts_dir))?;
+    let decoded = decode_broadcast(&broadcast, &artifacts)?;
+    if let Some(output_path) = output_path {
+        let events = annotate_events(serialize_broadcast(broadcast)?, &decoded);
+        create_parent_dir(output_path)?;
+        write_transformed_broadcast_to_file(&add_account_created_events(events)?, output_path)?;
+    }
+    Ok(decoded
+        .iter()
+        .map(|transaction| transaction.listing_line())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Reports the owners, role holders and admins of the contracts after a Foundry broadcast, as
+/// recorded by the `OwnershipTransferred`, `RoleGranted`, `RoleRevoked` and `AdminChanged`
+/// events of its receipts.
+///
+/// With `artifacts_dir`, role names are resolved from the ABIs and the other events of the
+/// artifacts are listed too.
+pub fn access_control_snapshot(
+    input_path: &str,
+    artifacts_dir: Option<&str>,
+) -> Result<String, String> {
+    let broadcast = deserialize_broadcast(&read_broadcast_file(input_path)?)?;
+    let artifacts = match artifacts_dir {
+        Some(artifacts_dir) => read_foundry_artifacts(Path::new(artifacts_dir))?,
+        None => vec![],
+    };
+    Ok(AccessControlSnapshot::from_broadcast(&broadcast, &artifacts)?.report())
+}
+
+/// Reports the ERC-20, ERC-721 and ERC-1155 balances of every holder after a Foundry broadcast,
+/// from the transfer events of its receipts.
+///
+/// The report warns about `senders` that hold nothing, since Echidna's calls from them can't
+/// move any token.
+pub fn token_balance_snapshot(input_path: &str, senders: &[String]) -> Result<String, String> {
+    let broadcast = deserialize_broadcast(&read_broadcast_file(input_path)?)?;
+    balances_report(&token_balances(&broadcast)?, senders)
+}
+
+/// Writes an Echidna config that seeds the fuzzer with `init_path` and scopes it with
+/// `filterFunctions`, in `Contract.function(types)` notation.
+///
+/// With `FilterMode::Allowlist`, the filter lists the functions called by the broadcast and the
+/// extra `functions`. With `FilterMode::Blacklist`, it lists the admin-only functions detected
+/// from the deployment and the extra `functions`. Both can be limited to some `contracts`.
+/// The artifacts, if any, are used to decode calls and to find well-known admin functions.
+///
+/// Returns the filter entries, with the reason of each admin-only function, and the functions
+/// that may be admin-only, which are left out of the filter.
+pub fn generate_echidna_config(
+    args: &EchidnaConfigArgs,
+) -> Result<(Vec<String>, Vec<String>), String> {
+    let broadcast = deserialize_broadcast(&read_broadcast_file(&args.input_path)?)?;
+    let artifacts = match &args.artifacts_dir {
+        Some(artifacts_dir) => read_foundry_artifacts(Path::new(artifacts_dir))?,
+        None => vec![],
+    };
+    let registry = ContractRegistry::from_broadcast(&broadcast)?;
+    let inventory = call_inventory(&decode_broadcast(&broadcast, &artifacts)?);
+    let (mut entries, mut suggestions): (Vec<(String, Option<String>)>, _) = match args.mode {
+        FilterMode::Allowlist => (
+            inventory
+                .iter()
+                .map(|function| (function.filter_entry(), None))
+                .collect(),
+            vec![],
+        ),
+        FilterMode::Blacklist => {
+            let access = AccessControlSnapshot::from_broadcast(&broadcast, &artifacts)?;
+            let functions = admin_only_functions(&inventory, &access, &registry, &artifacts)?;
+            (
+                functions
+                    .admin_only
+                    .into_iter()
+                    .map(|(entry, reason)| (entry, Some(reason)))
+                    .collect(),
+                functions.suggestions,
+            )
+        }
+    };
+    if !args.contracts.is_empty() {
+        let selected = |entry: &String| {
+            args.contracts
+                .iter()
+                .any(|contract| entry.starts_with(&format!("{contract}.")))
+        };
+        entries.retain(|(entry, _)| selected(entry));
+        suggestions.retain(|(entry, _)| selected(entry));
+    }
+    suggestions.retain(|(entry, _)| !args.functions.contains(entry));
+    for function in &args.functions {
+        if !entries.iter().any(|(entry, _)| entry == function) {
+            entries.push((function.clone(), None));
+        }
+    }
+
+    let libraries = parse_linked_libraries(&broadcast.libraries)?;
+    let filter_functions: Vec<String> = entries.iter().map(|(entry, _)| entry.clone()).collect();
+    let config = echidna_config(
+        &args.init_path,
+        compile_libraries_argument(&libraries).as_deref(),
+        args.mode == FilterMode::Blacklist,
+        &filter_functions,
+    );
+    create_parent_dir(&args.output_path)?;
+    write_to_file(&config, &args.output_path)?;
+    Ok((
+        entries
+            .into_iter()
+            .map(|(entry, reason)| match reason {
+                Some(reason) => format!("{entry} ({reason})"),
+                None => entry,
+            })
+            .collect(),
+        suggestions
+            .into_iter()
+            .map(|(entry, reason)| format!("{entry} ({reason})"))
+            .collect(),
+    ))
+}
+
+/// Writes an Etheno-like `init.json` that only deploys and configures what the `target` contract
+/// (a name or an address) depends on, for focused fuzzing campaigns.
+///
+/// Returns a summary of the kept contracts and transactions.
+pub fn slice_for_target(
+    input_path: &str,
+    target: &str,
+    output_path: &str,
+) -> Result<String, String> {
+    let broadcast = deserialize_broadcast(&read_broadcast_file(input_path)?)?;
+    let registry = ContractRegistry::from_broadcast(&broadcast)?;
+    let slice = slice_broadcast(broadcast, &registry, target)?;
+    let report = slice.report(&registry);
+    write_broadcast_as_etheno_events(slice.broadcast, output_path)?;
+    Ok(report)
+}
+
+/// Removes the events of an Etheno `init.json` (or a Foundry broadcast) that don't matter for
+/// the fuzzed properties, checking every removal in an embedded EVM.
+///
+/// A removal is kept if the replay leaves the same code at the `targets` (every created contract
+/// if empty) and the same values for the `slots` (`ADDRESS:SLOT`) and `calls` (`ADDRESS:CALLDATA`
+/// or `ADDRESS:signature()`). Returns a summary of the removed events.
+pub fn minimize_init(args: &MinimizeArgs) -> Result<String, String> {
+    let events = read_etheno_events(&args.input_path)?;
+    let targets = args
+        .targets
+        .iter()
+        .map(|target| parse_address(target))
+        .collect::<Result<Vec<_>, String>>()?;
+    let mut probes = vec![];
+    for slot in &args.slots {
+        probes.push(Probe::slot(slot)?);
+    }
+    for call in &args.calls {
+        probes.push(Probe::call(call)?);
+    }
+    let minimization = minimize_events(&events, &targets, &probes)?;
+    let values = minimization
+        .events
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("Failed to serialize the Etheno events: {err}"))?;
+    create_parent_dir(&args.output_path)?;
+    write_transformed_broadcast_to_file(&values, &args.output_path)?;
+    Ok(format!(
+        "Removed {} of {} events in {} replays, {} events left",
+        minimization.removed.len(),
+        events.len(),
+        minimization.replays,
+        minimization.events.len()
+    ))
+}
+
+/// Reports what every sender of an Etheno `init.json` (or a Foundry broadcast) spends in values
+/// and fees, and the first event each one can't pay for with the balance of its `AccountCreated`
+/// event, or `balance`, anvil's 10000 ETH if `None`. Echidna funds its accounts with
+/// `balanceAddr` instead.
+pub fn check_sender_funding(input_path: &str, balance: Option<&str>) -> Result<String, String> {
+    let events = read_etheno_events(input_path)?;
+    let balance = U256::from_be_bytes(&parse_word(balance.unwrap_or(ANVIL_ACCOUNT_BALANCE))?);
+    let senders = funding_analysis(&events, balance)?;
+    Ok(funding_report(&senders, &events))
+}
+
+/// Reports the contracts of an Etheno `init.json` (or a Foundry broadcast) created at Echidna's
+/// test contract address or at its accounts, and the creations sent from Echidna's deployer or
+/// senders that don't match their nonces, with a proposed fix for each.
+pub fn check_echidna_collisions(args: &CollisionsArgs) -> Result<String, String> {
+    let events = read_etheno_events(&args.input_path)?;
+    let account = |address: &str| parse_address(&pad_short_address(address));
+    let accounts = EchidnaAccounts {
+        deployer: account(&args.deployer)?,
+        contract_address: account(&args.contract_addr)?,
+        senders: args
+            .senders
+            .iter()
+            .map(|sender| account(sender))
+            .collect::<Result<Vec<_>, String>>()?,
+    };
+    let collisions = find_collisions(&events, &accounts)?;
+    collisions_report(&collisions, &events, &accounts)
+}
+
+/// Validates an Etheno `init.json` and fails with every issue, by array index and JSON path.
+/// Warnings alone don't fail, and are listed after the verdict.
+pub fn validate_init(input_path: &str) -> Result<String, String> {
+    let issues = validate_etheno_events(&read_broadcast_file(input_path)?)?;
+    let lines = |issues: &[&ValidationIssue]| {
+        issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let (warnings, errors): (Vec<_>, Vec<_>) = issues.iter().partition(|issue| issue.warning);
+    if !errors.is_empty() {
+        return Err(format!(
+            "{input_path} has {} issues:\n{}",
+            issues.len(),
+            lines(&issues.iter().collect::<Vec<_>>())
+        ));
+    }
+    if !warnings.is_empty() {
+        return Ok(format!(
+            "{input_path} is a valid Etheno file, with {} warnings:\n{}",
+            warnings.len(),
+            lines(&warnings)
+        ));
+    }
+    Ok(format!("{input_path} is a valid Etheno file"))
+}
+
+/// The JSON Schema of the accepted broadcast or of the emitted Etheno events, pretty-printed.
+pub fn json_schema(kind: SchemaKind) -> Result<String, String> {
+    let schema = match kind {
+        SchemaKind::Broadcast => broadcast_schema(),
+        SchemaKind::Etheno => etheno_events_schema(),
+    };
+    serde_json::to_string_pretty(&schema)
+        .map_err(|err| format!("Failed to serialize the JSON Schema: {err}"))
+}
+
+/// Compares two broadcasts or two Etheno `init.json` files by their deployments and calls,
+/// decoding calldata with the ABIs of `artifacts_dir` if provided.
+pub fn diff_deployments(args: &DiffArgs) -> Result<String, String> {
+    let artifacts = match &args.artifacts_dir {
+        Some(artifacts_dir) => read_foundry_artifacts(Path::new(artifacts_dir))?,
+        None => vec![],
+    };
+    let old = read_broadcast_or_etheno_events(&args.old_path)?;
+    let new = read_broadcast_or_etheno_events(&args.new_path)?;
+    let lines = diff_broadcasts(&old, &new, &artifacts)?;
+    if lines.is_empty() {
+        return Ok("No differences in the deployments and calls".to_string());
+    }
+    Ok(lines.join("\n"))
+}
+
+/// A Foundry broadcast or an Etheno-like `init.json`.
+enum Initialization {
+    Broadcast(Broadcast),
+    EthenoEvents(Vec<EthenoEvent>),
+}
+
+/// Reads either a Foundry broadcast, a JSON object, or an Etheno-like `init.json`, an array.
+fn read_initialization(path: &str) -> Result<Initialization, String> {
+    let content = read_broadcast_file(path)?;
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|err| format!("Failed to parse {path} as JSON: {err}"))?;
+    if json.is_object() {
+        Ok(Initialization::Broadcast(deserialize_broadcast(&content)?))
+    } else {
+        Ok(Initialization::EthenoEvents(deserialize_etheno_events(
+            &content,
+        )?))
+    }
+}
+
+/// Reads either a Foundry broadcast or an Etheno-like `init.json` into the broadcast model.
+fn read_broadcast_or_etheno_events(path: &str) -> Result<Broadcast, String> {
+    Ok(match read_initialization(path)? {
+        Initialization::Broadcast(broadcast) => broadcast,
+        Initialization::EthenoEvents(events) => broadcast_from_etheno_events(events),
+    })
+}
+
+fn write_broadcast_as_etheno_events(broadcast: Broadcast, output_path: &str) -> Result<(), String> {
+    let broadcast = serialize_broadcast(broadcast)?;
+    let etheno_like_broadcast = add_account_created_events(broadcast)?;
+    create_parent_dir(output_path)?;
+    write_transformed_broadcast_to_file(&etheno_like_broadcast, output_path)
+}
+
+/// Reads either a Foundry broadcast or an Etheno-like `init.json` into Etheno events.
+fn read_etheno_events(path: &str) -> Result<Vec<EthenoEvent>, String> {
+    match read_initialization(path)? {
+        Initialization::Broadcast(broadcast) => {
+            let events = add_account_created_events(serialize_broadcast(broadcast)?)?;
+            etheno_events_from_values(events)
+        }
+        Initialization::EthenoEvents(events) => Ok(events),
+    }
+}
+
+fn create_parent_dir(path: &str) -> Result<(), String> {
+    match Path::new(path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            create_dir_all(parent).map_err(|e| e.to_string())
+        }
+        _ => Ok(()),
+    }
 }
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None )]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     #[clap(
         short,
         long,
@@ -48,11 +743,619 @@ the first one found will be used by default."#
         help = "Path to a file where you want to save the transformed broadcast. If not provided, the default path is `src/crytic/init.json`."
     )]
     pub output_path: Option<String>,
+
+    #[clap(
+        long,
+        num_args = 0..,
+        value_name = "OLD=NEW",
+        help = "Rewrite the broadcast as if other accounts sent it. Without OLD=NEW pairs, the senders become Echidna's default senders 0x10000, 0x20000 and 0x30000."
+    )]
+    pub remap_senders: Option<Vec<String>>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Turn an Echidna reproducer into a Foundry regression test.
+    Reproducer(ReproducerArgs),
+    /// Export the deployment as a replay shell script of `cast` commands.
+    CastScript(CastScriptArgs),
+    /// Replay the events against a JSON-RPC node and compare the contract addresses.
+    Replay(ReplayArgs),
+    /// Record Etheno events from the blocks of a running node.
+    ImportRpc(ImportRpcArgs),
+    /// Run a recording JSON-RPC proxy, like Etheno's proxy mode.
+    Proxy(ProxyArgs),
+    /// Transform a hardhat-deploy `deployments/<network>` folder.
+    ImportHardhatDeploy(ImportHardhatDeployArgs),
+    /// Transform a Hardhat Ignition deployment folder.
+    ImportIgnition(ImportIgnitionArgs),
+    /// Transform dumped JSON-RPC transaction and receipt objects.
+    ImportRpcDump(ImportRpcDumpArgs),
+    /// List the contracts created by a broadcast, including factory children.
+    Contracts(ContractsArgs),
+    /// Export the deployed addresses as JSON, TOML, `.env` or Solidity constants.
+    AddressBook(AddressBookArgs),
+    /// Check hard-coded addresses in Solidity test files against the broadcast.
+    LintAddresses(LintAddressesArgs),
+    /// Decode calldata and constructor arguments with the ABIs of the Foundry artifacts.
+    Decode(DecodeArgs),
+    /// Report owners, role holders and admins from the broadcast's receipt logs.
+    AccessControl(AccessControlArgs),
+    /// Report token balances per holder from the broadcast's transfer events.
+    Balances(BalancesArgs),
+    /// Write an Echidna config with `filterFunctions` from the broadcast's calls.
+    EchidnaConfig(EchidnaConfigArgs),
+    /// Keep only the transactions one target contract depends on.
+    Slice(SliceArgs),
+    /// Remove the events that don't change the deployed code or the probed state.
+    Minimize(MinimizeArgs),
+    /// Check that the senders can pay for the values and fees of their transactions.
+    Funding(FundingArgs),
+    /// Check the created addresses and sender nonces against Echidna's own accounts.
+    Collisions(CollisionsArgs),
+    /// Check a hand-edited or generated Etheno `init.json` for malformed events.
+    Validate(ValidateArgs),
+    /// Print the JSON Schema of the accepted broadcast or of the emitted Etheno events.
+    Schema(SchemaArgs),
+    /// Compare two broadcasts or two init.json files by deployments and calls.
+    Diff(DiffArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReproducerArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Echidna reproducer, e.g. `corpus/reproducers/<hash>.txt`."
+    )]
+    pub reproducer_path: String,
+
+    #[clap(
+        long,
+        help = "Path to the Foundry broadcast or the Etheno `init.json` used to seed Echidna."
+    )]
+    pub init_path: String,
+
+    #[clap(
+        short,
+        long,
+        help = "Echidna test contract, as accepted by `deployCodeTo`, e.g. `EchidnaTest.sol:EchidnaTest`."
+    )]
+    pub contract: String,
+
+    #[clap(
+        short,
+        long,
+        help = "Name of the falsified property. If not provided, the reproducer is treated as an assertion failure of its last call."
+    )]
+    pub property: Option<String>,
+
+    #[clap(
+        long,
+        help = "Name of the generated test contract. Defaults to `<Contract>ReproducerTest`."
+    )]
+    pub test_name: Option<String>,
+
+    #[clap(
+        long,
+        default_value = DEFAULT_ECHIDNA_CONTRACT_ADDRESS,
+        help = "Address at which Echidna deploys the test contract."
+    )]
+    pub contract_address: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "test/EchidnaReproducer.t.sol",
+        help = "Path to a file where you want to save the generated Foundry test."
+    )]
+    pub output_path: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CastScriptArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry broadcast or the Etheno `init.json` to export."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "replay.sh",
+        help = "Path to a file where you want to save the replay script."
+    )]
+    pub output_path: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReplayArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry broadcast or the Etheno `init.json` to replay."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "http://127.0.0.1:8545",
+        help = "JSON-RPC endpoint of the node, e.g. a local anvil."
+    )]
+    pub rpc_url: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ImportRpcArgs {
+    #[clap(
+        short,
+        long,
+        default_value = "http://127.0.0.1:8545",
+        help = "JSON-RPC endpoint of the node, e.g. a local anvil."
+    )]
+    pub rpc_url: String,
+
+    #[clap(long, default_value_t = 0, help = "First block to import.")]
+    pub from_block: u64,
+
+    #[clap(
+        long,
+        help = "Last block to import. If not provided, the latest block is used."
+    )]
+    pub to_block: Option<u64>,
+
+    #[clap(
+        short,
+        long,
+        default_value = "src/crytic/init.json",
+        help = "Path to a file where you want to save the Etheno events."
+    )]
+    pub output_path: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ProxyArgs {
+    #[clap(
+        short,
+        long,
+        default_value = "127.0.0.1:8546",
+        help = "Address the proxy listens on."
+    )]
+    pub listen: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "http://127.0.0.1:8545",
+        help = "JSON-RPC endpoint of the upstream node."
+    )]
+    pub upstream_url: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "src/crytic/init.json",
+        help = "Path to a file where you want to save the recorded Etheno events."
+    )]
+    pub output_path: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ImportHardhatDeployArgs {
+    #[clap(
+        short,
+        long,
+        default_value = "deployments/localhost",
+        help = "Path to the hardhat-deploy `deployments/<network>` folder."
+    )]
+    pub deployments_dir: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "src/crytic/init.json",
+        help = "Path to a file where you want to save the transformed deployments."
+    )]
+    pub output_path: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ImportIgnitionArgs {
+    #[clap(
+        short,
+        long,
+        default_value = "ignition/deployments/chain-31337",
+        help = "Path to the Hardhat Ignition deployment folder holding `journal.jsonl`."
+    )]
+    pub deployment_dir: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "src/crytic/init.json",
+        help = "Path to a file where you want to save the transformed deployment."
+    )]
+    pub output_path: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ImportRpcDumpArgs {
+    #[clap(
+        short,
+        long,
+        required = true,
+        num_args = 1..,
+        help = "Paths to JSON or NDJSON files with transaction and receipt objects."
+    )]
+    pub input_paths: Vec<String>,
+
+    #[clap(
+        short,
+        long,
+        default_value = "src/crytic/init.json",
+        help = "Path to a file where you want to save the transformed transactions."
+    )]
+    pub output_path: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ContractsArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry broadcast, e.g. run-latest.json."
+    )]
+    pub input_path: String,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressBookFormat {
+    Json,
+    Toml,
+    Env,
+    /// Solidity library of address constants.
+    Sol,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct AddressBookArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry broadcast, e.g. run-latest.json."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "addresses.json",
+        help = "Path to a file where you want to save the address book."
+    )]
+    pub output_path: String,
+
+    #[clap(
+        short,
+        long,
+        value_enum,
+        help = "Format of the address book. Inferred from the output extension if omitted."
+    )]
+    pub format: Option<AddressBookFormat>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct LintAddressesArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry broadcast, e.g. run-latest.json."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short = 'd',
+        long,
+        num_args = 1..,
+        default_values = ["test", "src/crytic"],
+        help = "Directories with the Echidna test contracts."
+    )]
+    pub test_dirs: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DecodeArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry broadcast, e.g. run-latest.json."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "out",
+        help = "Path to the Foundry artifacts directory."
+    )]
+    pub artifacts_dir: String,
+
+    #[clap(
+        short,
+        long,
+        help = "Path to a file where you want to save the Etheno events annotated with the decoded calls."
+    )]
+    pub output_path: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct AccessControlArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry broadcast, e.g. run-latest.json."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry artifacts directory, to name roles and decode other events."
+    )]
+    pub artifacts_dir: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct BalancesArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry broadcast, e.g. run-latest.json."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short,
+        long,
+        num_args = 1..,
+        default_values = DEFAULT_ECHIDNA_SENDERS,
+        help = "Senders of the Echidna config, flagged if they hold no tokens."
+    )]
+    pub senders: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SliceArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry broadcast, e.g. run-latest.json."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short,
+        long,
+        help = "Name or address of the contract to keep the dependencies of."
+    )]
+    pub target: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "src/crytic/init.json",
+        help = "Path to a file where you want to save the sliced Etheno events."
+    )]
+    pub output_path: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MinimizeArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Etheno init.json or the Foundry broadcast to minimize."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short,
+        long,
+        help = "Path to a file where you want to save the minimized Etheno events."
+    )]
+    pub output_path: String,
+
+    #[clap(
+        short,
+        long,
+        num_args = 1..,
+        help = "Addresses whose code must not change. Every created contract by default."
+    )]
+    pub targets: Vec<String>,
+
+    #[clap(
+        long = "slot",
+        help = "Storage slot whose value must not change, as ADDRESS:SLOT. Can be repeated."
+    )]
+    pub slots: Vec<String>,
+
+    #[clap(
+        long = "call",
+        help = "View call whose result must not change, as ADDRESS:CALLDATA or ADDRESS:signature(). Can be repeated."
+    )]
+    pub calls: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct FundingArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Etheno init.json or the Foundry broadcast."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short,
+        long,
+        help = "Initial balance of the senders in wei, e.g. Echidna's balanceAddr (0xffffffff by default). Anvil's 10000 ETH if omitted."
+    )]
+    pub balance_addr: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CollisionsArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Etheno init.json or the Foundry broadcast."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        long,
+        default_value = DEFAULT_ECHIDNA_DEPLOYER,
+        help = "Echidna's `deployer`, the account that deploys the test contract."
+    )]
+    pub deployer: String,
+
+    #[clap(
+        long,
+        default_value = DEFAULT_ECHIDNA_CONTRACT_ADDRESS,
+        help = "Echidna's `contractAddr`, the address of the test contract."
+    )]
+    pub contract_addr: String,
+
+    #[clap(
+        short,
+        long,
+        num_args = 1..,
+        default_values = DEFAULT_ECHIDNA_SENDERS,
+        help = "Echidna's `sender` accounts."
+    )]
+    pub senders: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ValidateArgs {
+    #[clap(
+        short,
+        long,
+        default_value = "src/crytic/init.json",
+        help = "Path to the Etheno init.json to validate."
+    )]
+    pub input_path: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    #[clap(
+        long = "old",
+        help = "Path to the previous Foundry broadcast or Etheno init.json."
+    )]
+    pub old_path: String,
+
+    #[clap(
+        long = "new",
+        help = "Path to the changed Foundry broadcast or Etheno init.json."
+    )]
+    pub new_path: String,
+
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry artifacts directory, to decode calldata and constructor arguments."
+    )]
+    pub artifacts_dir: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SchemaArgs {
+    #[clap(value_enum, help = "Which JSON Schema to print.")]
+    pub kind: SchemaKind,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchemaKind {
+    /// The subset of a Foundry broadcast that is read.
+    Broadcast,
+    /// The Etheno events written to `init.json`.
+    Etheno,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterMode {
+    /// Only fuzz the functions called by the broadcast.
+    Allowlist,
+    /// Fuzz everything but the admin-only functions.
+    Blacklist,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct EchidnaConfigArgs {
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry broadcast, e.g. run-latest.json."
+    )]
+    pub input_path: String,
+
+    #[clap(
+        short,
+        long,
+        default_value = "echidna.yaml",
+        help = "Path to a file where you want to save the Echidna config."
+    )]
+    pub output_path: String,
+
+    #[clap(
+        long,
+        default_value = "src/crytic/init.json",
+        help = "Path to the transformed broadcast that Echidna is initialized with."
+    )]
+    pub init_path: String,
+
+    #[clap(
+        short,
+        long,
+        value_enum,
+        default_value = "blacklist",
+        help = "Whether filterFunctions allowlists the called functions or excludes admin-only ones."
+    )]
+    pub mode: FilterMode,
+
+    #[clap(
+        short,
+        long,
+        num_args = 1..,
+        help = "Only keep the functions of these contracts."
+    )]
+    pub contracts: Vec<String>,
+
+    #[clap(
+        short,
+        long,
+        num_args = 1..,
+        help = "Extra `Contract.function(types)` entries to add to filterFunctions."
+    )]
+    pub functions: Vec<String>,
+
+    #[clap(
+        short,
+        long,
+        help = "Path to the Foundry artifacts directory, to decode calls and find admin functions."
+    )]
+    pub artifacts_dir: Option<String>,
 }
 
 impl Args {
     pub fn new() -> Result<Self, String> {
         let mut args = Self::parse();
+        if args.command.is_some() {
+            return Ok(args);
+        }
         if args.input_path.is_none() {
             let glob_pattern = "broadcast/*.s.sol/31337/run-latest.json";
             let mut paths = match glob(glob_pattern) {
@@ -79,7 +1382,9 @@ impl Args {
                 None => return Err("Failed to convert output path to string".to_string()),
             });
         } else {
-            let output_path = match Path::new(args.output_path.as_ref().unwrap()).parent() {
+            let output_path = match Path::new(args.output_path.as_deref().unwrap_or_default())
+                .parent()
+            {
                 Some(p) => p,
                 None => {
                     return Err("Failed to extract parent directory from output path".to_string())
@@ -92,4 +1397,307 @@ impl Args {
         }
         Ok(args)
     }
+
+    /// Runs the selected command and returns the message to print on success.
+    pub fn run(self) -> Result<String, String> {
+        match self.command {
+            None if self.remap_senders.is_some() => {
+                let report = transform_broadcast_with_remapped_senders(
+                    &self.input_path.unwrap_or_default(),
+                    &self.output_path.unwrap_or_default(),
+                    &self.remap_senders.unwrap_or_default(),
+                )?;
+                Ok(format!("Transformed broadcast successfully!\n{report}"))
+            }
+            None => {
+                let report = transform_broadcast_file(
+                    &self.input_path.unwrap_or_default(),
+                    &self.output_path.unwrap_or_default(),
+                    None,
+                )?;
+                Ok(match report.is_empty() {
+                    true => "Transformed broadcast successfully!".to_string(),
+                    false => format!("Transformed broadcast successfully!\n{report}"),
+                })
+            }
+            Some(Command::Reproducer(args)) => {
+                generate_reproducer_test(&args)?;
+                Ok(format!("Foundry test written to {}", args.output_path))
+            }
+            Some(Command::CastScript(args)) => {
+                export_cast_script(&args.input_path, &args.output_path)?;
+                Ok(format!("Replay script written to {}", args.output_path))
+            }
+            Some(Command::Replay(args)) => {
+                let sent_transactions = replay_on_node(&args.input_path, &args.rpc_url)?;
+                Ok(format!(
+                    "Replayed {sent_transactions} transactions on {} successfully!",
+                    args.rpc_url
+                ))
+            }
+            Some(Command::ImportRpc(args)) => {
+                import_from_node(
+                    &args.rpc_url,
+                    args.from_block,
+                    args.to_block,
+                    &args.output_path,
+                )?;
+                Ok(format!("Imported blocks into {}", args.output_path))
+            }
+            Some(Command::Proxy(args)) => {
+                let recorded_transactions =
+                    run_recording_proxy(&args.listen, &args.upstream_url, &args.output_path)?;
+                Ok(format!(
+                    "Recorded {recorded_transactions} transactions into {}",
+                    args.output_path
+                ))
+            }
+            Some(Command::ImportHardhatDeploy(args)) => {
+                let chain_id =
+                    transform_hardhat_deployments(&args.deployments_dir, &args.output_path)?;
+                Ok(match chain_id {
+                    Some(chain_id) => {
+                        format!("Transformed deployments of chain {chain_id} successfully!")
+                    }
+                    None => "Transformed deployments successfully!".to_string(),
+                })
+            }
+            Some(Command::ImportIgnition(args)) => {
+                let chain_id =
+                    transform_ignition_deployment(&args.deployment_dir, &args.output_path)?;
+                Ok(match chain_id {
+                    Some(chain_id) => {
+                        format!("Transformed deployment of chain {chain_id} successfully!")
+                    }
+                    None => "Transformed deployment successfully!".to_string(),
+                })
+            }
+            Some(Command::Contracts(args)) => list_broadcast_contracts(&args.input_path),
+            Some(Command::LintAddresses(args)) => {
+                let (linted_files, warnings) =
+                    lint_hardcoded_addresses(&args.input_path, &args.test_dirs)?;
+                let mut lines = warnings;
+                lines.push(format!(
+                    "No stale addresses found in {linted_files} Solidity files"
+                ));
+                Ok(lines.join("\n"))
+            }
+            Some(Command::AddressBook(args)) => {
+                export_address_book(&args.input_path, &args.output_path, args.format)?;
+                Ok(format!("Exported address book to {}", args.output_path))
+            }
+            Some(Command::Decode(args)) => decode_broadcast_calldata(
+                &args.input_path,
+                &args.artifacts_dir,
+                args.output_path.as_deref(),
+            ),
+            Some(Command::AccessControl(args)) => {
+                access_control_snapshot(&args.input_path, args.artifacts_dir.as_deref())
+            }
+            Some(Command::Balances(args)) => {
+                token_balance_snapshot(&args.input_path, &args.senders)
+            }
+            Some(Command::EchidnaConfig(args)) => {
+                let (entries, suggestions) = generate_echidna_config(&args)?;
+                let mut report = format!(
+                    "Echidna config written to {} with {} filterFunctions entries:\n{}",
+                    args.output_path,
+                    entries.len(),
+                    entries.join("\n")
+                );
+                if !suggestions.is_empty() {
+                    report.push_str(&format!(
+                        "\nOnly privileged accounts called these functions, add them with -f if they are admin-only:\n{}",
+                        suggestions.join("\n")
+                    ));
+                }
+                Ok(report)
+            }
+            Some(Command::Slice(args)) => {
+                let report = slice_for_target(&args.input_path, &args.target, &args.output_path)?;
+                Ok(format!(
+                    "Sliced Etheno events written to {}\n{report}",
+                    args.output_path
+                ))
+            }
+            Some(Command::Minimize(args)) => minimize_init(&args),
+            Some(Command::Funding(args)) => {
+                check_sender_funding(&args.input_path, args.balance_addr.as_deref())
+            }
+            Some(Command::Collisions(args)) => check_echidna_collisions(&args),
+            Some(Command::Validate(args)) => validate_init(&args.input_path),
+            Some(Command::Schema(args)) => json_schema(args.kind),
+            Some(Command::Diff(args)) => diff_deployments(&args),
+            Some(Command::ImportRpcDump(args)) => {
+                let transactions = transform_rpc_dump(&args.input_paths, &args.output_path)?;
+                Ok(format!(
+                    "Transformed {transactions} transactions into {}",
+                    args.output_path
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
+    use crate::evm::create_address;
+    use crate::primitives::encode_hex;
+    use std::fs::{read_to_string, remove_dir_all, write};
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn run(args: &[&str]) -> Result<String, String> {
+        Args::parse_from([&["foundry2echidna"], args].concat()).run()
+    }
+
+    fn read_events(path: &Path) -> Vec<EthenoEvent> {
+        deserialize_etheno_events(&read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn it_should_transform_with_remapped_senders() {
+        let dir = temp_dir("foundry2echidna_cli_remap_test");
+        let output = dir.join("init.json");
+
+        let report = run(&[
+            "-i",
+            "test_json_files/test_broadcast.json",
+            "-o",
+            output.to_str().unwrap(),
+            "--remap-senders",
+        ])
+        .unwrap();
+
+        let sender = pad_short_address(DEFAULT_ECHIDNA_SENDERS[0]);
+        assert!(report.contains(&format!(
+            "Remapped 0x90f79bf6eb2c4f870365e785982e1f101e93b906 to {sender}"
+        )));
+        let events = read_events(&output);
+        assert!(events.contains(&EthenoEvent::AccountCreated {
+            address: sender.clone(),
+            balance: None,
+        }));
+        assert!(events.iter().all(|event| match event {
+            EthenoEvent::ContractCreated { from, .. } | EthenoEvent::FunctionCall { from, .. } =>
+                *from == sender,
+            _ => true,
+        }));
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_should_write_a_slice() {
+        let dir = temp_dir("foundry2echidna_cli_slice_test");
+        let output = dir.join("sliced/init.json");
+
+        run(&[
+            "slice",
+            "-i",
+            "test_json_files/test_broadcast.json",
+            "-t",
+            "PrivacyFactory",
+            "-o",
+            output.to_str().unwrap(),
+        ])
+        .unwrap();
+
+        let created: Vec<String> = read_events(&output)
+            .into_iter()
+            .filter_map(|event| match event {
+                EthenoEvent::ContractCreated {
+                    contract_address, ..
+                } => Some(contract_address),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["0x261D8c5e9742e6f7f1076Fa1F560894524e19cad"], created);
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_should_minimize_calls_that_change_nothing() {
+        let dir = temp_dir("foundry2echidna_cli_minimize_test");
+        let input = dir.join("init.json");
+        let output = dir.join("minimized.json");
+        let sender = "0x90f79bf6eb2c4f870365e785982e1f101e93b906";
+        let contract = encode_hex(&create_address(&parse_address(sender).unwrap(), 0));
+        let transaction = |data: &str| serde_json::json!({"from": sender, "gas_used": "0x0", "gas_price": "0x0", "data": data, "value": "0x0"});
+        // Deploys a contract whose code is a single STOP, then calls it.
+        let mut creation = transaction("0x6001600c60003960016000f300");
+        creation["event"] = "ContractCreated".into();
+        creation["contract_address"] = contract.clone().into();
+        let mut call = transaction("0x");
+        call["event"] = "FunctionCall".into();
+        call["to"] = contract.into();
+        let account = serde_json::json!({"event": "AccountCreated", "address": sender});
+        write_transformed_broadcast_to_file(&[account, creation, call], input.to_str().unwrap())
+            .unwrap();
+
+        let report = run(&[
+            "minimize",
+            "-i",
+            input.to_str().unwrap(),
+            "-o",
+            output.to_str().unwrap(),
+        ])
+        .unwrap();
+
+        assert_eq!("Removed 1 of 3 events in 4 replays, 2 events left", report);
+        assert!(!read_events(&output)
+            .iter()
+            .any(|event| matches!(event, EthenoEvent::FunctionCall { .. })));
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_should_validate_calls_to_factory_children_with_a_warning() {
+        let dir = temp_dir("foundry2echidna_cli_validate_test");
+        let input = dir.join("broadcast.json");
+        let output = dir.join("init.json");
+        let child = "0xf3dfb0a70010735b0a14b4a69afc242b19600049";
+        let mut broadcast: serde_json::Value =
+            serde_json::from_str(&read_to_string("test_json_files/test_broadcast.json").unwrap())
+                .unwrap();
+        let transactions = broadcast["transactions"].as_array_mut().unwrap();
+        // The fixture truncates its init code to an odd number of digits.
+        for transaction in transactions.iter_mut() {
+            transaction["transaction"]["data"] = "0x6080".into();
+        }
+        transactions.push(serde_json::json!({
+            "transactionType": "CALL",
+            "contractAddress": child,
+            "transaction": {
+                "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+                "to": child,
+                "value": "0x0",
+                "data": "0x"
+            }
+        }));
+        broadcast["receipts"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({"gasUsed": "0x5208", "effectiveGasPrice": "0x1", "logs": []}));
+        write(&input, broadcast.to_string()).unwrap();
+
+        run(&[
+            "-i",
+            input.to_str().unwrap(),
+            "-o",
+            output.to_str().unwrap(),
+        ])
+        .unwrap();
+        let report = run(&["validate", "-i", output.to_str().unwrap()]).unwrap();
+
+        assert!(report.contains("is a valid Etheno file, with 1 warnings"));
+        assert!(report.contains(&format!("warning: call to {child}")));
+        remove_dir_all(&dir).unwrap();
+    }
 }
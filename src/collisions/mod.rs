@@ -0,0 +1,228 @@
+use crate::data_model::EthenoEvent;
+use crate::evm::{create_address, Address};
+use crate::primitives::{encode_hex, parse_address, to_checksum_address};
+use std::collections::HashMap;
+
+/*//////////////////////////////////////////////////////////////
+                    ECHIDNA ADDRESS COLLISIONS
+////////////////////////////////////////////////////////////// */
+/// The accounts of an Echidna campaign, from its `deployer`, `contractAddr` and `sender` config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EchidnaAccounts {
+    pub deployer: Address,
+    pub contract_address: Address,
+    pub senders: Vec<Address>,
+}
+
+impl EchidnaAccounts {
+    /// What the address is to Echidna, if it is its deployer or one of its senders.
+    fn role(&self, address: &Address) -> Option<&'static str> {
+        match (*address == self.deployer, self.senders.contains(address)) {
+            (true, true) => Some("Echidna's deployer and sender"),
+            (true, false) => Some("Echidna's deployer"),
+            (false, true) => Some("Echidna's sender"),
+            (false, false) => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Collision {
+    /// The event creates a contract where Echidna deploys the test contract.
+    ContractAddress { event: usize },
+    /// The event creates a contract at the address of Echidna's deployer or of a sender.
+    AccountAddress { event: usize, account: Address },
+    /// The event creates a contract from one of Echidna's accounts, but at an address that
+    /// doesn't match the nonce the account has when the events replay from a fresh chain.
+    Nonce {
+        event: usize,
+        account: Address,
+        nonce: u64,
+        recorded: Address,
+    },
+}
+
+/// Checks the contracts created by the events against the test contract address and the
+/// accounts of Echidna, and the creations sent from those accounts against their nonces.
+pub fn find_collisions(
+    events: &[EthenoEvent],
+    accounts: &EchidnaAccounts,
+) -> Result<Vec<Collision>, String> {
+    let mut collisions = vec![];
+    let mut nonces: HashMap<Address, u64> = HashMap::new();
+    for (i, event) in events.iter().enumerate() {
+        let (from, created) = match event {
+            EthenoEvent::ContractCreated {
+                from,
+                contract_address,
+                ..
+            } => (from, Some(parse_address(contract_address)?)),
+            EthenoEvent::FunctionCall { from, .. } => (from, None),
+            EthenoEvent::AccountCreated { .. } | EthenoEvent::BlockMined { .. } => continue,
+        };
+        let from = parse_address(from)?;
+        let nonce = nonces.entry(from).or_default();
+        let sender_nonce = *nonce;
+        *nonce += 1;
+
+        let Some(created) = created else {
+            continue;
+        };
+        if created == accounts.contract_address {
+            collisions.push(Collision::ContractAddress { event: i });
+        }
+        if accounts.role(&created).is_some() {
+            collisions.push(Collision::AccountAddress {
+                event: i,
+                account: created,
+            });
+        }
+        if accounts.role(&from).is_some() && create_address(&from, sender_nonce) != created {
+            collisions.push(Collision::Nonce {
+                event: i,
+                account: from,
+                nonce: sender_nonce,
+                recorded: created,
+            });
+        }
+    }
+    Ok(collisions)
+}
+
+/// A warning and a proposed fix for every collision.
+pub fn collisions_report(
+    collisions: &[Collision],
+    events: &[EthenoEvent],
+    accounts: &EchidnaAccounts,
+) -> Result<String, String> {
+    if collisions.is_empty() {
+        return Ok(
+            "No collisions with Echidna's deployer, test contract address and senders".to_string(),
+        );
+    }
+    let checksum = |address: &Address| to_checksum_address(&encode_hex(address));
+    let mut lines = vec![];
+    for collision in collisions {
+        match collision {
+            Collision::ContractAddress { event } => {
+                lines.push(format!(
+                    "warning: event [{event}] creates a contract at {}, where Echidna deploys the test contract, so the campaign would not test the harness",
+                    checksum(&accounts.contract_address)?
+                ));
+                lines.push(format!(
+                    "  fix: set `contractAddr: \"{}\"` in the Echidna config",
+                    checksum(&free_contract_address(events, accounts)?)?
+                ));
+            }
+            Collision::AccountAddress { event, account } => {
+                lines.push(format!(
+                    "warning: event [{event}] creates a contract at {}, which is {}",
+                    checksum(account)?,
+                    accounts.role(account).unwrap_or("an Echidna account")
+                ));
+                lines.push(
+                    "  fix: set `deployer` and `sender` in the Echidna config to accounts the events don't create".to_string(),
+                );
+            }
+            Collision::Nonce {
+                event,
+                account,
+                nonce,
+                recorded,
+            } => {
+                lines.push(format!(
+                    "warning: event [{event}] creates {} from {}, {}, but with nonce {nonce} the creation lands at {}",
+                    checksum(recorded)?,
+                    checksum(account)?,
+                    accounts.role(account).unwrap_or("an Echidna account"),
+                    checksum(&create_address(account, *nonce))?
+                ));
+                lines.push(
+                    "  fix: regenerate init.json with `--remap-senders` so the addresses follow the nonces of Echidna's accounts".to_string(),
+                );
+            }
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+/// The first address the deployer would create that the events and Echidna's accounts don't use.
+fn free_contract_address(
+    events: &[EthenoEvent],
+    accounts: &EchidnaAccounts,
+) -> Result<Address, String> {
+    let mut used = vec![accounts.deployer, accounts.contract_address];
+    used.extend(&accounts.senders);
+    for event in events {
+        if let EthenoEvent::ContractCreated {
+            contract_address, ..
+        } = event
+        {
+            used.push(parse_address(contract_address)?);
+        }
+    }
+    Ok((0..)
+        .map(|nonce| create_address(&accounts.deployer, nonce))
+        .find(|address| !used.contains(address))
+        .unwrap_or(accounts.contract_address))
+}
+
+#[cfg(test)]
+mod collisions_tests {
+    use super::*;
+    use crate::echidna::{
+        DEFAULT_ECHIDNA_CONTRACT_ADDRESS, DEFAULT_ECHIDNA_DEPLOYER, DEFAULT_ECHIDNA_SENDERS,
+    };
+
+    fn creation(from: &Address, contract_address: &Address) -> EthenoEvent {
+        EthenoEvent::ContractCreated {
+            from: encode_hex(from),
+            contract_address: encode_hex(contract_address),
+            gas_used: "0x0".to_string(),
+            gas_price: "0x0".to_string(),
+            data: "0x".to_string(),
+            value: "0x0".to_string(),
+        }
+    }
+
+    #[test]
+    fn it_should_find_creations_at_the_test_contract_address_and_nonce_mismatches() {
+        let accounts = EchidnaAccounts {
+            deployer: parse_address(DEFAULT_ECHIDNA_DEPLOYER).unwrap(),
+            contract_address: parse_address(DEFAULT_ECHIDNA_CONTRACT_ADDRESS).unwrap(),
+            senders: DEFAULT_ECHIDNA_SENDERS
+                .iter()
+                .map(|sender| parse_address(sender).unwrap())
+                .collect(),
+        };
+        let sender = accounts.senders[0];
+        let events = vec![
+            EthenoEvent::AccountCreated {
+                address: encode_hex(&sender),
+                balance: None,
+            },
+            creation(&sender, &create_address(&sender, 0)),
+            creation(&sender, &accounts.contract_address),
+        ];
+
+        let collisions = find_collisions(&events, &accounts).unwrap();
+
+        assert_eq!(
+            vec![
+                Collision::ContractAddress { event: 2 },
+                Collision::Nonce {
+                    event: 2,
+                    account: sender,
+                    nonce: 1,
+                    recorded: accounts.contract_address,
+                },
+            ],
+            collisions
+        );
+        let report = collisions_report(&collisions, &events, &accounts).unwrap();
+        assert!(report.contains(&format!(
+            "fix: set `contractAddr: \"{}\"`",
+            to_checksum_address(&encode_hex(&create_address(&accounts.deployer, 0))).unwrap()
+        )));
+    }
+}
@@ -7,6 +7,14 @@ use serde::{Deserialize, Serialize};
 pub struct Broadcast {
     pub transactions: Vec<Transaction>,
     pub receipts: Vec<Receipt>,
+    /// Libraries linked into the deployed contracts, as `path:Name:0xaddress`.
+    #[serde(default)]
+    pub libraries: Vec<String>,
+    /// Chain ID the broadcast was sent to. Missing in broadcasts of older Foundry versions.
+    pub chain: Option<u64>,
+    /// Git commit of the project when the script was run.
+    pub commit: Option<String>,
+    pub timestamp: Option<u64>,
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
@@ -15,7 +23,28 @@ pub struct Transaction {
     #[serde(rename(serialize = "event"))]
     pub transaction_type: String,
     pub contract_address: String,
+    #[serde(default, skip_serializing)]
+    pub contract_name: Option<String>,
+    /// Signature of the called function, e.g. `setNumber(uint256)`, as resolved by Foundry.
+    #[serde(default, skip_serializing)]
+    pub function: Option<String>,
+    /// Human readable call or constructor arguments, as rendered by Foundry.
+    #[serde(default, skip_serializing)]
+    pub arguments: Option<Vec<String>>,
     pub transaction: TransactionDetails,
+    /// Contracts created by the transaction itself, e.g. the children deployed by a factory.
+    #[serde(default, skip_serializing)]
+    pub additional_contracts: Vec<AdditionalContract>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AdditionalContract {
+    /// `CREATE` or `CREATE2`.
+    pub transaction_type: String,
+    pub contract_name: Option<String>,
+    pub address: String,
+    pub init_code: String,
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
@@ -24,6 +53,16 @@ pub struct Receipt {
     pub gas_used: String,
     #[serde(rename(serialize = "gas_price"))]
     pub effective_gas_price: String,
+    /// Events emitted by the transaction. Etheno does not record them.
+    #[serde(default, skip_serializing)]
+    pub logs: Vec<Log>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Log {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
@@ -33,3 +72,71 @@ pub struct TransactionDetails {
     pub value: String,
     pub data: String,
 }
+
+/*//////////////////////////////////////////////////////////////
+                    JSON-RPC DATA MODEL STRUCTS
+////////////////////////////////////////////////////////////// */
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct RpcBlock {
+    pub number: String,
+    pub timestamp: String,
+    pub transactions: Vec<RpcTransaction>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransaction {
+    pub hash: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub value: String,
+    pub input: String,
+    pub gas_price: Option<String>,
+    pub block_number: Option<String>,
+    pub transaction_index: Option<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcReceipt {
+    pub transaction_hash: String,
+    pub contract_address: Option<String>,
+    pub gas_used: String,
+    pub effective_gas_price: Option<String>,
+    pub status: Option<String>,
+    #[serde(default)]
+    pub logs: Vec<Log>,
+}
+
+/*//////////////////////////////////////////////////////////////
+                        ETHENO EVENT MODEL
+////////////////////////////////////////////////////////////// */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event")]
+pub enum EthenoEvent {
+    AccountCreated {
+        address: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        balance: Option<String>,
+    },
+    ContractCreated {
+        from: String,
+        contract_address: String,
+        gas_used: String,
+        gas_price: String,
+        data: String,
+        value: String,
+    },
+    FunctionCall {
+        from: String,
+        to: String,
+        gas_used: String,
+        gas_price: String,
+        data: String,
+        value: String,
+    },
+    BlockMined {
+        number: u64,
+        timestamp: u64,
+    },
+}
@@ -0,0 +1,300 @@
+use crate::abi::{decode, function_selector, AbiEntry, AbiType, AbiValue};
+use crate::artifacts::ContractArtifact;
+use crate::data_model::{Broadcast, Transaction};
+use crate::primitives::{
+    decode_hex, encode_hex, normalize_address, parse_word, to_checksum_address,
+};
+use crate::registry::ContractRegistry;
+use serde_json::{json, Value};
+
+/*//////////////////////////////////////////////////////////////
+                    CALLDATA DECODING
+////////////////////////////////////////////////////////////// */
+#[derive(Debug, PartialEq, Eq)]
+pub struct DecodedTransaction {
+    pub contract: Option<String>,
+    /// EIP-55 checksummed address of the created or called contract.
+    pub address: String,
+    /// EIP-55 checksummed sender.
+    pub from: String,
+    /// Sent value in wei, in decimal.
+    pub value: String,
+    pub is_creation: bool,
+    /// Canonical signature, e.g. `setNumber(uint256)` or `constructor(address)`.
+    pub function: Option<String>,
+    /// Arguments rendered like Solidity literals, `None` if they could not be decoded.
+    pub arguments: Option<Vec<String>>,
+    /// First four bytes of the calldata of a call that matches no known function.
+    pub selector: Option<String>,
+}
+
+impl DecodedTransaction {
+    /// One line listing, e.g. `Counter.setNumber(42) from 0x90F7... value 0`.
+    pub fn listing_line(&self) -> String {
+        let arguments = match &self.arguments {
+            Some(arguments) => arguments.join(", "),
+            None => "?".to_string(),
+        };
+        if self.is_creation {
+            return format!(
+                "new {}({arguments}) at {} from {} value {}",
+                self.contract.as_deref().unwrap_or("<unknown>"),
+                self.address,
+                self.from,
+                self.value
+            );
+        }
+        let target = self.contract.as_deref().unwrap_or(&self.address);
+        let call = match (&self.function, &self.selector) {
+            (Some(function), _) => format!("{}({arguments})", function_name(function)),
+            (None, Some(selector)) => format!("<unknown {selector}>"),
+            (None, None) => "<receive>".to_string(),
+        };
+        format!("{target}.{call} from {} value {}", self.from, self.value)
+    }
+
+    /// Annotation added to the Etheno events next to the raw data.
+    pub fn annotation(&self) -> Value {
+        json!({
+            "contract": self.contract,
+            "function": self.function,
+            "arguments": self.arguments,
+        })
+    }
+}
+
+/// Decodes the calldata and constructor arguments of every transaction of the broadcast with
+/// the ABIs of the artifacts. Falls back to the `function` and `arguments` recorded by Foundry
+/// when no artifact matches.
+pub fn decode_broadcast(
+    broadcast: &Broadcast,
+    artifacts: &[ContractArtifact],
+) -> Result<Vec<DecodedTransaction>, String> {
+    let registry = ContractRegistry::from_broadcast(broadcast)?;
+    broadcast
+        .transactions
+        .iter()
+        .map(|transaction| decode_transaction(transaction, &registry, artifacts))
+        .collect()
+}
+
+fn decode_transaction(
+    transaction: &Transaction,
+    registry: &ContractRegistry,
+    artifacts: &[ContractArtifact],
+) -> Result<DecodedTransaction, String> {
+    let details = &transaction.transaction;
+    let is_creation = transaction.transaction_type != "CALL";
+    let address = match (&details.to, is_creation) {
+        (Some(to), false) => to.clone(),
+        _ => transaction.contract_address.clone(),
+    };
+    let normalized_address = normalize_address(&address)?;
+    let contract = registry
+        .contracts
+        .iter()
+        .find(|contract| normalize_address(&contract.address).as_ref() == Ok(&normalized_address))
+        .and_then(|contract| contract.name.clone())
+        .or(transaction.contract_name.clone());
+    // Malformed calldata can't be decoded, but its transaction is still listed.
+    let data = decode_hex(&details.data).unwrap_or_default();
+
+    let (function, arguments, selector) = if is_creation {
+        // The deployment proxy receives the salt in front of the init code.
+        let init_code = match transaction.transaction_type.as_str() {
+            "CREATE2" => data.get(32..).unwrap_or_default(),
+            _ => &data[..],
+        };
+        let artifact = contract
+            .as_deref()
+            .and_then(|name| artifacts.iter().find(|artifact| artifact.name == name));
+        match artifact.and_then(|artifact| decode_constructor(artifact, init_code)) {
+            Some((function, arguments)) => (Some(function), Some(arguments), None),
+            None => (None, transaction.arguments.clone(), None),
+        }
+    } else {
+        // Artifacts of the called contract are tried first, since selectors may collide.
+        let candidates = artifacts
+            .iter()
+            .filter(|artifact| contract.as_deref() == Some(artifact.name.as_str()))
+            .chain(artifacts.iter());
+        let decoded = candidates
+            .flat_map(|artifact| &artifact.abi)
+            .find_map(|entry| decode_function_call(entry, &data));
+        match (decoded, &transaction.function) {
+            (Some((function, arguments)), _) => (Some(function), Some(arguments), None),
+            (None, Some(function)) => (
+                // Foundry may append the return types, like `balanceOf(address):(uint256)`.
+                Some(function.split(':').next().unwrap_or_default().to_string()),
+                transaction.arguments.clone(),
+                None,
+            ),
+            (None, None) if data.len() < 4 => (None, Some(vec![]), None),
+            (None, None) => (None, None, Some(encode_hex(&data[..4]))),
+        }
+    };
+
+    Ok(DecodedTransaction {
+        contract,
+        address: to_checksum_address(&address)?,
+        from: to_checksum_address(&details.from)?,
+        value: AbiValue::Uint(256, parse_word(&details.value)?).display(),
+        is_creation,
+        function,
+        arguments,
+        selector,
+    })
+}
+
+/// The constructor signature, and the arguments that follow the artifact's bytecode in
+/// `init_code` if they decode.
+pub fn decode_constructor(
+    artifact: &ContractArtifact,
+    init_code: &[u8],
+) -> Option<(String, Vec<String>)> {
+    let types = match artifact
+        .abi
+        .iter()
+        .find(|entry| entry.kind == "constructor")
+    {
+        Some(constructor) => constructor.input_types().ok()?,
+        None => vec![],
+    };
+    let arguments = decode(&types, init_code.get(artifact.bytecode_length()..)?).ok()?;
+    Some((
+        format!("constructor({})", canonical_types(&types)),
+        arguments.iter().map(AbiValue::display).collect(),
+    ))
+}
+
+fn decode_function_call(entry: &AbiEntry, calldata: &[u8]) -> Option<(String, Vec<String>)> {
+    if entry.kind != "function" || calldata.len() < 4 {
+        return None;
+    }
+    let signature = entry.signature().ok()?;
+    if function_selector(&signature) != calldata[..4] {
+        return None;
+    }
+    let arguments = decode(&entry.input_types().ok()?, &calldata[4..]).ok()?;
+    Some((signature, arguments.iter().map(AbiValue::display).collect()))
+}
+
+fn canonical_types(types: &[AbiType]) -> String {
+    types
+        .iter()
+        .map(AbiType::canonical)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// `setNumber(uint256)` -> `setNumber`.
+fn function_name(signature: &str) -> &str {
+    signature.split('(').next().unwrap_or(signature)
+}
+
+/// Adds the decoded transactions to their serialized events, under a `decoded` field.
+pub fn annotate_events(events: Vec<Value>, decoded: &[DecodedTransaction]) -> Vec<Value> {
+    events
+        .into_iter()
+        .zip(decoded)
+        .map(|(mut event, decoded)| {
+            if let Some(event) = event.as_object_mut() {
+                event.insert("decoded".to_string(), decoded.annotation());
+            }
+            event
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod decoder_tests {
+    use super::*;
+    use crate::abi::AbiParam;
+    use crate::deserialization::deserialize_broadcast;
+    use crate::file_handling::read_broadcast_file;
+    use crate::serialization::serialize_broadcast;
+
+    fn broadcast() -> Broadcast {
+        deserialize_broadcast(&read_broadcast_file("test_json_files/test_broadcast.json").unwrap())
+            .unwrap()
+    }
+
+    fn entry(kind: &str, name: &str, inputs: &[&str]) -> AbiEntry {
+        AbiEntry {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            inputs: inputs
+                .iter()
+                .map(|input| AbiParam {
+                    kind: input.to_string(),
+                    components: vec![],
+                    indexed: false,
+                })
+                .collect(),
+            anonymous: false,
+        }
+    }
+
+    #[test]
+    fn it_should_fall_back_to_recorded_function_and_arguments() {
+        let decoded = decode_broadcast(&broadcast(), &[]).unwrap();
+
+        assert_eq!(
+            "Ethernaut.registerLevel(0x261D8c5e9742e6f7f1076Fa1F560894524e19cad) from 0x90F79bf6EB2c4f870365E785982E1f101E93b906 value 0",
+            decoded[2].listing_line()
+        );
+        assert_eq!(
+            "new Ethernaut(?) at 0x057ef64E23666F000b34aE31332854aCBd1c8544 from 0x90F79bf6EB2c4f870365E785982E1f101E93b906 value 0",
+            decoded[0].listing_line()
+        );
+    }
+
+    #[test]
+    fn it_should_decode_calls_and_constructors_with_artifact_abis() {
+        let mut broadcast = broadcast();
+        for transaction in &mut broadcast.transactions {
+            transaction.function = None;
+            transaction.arguments = None;
+        }
+        broadcast.transactions[0].transaction.data = format!(
+            "0x6080{}",
+            "000000000000000000000000000000000000000000000000000000000000002a"
+        );
+        let artifacts = [
+            ContractArtifact {
+                name: "Ethernaut".to_string(),
+                abi: vec![
+                    entry("constructor", "", &["uint256"]),
+                    entry("function", "createLevelInstance", &["address"]),
+                ],
+                bytecode: "6080".to_string(),
+            },
+            ContractArtifact {
+                name: "PrivacyFactory".to_string(),
+                abi: vec![],
+                bytecode: "60".to_string(),
+            },
+        ];
+
+        let decoded = decode_broadcast(&broadcast, &artifacts).unwrap();
+
+        assert_eq!(
+            Some("constructor(uint256)".to_string()),
+            decoded[0].function
+        );
+        assert_eq!(Some(vec!["42".to_string()]), decoded[0].arguments);
+        assert_eq!(
+            Some("createLevelInstance(address)".to_string()),
+            decoded[3].function
+        );
+        let events = annotate_events(serialize_broadcast(broadcast).unwrap(), &decoded);
+        assert_eq!(
+            json!({
+                "contract": "Ethernaut",
+                "function": "createLevelInstance(address)",
+                "arguments": ["0x261D8c5e9742e6f7f1076Fa1F560894524e19cad"],
+            }),
+            events[3]["decoded"]
+        );
+    }
+}
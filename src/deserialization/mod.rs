@@ -1,4 +1,7 @@
-use crate::data_model::Broadcast;
+use crate::data_model::{
+    Broadcast, EthenoEvent, Receipt, RpcReceipt, RpcTransaction, Transaction, TransactionDetails,
+};
+use serde_json::Value;
 /*//////////////////////////////////////////////////////////////
                     DESERIALIZATION FUNCTIONS
 ////////////////////////////////////////////////////////////// */
@@ -8,10 +11,70 @@ pub fn deserialize_broadcast(broadcast_to_deserialize: &str) -> Result<Broadcast
     Ok(broadcast)
 }
 
+pub fn deserialize_etheno_events(events_to_deserialize: &str) -> Result<Vec<EthenoEvent>, String> {
+    let events: Vec<EthenoEvent> = serde_json::from_str(events_to_deserialize)
+        .map_err(|err| format!("Failed to deserialize Etheno events: {err}"))?;
+    Ok(events)
+}
+
+pub fn etheno_events_from_values(events: Vec<Value>) -> Result<Vec<EthenoEvent>, String> {
+    events
+        .into_iter()
+        .filter(|event| !event.is_null())
+        .map(|event| {
+            serde_json::from_value(event)
+                .map_err(|err| format!("Failed to deserialize Etheno event: {err}"))
+        })
+        .collect()
+}
+
+/// Maps a JSON-RPC transaction and its receipt to the broadcast model.
+/// A transaction without `to` is a contract creation, deployed at the receipt's `contractAddress`.
+pub fn broadcast_transaction_from_rpc(
+    transaction: RpcTransaction,
+    receipt: RpcReceipt,
+) -> Result<(Transaction, Receipt), String> {
+    let (transaction_type, contract_address) = match (&transaction.to, receipt.contract_address) {
+        (None, Some(contract_address)) => ("CREATE", contract_address),
+        (None, None) => {
+            return Err(format!(
+                "Contract creation {} has no contractAddress in its receipt",
+                transaction.hash
+            ))
+        }
+        (Some(to), _) => ("CALL", to.clone()),
+    };
+    let effective_gas_price = receipt
+        .effective_gas_price
+        .or(transaction.gas_price)
+        .ok_or(format!("Transaction {} has no gas price", transaction.hash))?;
+    Ok((
+        Transaction {
+            transaction_type: transaction_type.to_string(),
+            contract_address,
+            contract_name: None,
+            additional_contracts: vec![],
+            function: None,
+            arguments: None,
+            transaction: TransactionDetails {
+                from: transaction.from,
+                to: transaction.to,
+                value: transaction.value,
+                data: transaction.input,
+            },
+        },
+        Receipt {
+            gas_used: receipt.gas_used,
+            effective_gas_price,
+            logs: receipt.logs,
+        },
+    ))
+}
+
 #[cfg(test)]
 mod deserialization_tests {
     use super::*;
-    use crate::data_model::{Receipt, Transaction, TransactionDetails};
+    use crate::data_model::Log;
 
     #[test]
     fn it_should_deserialize_broadcast() {
@@ -116,6 +179,10 @@ mod deserialization_tests {
         let expected_tx1 = Transaction {
             transaction_type: "CREATE".to_string(),
             contract_address: "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad".to_string(),
+            contract_name: Some("PrivacyFactory".to_string()),
+            additional_contracts: vec![],
+            function: None,
+            arguments: None,
             transaction: TransactionDetails {
                 from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
                 to: None,
@@ -127,6 +194,12 @@ mod deserialization_tests {
         let expected_tx2 = Transaction {
             transaction_type: "CALL".to_string(),
             contract_address: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+            contract_name: Some("Ethernaut".to_string()),
+            additional_contracts: vec![],
+            function: Some("registerLevel(address)".to_string()),
+            arguments: Some(vec![
+                "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad".to_string()
+            ]),
             transaction: TransactionDetails {
                 from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
                 to: Some("0x057ef64e23666f000b34ae31332854acbd1c8544".to_string()),
@@ -137,18 +210,132 @@ mod deserialization_tests {
         let expected_receipt1 = Receipt {
             gas_used: "0x71658".to_string(),
             effective_gas_price: "0xe0fed783".to_string(),
+            logs: vec![Log {
+                address: "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad".to_string(),
+                topics: vec![
+                    "0x8be0079c531659141344cd1fd0a4f28419497f9722a3daafe3b4186f6b6457e0"
+                        .to_string(),
+                    "0x0000000000000000000000000000000000000000000000000000000000000000"
+                        .to_string(),
+                    "0x00000000000000000000000090f79bf6eb2c4f870365e785982e1f101e93b906"
+                        .to_string(),
+                ],
+                data: "0x".to_string(),
+            }],
         };
         let expected_receipt2 = Receipt {
             gas_used: "0xb3bd".to_string(),
             effective_gas_price: "0xe0fed783".to_string(),
+            logs: vec![],
         };
         let transactions = vec![expected_tx1, expected_tx2];
         let receipts = vec![expected_receipt1, expected_receipt2];
         let expected_broadcast = Broadcast {
             transactions,
             receipts,
+            libraries: vec![],
+            chain: None,
+            commit: Some("cba0070".to_string()),
+            timestamp: Some(1668342002),
         };
         let deserialization_result = deserialize_broadcast(broadcast_to_deserialize).unwrap();
         assert_eq!(expected_broadcast, deserialization_result);
     }
+
+    #[test]
+    fn it_should_deserialize_etheno_events() {
+        let events_to_deserialize = r#"[
+    {
+        "event": "AccountCreated",
+        "address": "5409ed021d9299bf6814279a6a1411a7e866a631"
+    },
+    {
+        "event": "ContractCreated",
+        "from": "0x5409ed021d9299bf6814279a6a1411a7e866a631",
+        "contract_address": "0x871dd7c2b4b25e1aa18728e9d5f2af4c4e431f5c",
+        "gas_used": "0x3d2723",
+        "gas_price": "0x4a817c800",
+        "data": "0x610120604052",
+        "value": "0x0"
+    },
+    {
+        "event": "BlockMined",
+        "number": 2,
+        "timestamp": 1668342002
+    }
+]"#;
+        let expected_events = vec![
+            EthenoEvent::AccountCreated {
+                address: "5409ed021d9299bf6814279a6a1411a7e866a631".to_string(),
+                balance: None,
+            },
+            EthenoEvent::ContractCreated {
+                from: "0x5409ed021d9299bf6814279a6a1411a7e866a631".to_string(),
+                contract_address: "0x871dd7c2b4b25e1aa18728e9d5f2af4c4e431f5c".to_string(),
+                gas_used: "0x3d2723".to_string(),
+                gas_price: "0x4a817c800".to_string(),
+                data: "0x610120604052".to_string(),
+                value: "0x0".to_string(),
+            },
+            EthenoEvent::BlockMined {
+                number: 2,
+                timestamp: 1668342002,
+            },
+        ];
+        let deserialization_result = deserialize_etheno_events(events_to_deserialize).unwrap();
+        assert_eq!(expected_events, deserialization_result);
+    }
+
+    #[test]
+    fn it_should_map_rpc_contract_creation_to_broadcast_transaction() {
+        let transaction: RpcTransaction = serde_json::from_str(
+            r#"{
+            "hash": "0x5370406a7d060079764126708230356640e3494965321ab622842123ebb71052",
+            "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+            "to": null,
+            "value": "0x0",
+            "input": "0x6080",
+            "gasPrice": "0x1"
+        }"#,
+        )
+        .unwrap();
+        let receipt: RpcReceipt = serde_json::from_str(
+            r#"{
+            "transactionHash": "0x5370406a7d060079764126708230356640e3494965321ab622842123ebb71052",
+            "contractAddress": "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad",
+            "gasUsed": "0x71658",
+            "effectiveGasPrice": "0xe0fed783",
+            "status": "0x1"
+        }"#,
+        )
+        .unwrap();
+
+        let (transaction, receipt) = broadcast_transaction_from_rpc(transaction, receipt).unwrap();
+
+        assert_eq!(
+            Transaction {
+                transaction_type: "CREATE".to_string(),
+                contract_address: "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad".to_string(),
+                contract_name: None,
+                additional_contracts: vec![],
+                function: None,
+                arguments: None,
+                transaction: TransactionDetails {
+                    from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+                    to: None,
+                    value: "0x0".to_string(),
+                    data: "0x6080".to_string(),
+                },
+            },
+            transaction
+        );
+        assert_eq!(
+            Receipt {
+                gas_used: "0x71658".to_string(),
+                effective_gas_price: "0xe0fed783".to_string(),
+                logs: vec![],
+            },
+            receipt
+        );
+    }
 }
@@ -0,0 +1,499 @@
+use crate::artifacts::ContractArtifact;
+use crate::data_model::{Broadcast, EthenoEvent, Receipt, Transaction, TransactionDetails};
+use crate::decoder::{decode_broadcast, decode_constructor, DecodedTransaction};
+use crate::primitives::{decode_hex, normalize_address, parse_word, to_checksum_address};
+use crate::registry::ContractRegistry;
+use std::collections::HashMap;
+
+/*//////////////////////////////////////////////////////////////
+                        SEMANTIC DIFF
+////////////////////////////////////////////////////////////// */
+/// A created contract, identified across the two sides by its name and creation order.
+#[derive(Debug)]
+struct Deployment {
+    /// `Name`, or `Name #2` for the second contract of the same name. Contracts without a name,
+    /// like those of an `init.json` whose init code matches no artifact, are identified by their
+    /// deployer and its nonce, like `<created by 0x... at nonce 2>`.
+    key: String,
+    /// Lowercase address.
+    address: String,
+    name: Option<String>,
+    /// Lowercase hex init code, without the CREATE2 salt.
+    init_code: String,
+    arguments: Option<Vec<String>>,
+    /// Length of the artifact's bytecode in bytes, where the constructor arguments start.
+    bytecode_length: Option<usize>,
+}
+
+#[derive(Debug)]
+struct Call {
+    /// Key of the called deployment, or the checksummed address of an external contract.
+    callee: String,
+    /// Lowercase hex calldata.
+    data: String,
+    value: [u8; 32],
+    /// The decoded call, if its function is known.
+    listing: Option<String>,
+}
+
+impl Call {
+    fn calldata_line(&self) -> String {
+        format!("{} calldata {}", self.callee, self.data)
+    }
+}
+
+/// The broadcast form of Etheno events, so that `init.json` files are diffed like broadcasts.
+/// Contract names are unknown, and calls can only be decoded with artifacts.
+pub fn broadcast_from_etheno_events(events: Vec<EthenoEvent>) -> Broadcast {
+    let mut broadcast = Broadcast {
+        transactions: vec![],
+        receipts: vec![],
+        libraries: vec![],
+        chain: None,
+        commit: None,
+        timestamp: None,
+    };
+    for event in events {
+        let (transaction_type, contract_address, from, to, gas_used, gas_price, data, value) =
+            match event {
+                EthenoEvent::ContractCreated {
+                    from,
+                    contract_address,
+                    gas_used,
+                    gas_price,
+                    data,
+                    value,
+                } => (
+                    "CREATE",
+                    contract_address,
+                    from,
+                    None,
+                    gas_used,
+                    gas_price,
+                    data,
+                    value,
+                ),
+                EthenoEvent::FunctionCall {
+                    from,
+                    to,
+                    gas_used,
+                    gas_price,
+                    data,
+                    value,
+                } => (
+                    "CALL",
+                    to.clone(),
+                    from,
+                    Some(to),
+                    gas_used,
+                    gas_price,
+                    data,
+                    value,
+                ),
+                EthenoEvent::AccountCreated { .. } | EthenoEvent::BlockMined { .. } => continue,
+            };
+        broadcast.transactions.push(Transaction {
+            transaction_type: transaction_type.to_string(),
+            contract_address,
+            contract_name: None,
+            function: None,
+            arguments: None,
+            transaction: TransactionDetails {
+                from,
+                to,
+                value,
+                data,
+            },
+            additional_contracts: vec![],
+        });
+        broadcast.receipts.push(Receipt {
+            gas_used,
+            effective_gas_price: gas_price,
+            logs: vec![],
+        });
+    }
+    broadcast
+}
+
+/// Compares two broadcasts by what they deploy and call rather than line by line.
+///
+/// Contracts are aligned by name and creation order, and calls by callee and call order. With
+/// `artifacts`, unnamed contracts are named after the artifact whose bytecode their init code
+/// starts with, and the others are aligned by deployer and nonce. The
+/// contracts that moved are reported as address shifts, and their old addresses are replaced
+/// by the new ones before comparing init code, arguments and calldata, so that a shift alone
+/// doesn't show up as a change of every call that passes the address. With `artifacts`,
+/// calldata is decoded and bytecode changes are told apart from constructor argument changes.
+///
+/// Lines start with `+` for additions, `-` for removals and `~` for changes.
+pub fn diff_broadcasts(
+    old: &Broadcast,
+    new: &Broadcast,
+    artifacts: &[ContractArtifact],
+) -> Result<Vec<String>, String> {
+    let old_decoded = decode_broadcast(old, artifacts)?;
+    let new_decoded = decode_broadcast(new, artifacts)?;
+    let old_deployments = deployments(old, &old_decoded, artifacts)?;
+    let new_deployments = deployments(new, &new_decoded, artifacts)?;
+
+    let mut shifts: Vec<(String, String)> = vec![];
+    for deployment in &new_deployments {
+        if let Some(old_deployment) = old_deployments
+            .iter()
+            .find(|old_deployment| old_deployment.key == deployment.key)
+        {
+            if old_deployment.address != deployment.address {
+                shifts.push((old_deployment.address.clone(), deployment.address.clone()));
+            }
+        }
+    }
+
+    let mut lines = vec![];
+    for deployment in &new_deployments {
+        let Some(old_deployment) = old_deployments
+            .iter()
+            .find(|old_deployment| old_deployment.key == deployment.key)
+        else {
+            lines.push(format!(
+                "+ deployment {} at {}",
+                deployment.key,
+                to_checksum_address(&deployment.address)?
+            ));
+            continue;
+        };
+        lines.extend(deployment_changes(old_deployment, deployment, &shifts)?);
+    }
+    for old_deployment in &old_deployments {
+        if !new_deployments
+            .iter()
+            .any(|deployment| deployment.key == old_deployment.key)
+        {
+            lines.push(format!(
+                "- deployment {} at {}",
+                old_deployment.key,
+                to_checksum_address(&old_deployment.address)?
+            ));
+        }
+    }
+
+    let old_calls = calls(old, &old_decoded, &old_deployments)?;
+    let new_calls = calls(new, &new_decoded, &new_deployments)?;
+    let mut callees: Vec<&str> = vec![];
+    for call in new_calls.iter().chain(&old_calls) {
+        if !callees.contains(&call.callee.as_str()) {
+            callees.push(&call.callee);
+        }
+    }
+    for callee in callees {
+        let old_calls: Vec<&Call> = old_calls.iter().filter(|c| c.callee == callee).collect();
+        let new_calls: Vec<&Call> = new_calls.iter().filter(|c| c.callee == callee).collect();
+        for k in 0..old_calls.len().max(new_calls.len()) {
+            match (old_calls.get(k), new_calls.get(k)) {
+                (None, Some(call)) => lines.push(format!(
+                    "+ call #{} {}",
+                    k + 1,
+                    call.listing.clone().unwrap_or(call.calldata_line())
+                )),
+                (Some(call), None) => lines.push(format!(
+                    "- call #{} {}",
+                    k + 1,
+                    call.listing.clone().unwrap_or(call.calldata_line())
+                )),
+                (Some(old_call), Some(call)) => {
+                    if shifted(&old_call.data, &shifts) == call.data && old_call.value == call.value
+                    {
+                        continue;
+                    }
+                    let (old_line, line) = match (&old_call.listing, &call.listing) {
+                        (Some(old_listing), Some(listing))
+                            if shifted(old_listing, &shifts) != listing.to_lowercase() =>
+                        {
+                            (old_listing.clone(), listing.clone())
+                        }
+                        _ => (old_call.calldata_line(), call.calldata_line()),
+                    };
+                    lines.push(format!("~ call #{} {old_line} -> {line}", k + 1));
+                }
+                (None, None) => {}
+            }
+        }
+    }
+    Ok(lines)
+}
+
+fn deployment_changes(
+    old: &Deployment,
+    new: &Deployment,
+    shifts: &[(String, String)],
+) -> Result<Vec<String>, String> {
+    let mut lines = vec![];
+    if old.address != new.address {
+        lines.push(format!(
+            "~ {}: address {} -> {}",
+            new.key,
+            to_checksum_address(&old.address)?,
+            to_checksum_address(&new.address)?
+        ));
+    }
+    let arguments_changed = match (&old.arguments, &new.arguments) {
+        (Some(old_arguments), Some(arguments)) => {
+            shifted(&old_arguments.join(", "), shifts) != arguments.join(", ").to_lowercase()
+        }
+        _ => false,
+    };
+    let arguments_line = || {
+        let render = |arguments: &Option<Vec<String>>| match arguments {
+            Some(arguments) => arguments.join(", "),
+            None => "?".to_string(),
+        };
+        format!(
+            "~ {}: constructor arguments ({}) -> ({})",
+            new.key,
+            render(&old.arguments),
+            render(&new.arguments)
+        )
+    };
+    let old_init_code = shifted(&old.init_code, shifts);
+    if old_init_code == new.init_code {
+        return Ok(lines);
+    }
+
+    let bytecode_length = new
+        .bytecode_length
+        .or(old.bytecode_length)
+        .map(|length| 2 + 2 * length);
+    match bytecode_length {
+        Some(length) if old_init_code.len() >= length && new.init_code.len() >= length => {
+            if old_init_code[..length] != new.init_code[..length] {
+                lines.push(format!("~ {}: bytecode changed", new.key));
+            }
+            if old_init_code[length..] != new.init_code[length..] {
+                lines.push(arguments_line());
+            }
+        }
+        // Without a name, the contract is aligned by its creation, so its code changed.
+        _ if new.name.is_none() => lines.push(format!("~ {}: bytecode changed", new.key)),
+        _ => {
+            lines.push(format!("~ {}: init code changed", new.key));
+            if arguments_changed {
+                lines.push(arguments_line());
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// Lowercase `text`, with the old addresses of the shifted contracts replaced by the new ones.
+fn shifted(text: &str, shifts: &[(String, String)]) -> String {
+    shifts.iter().fold(text.to_lowercase(), |text, (old, new)| {
+        text.replace(&old[2..], &new[2..])
+    })
+}
+
+fn deployments(
+    broadcast: &Broadcast,
+    decoded: &[DecodedTransaction],
+    artifacts: &[ContractArtifact],
+) -> Result<Vec<Deployment>, String> {
+    let registry = ContractRegistry::from_broadcast(broadcast)?;
+    let mut sender_nonces: Vec<u64> = vec![];
+    let mut nonces: HashMap<String, u64> = HashMap::new();
+    for transaction in &broadcast.transactions {
+        let nonce = nonces
+            .entry(normalize_address(&transaction.transaction.from)?)
+            .or_default();
+        sender_nonces.push(*nonce);
+        *nonce += 1;
+    }
+    // Factories have nonce 1 after their deployment (EIP-161).
+    let mut factory_nonces: HashMap<String, u64> = HashMap::new();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut deployments = vec![];
+    for record in &registry.contracts {
+        let deployer = normalize_address(&record.deployer)?;
+        let nonce = if record.from_factory {
+            let nonce = factory_nonces.entry(deployer.clone()).or_insert(1);
+            *nonce += 1;
+            *nonce - 1
+        } else {
+            sender_nonces[record.transaction_index]
+        };
+        let transaction = &broadcast.transactions[record.transaction_index];
+        let address = normalize_address(&record.address)?;
+        let (init_code, arguments) = if record.from_factory {
+            let child = transaction
+                .additional_contracts
+                .iter()
+                .find(|child| normalize_address(&child.address).as_ref() == Ok(&address));
+            (child.map(|child| child.init_code.clone()), None)
+        } else {
+            let data = &transaction.transaction.data;
+            let init_code = match record.creation_kind.as_str() {
+                // The deployment proxy receives the salt in front of the init code.
+                "CREATE2" => data.trim_start_matches("0x").get(64..).unwrap_or_default(),
+                _ => data.trim_start_matches("0x"),
+            };
+            (
+                Some(format!("0x{init_code}")),
+                decoded[record.transaction_index].arguments.clone(),
+            )
+        };
+
+        let init_code = init_code.unwrap_or_default().to_lowercase();
+        let named = |artifact: &&ContractArtifact| {
+            record
+                .name
+                .as_ref()
+                .is_none_or(|name| artifact.name == *name)
+        };
+        let artifact = artifacts
+            .iter()
+            .filter(named)
+            .filter(|artifact| artifact.matches_init_code(&init_code))
+            .max_by_key(|artifact| artifact.bytecode_length())
+            .or_else(|| {
+                record
+                    .name
+                    .as_ref()
+                    .and_then(|_| artifacts.iter().find(named))
+            });
+        let name = record
+            .name
+            .clone()
+            .or(artifact.map(|artifact| artifact.name.clone()));
+        let arguments = arguments.or_else(|| {
+            let (_, arguments) = decode_constructor(artifact?, &decode_hex(&init_code).ok()?)?;
+            Some(arguments)
+        });
+        let label = match &name {
+            Some(name) => name.clone(),
+            None => format!(
+                "<created by {} at nonce {nonce}>",
+                to_checksum_address(&deployer)?
+            ),
+        };
+        let occurrence = occurrences.entry(label.clone()).or_default();
+        *occurrence += 1;
+        deployments.push(Deployment {
+            key: match *occurrence {
+                1 => label,
+                occurrence => format!("{label} #{occurrence}"),
+            },
+            address,
+            name,
+            init_code,
+            arguments,
+            bytecode_length: artifact.map(ContractArtifact::bytecode_length),
+        });
+    }
+    Ok(deployments)
+}
+
+fn calls(
+    broadcast: &Broadcast,
+    decoded: &[DecodedTransaction],
+    deployments: &[Deployment],
+) -> Result<Vec<Call>, String> {
+    let mut calls = vec![];
+    for (transaction, decoded) in broadcast.transactions.iter().zip(decoded) {
+        if transaction.transaction_type != "CALL" {
+            continue;
+        }
+        let details = &transaction.transaction;
+        let to = normalize_address(details.to.as_deref().unwrap_or_default())?;
+        let callee = match deployments
+            .iter()
+            .find(|deployment| deployment.address == to)
+        {
+            Some(deployment) => deployment.key.clone(),
+            None => to_checksum_address(&to)?,
+        };
+        calls.push(Call {
+            callee,
+            data: details.data.to_lowercase(),
+            value: parse_word(&details.value)?,
+            listing: decoded.function.is_some().then(|| decoded.listing_line()),
+        });
+    }
+    Ok(calls)
+}
+
+#[cfg(test)]
+mod diff_tests {
+    use super::*;
+    use crate::deserialization::{deserialize_broadcast, etheno_events_from_values};
+    use crate::file_handling::read_broadcast_file;
+    use crate::serialization::{add_account_created_events, serialize_broadcast};
+
+    fn fixture() -> Broadcast {
+        deserialize_broadcast(&read_broadcast_file("test_json_files/test_broadcast.json").unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn it_should_report_shifts_and_changes_but_not_shifted_calldata() {
+        let old = fixture();
+        let mut new = fixture();
+        let shifted_factory = "0x1111111111111111111111111111111111111111";
+        new.transactions[1].contract_address = shifted_factory.to_string();
+        let level_instance = &mut new.transactions[3].transaction;
+        level_instance.data = level_instance.data.replace(
+            "261d8c5e9742e6f7f1076fa1f560894524e19cad",
+            &shifted_factory[2..],
+        );
+        for transaction in &mut new.transactions[2..] {
+            transaction.arguments = Some(vec![to_checksum_address(shifted_factory).unwrap()]);
+        }
+        new.transactions[2].transaction.data = "0x202024".to_string();
+
+        assert_eq!(
+            vec![
+                "~ PrivacyFactory: address 0x261D8c5e9742e6f7f1076Fa1F560894524e19cad -> 0x1111111111111111111111111111111111111111".to_string(),
+                "~ call #1 Ethernaut calldata 0x202023 -> Ethernaut calldata 0x202024".to_string(),
+            ],
+            diff_broadcasts(&old, &new, &[]).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_align_unnamed_deployments_by_creation_or_artifact() {
+        let init_json = |broadcast: Broadcast| {
+            let events = add_account_created_events(serialize_broadcast(broadcast).unwrap());
+            broadcast_from_etheno_events(etheno_events_from_values(events.unwrap()).unwrap())
+        };
+        let with_factory_code = |init_code: &str| {
+            let mut broadcast = fixture();
+            broadcast.transactions[1].transaction.data = init_code.to_string();
+            init_json(broadcast)
+        };
+        let argument = |value: u8| format!("{value:0>64x}");
+
+        assert_eq!(
+            vec!["~ <created by 0x90F79bf6EB2c4f870365E785982E1f101E93b906 at nonce 1>: bytecode changed"],
+            diff_broadcasts(
+                &with_factory_code("0x608060405"),
+                &with_factory_code("0x608060406"),
+                &[]
+            )
+            .unwrap()
+        );
+
+        let artifacts = [ContractArtifact {
+            name: "PrivacyFactory".to_string(),
+            abi: serde_json::from_str(
+                r#"[{"type": "constructor", "inputs": [{"type": "uint256"}]}]"#,
+            )
+            .unwrap(),
+            bytecode: "60a061".to_string(),
+        }];
+        assert_eq!(
+            vec!["~ PrivacyFactory: constructor arguments (1) -> (2)"],
+            diff_broadcasts(
+                &with_factory_code(&format!("0x60a061{}", argument(1))),
+                &with_factory_code(&format!("0x60a061{}", argument(2))),
+                &artifacts
+            )
+            .unwrap()
+        );
+    }
+}
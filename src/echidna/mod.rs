@@ -0,0 +1,16 @@
+/*//////////////////////////////////////////////////////////////
+                    ECHIDNA DEFAULT ACCOUNTS
+////////////////////////////////////////////////////////////// */
+/// Senders of Echidna's default config, which also deploys the test contract from `0x30000`.
+pub const DEFAULT_ECHIDNA_SENDERS: [&str; 3] = [
+    "0x0000000000000000000000000000000000010000",
+    "0x0000000000000000000000000000000000020000",
+    "0x0000000000000000000000000000000000030000",
+];
+
+/// Account from which Echidna deploys the test contract by default.
+pub const DEFAULT_ECHIDNA_DEPLOYER: &str = "0x0000000000000000000000000000000000030000";
+
+/// Echidna's default `contractAddr`, where it places the test contract regardless of the
+/// deployer's nonce.
+pub const DEFAULT_ECHIDNA_CONTRACT_ADDRESS: &str = "0x00a329c0648769a73afac7f9381e08fb43dbea72";
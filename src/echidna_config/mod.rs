@@ -0,0 +1,296 @@
+use crate::access_control::AccessControlSnapshot;
+use crate::artifacts::ContractArtifact;
+use crate::decoder::DecodedTransaction;
+use crate::registry::ContractRegistry;
+
+/*//////////////////////////////////////////////////////////////
+                    ECHIDNA FUNCTION FILTERS
+////////////////////////////////////////////////////////////// */
+/// Functions of OpenZeppelin's `Ownable`, `AccessControl`, `Pausable` and UUPS proxies that only
+/// privileged accounts may call.
+const ADMIN_FUNCTIONS: [&str; 10] = [
+    "transferOwnership(address)",
+    "renounceOwnership()",
+    "grantRole(bytes32,address)",
+    "revokeRole(bytes32,address)",
+    "renounceRole(bytes32,address)",
+    "upgradeTo(address)",
+    "upgradeToAndCall(address,bytes)",
+    "changeAdmin(address)",
+    "pause()",
+    "unpause()",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CalledFunction {
+    pub contract: String,
+    /// EIP-55 checksummed address of the called contract.
+    pub address: String,
+    /// Canonical signature, e.g. `setNumber(uint256)`.
+    pub signature: String,
+    /// Accounts that called the function, in call order.
+    pub senders: Vec<String>,
+}
+
+impl CalledFunction {
+    /// Echidna's `Contract.function(types)` notation.
+    pub fn filter_entry(&self) -> String {
+        format!("{}.{}", self.contract, self.signature)
+    }
+}
+
+/// Functions called by the broadcast, from the decoded calls whose contract and function are
+/// known. Calls whose selector matches no known function are left out.
+pub fn call_inventory(decoded: &[DecodedTransaction]) -> Vec<CalledFunction> {
+    let mut inventory: Vec<CalledFunction> = vec![];
+    for transaction in decoded
+        .iter()
+        .filter(|transaction| !transaction.is_creation)
+    {
+        let (Some(contract), Some(signature)) = (&transaction.contract, &transaction.function)
+        else {
+            continue;
+        };
+        match inventory.iter_mut().find(|function| {
+            function.address == transaction.address && function.signature == *signature
+        }) {
+            Some(function) if function.senders.contains(&transaction.from) => {}
+            Some(function) => function.senders.push(transaction.from.clone()),
+            None => inventory.push(CalledFunction {
+                contract: contract.clone(),
+                address: transaction.address.clone(),
+                signature: signature.clone(),
+                senders: vec![transaction.from.clone()],
+            }),
+        }
+    }
+    inventory
+}
+
+/// Functions in `Contract.function(types)` notation, with the reason why they look admin-only.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AdminFunctions {
+    /// Functions to exclude from fuzzing.
+    pub admin_only: Vec<(String, String)>,
+    /// Functions that only privileged accounts called, while nothing shows that other accounts
+    /// can't, e.g. a deployment script calling a public function. They are left to review.
+    pub suggestions: Vec<(String, String)>,
+}
+
+/// Finds the admin-only functions of the deployment.
+///
+/// A function is admin-only if it is a well-known admin function of a deployed contract's ABI
+/// or of the inventory, or if only the owner, the admin or role holders of its contract called
+/// it while other accounts called other functions of the contract. Functions that only privileged
+/// accounts called, on contracts that no other account called, are suggestions. The privileged
+/// accounts are the ones after the broadcast.
+pub fn admin_only_functions(
+    inventory: &[CalledFunction],
+    access: &AccessControlSnapshot,
+    registry: &ContractRegistry,
+    artifacts: &[ContractArtifact],
+) -> Result<AdminFunctions, String> {
+    let mut functions = AdminFunctions::default();
+    let add = |functions: &mut Vec<(String, String)>, entry: String, reason: String| {
+        if !functions.iter().any(|(other, _)| *other == entry) {
+            functions.push((entry, reason));
+        }
+    };
+
+    let mut deployed_names: Vec<&str> = vec![];
+    for name in registry
+        .contracts
+        .iter()
+        .filter_map(|contract| contract.name.as_deref())
+    {
+        if !deployed_names.contains(&name) {
+            deployed_names.push(name);
+        }
+    }
+    for name in deployed_names {
+        let abi = artifacts
+            .iter()
+            .filter(|artifact| artifact.name == name)
+            .flat_map(|artifact| &artifact.abi);
+        for entry in abi.filter(|entry| entry.kind == "function") {
+            let signature = entry.signature()?;
+            if ADMIN_FUNCTIONS.contains(&signature.as_str()) {
+                add(
+                    &mut functions.admin_only,
+                    format!("{name}.{signature}"),
+                    "well-known admin function".to_string(),
+                );
+            }
+        }
+    }
+
+    for function in inventory {
+        if ADMIN_FUNCTIONS.contains(&function.signature.as_str()) {
+            add(
+                &mut functions.admin_only,
+                function.filter_entry(),
+                "well-known admin function".to_string(),
+            );
+            continue;
+        }
+        let Some(contract) = access
+            .contracts
+            .iter()
+            .find(|contract| contract.address == function.address)
+        else {
+            continue;
+        };
+        let privileged: Vec<&String> = contract
+            .owner
+            .iter()
+            .chain(&contract.admin)
+            .chain(contract.roles.iter().flat_map(|(_, holders)| holders))
+            .collect();
+        if !function
+            .senders
+            .iter()
+            .all(|sender| privileged.contains(&sender))
+        {
+            continue;
+        }
+        let reason = format!("only called by privileged {}", function.senders.join(", "));
+        let unprivileged_sender = inventory
+            .iter()
+            .filter(|other| other.address == function.address)
+            .flat_map(|other| &other.senders)
+            .find(|sender| !privileged.contains(sender));
+        match unprivileged_sender {
+            Some(sender) => add(
+                &mut functions.admin_only,
+                function.filter_entry(),
+                format!("{reason}, while {sender} called other functions"),
+            ),
+            None => add(&mut functions.suggestions, function.filter_entry(), reason),
+        }
+    }
+    Ok(functions)
+}
+
+/// Echidna YAML config seeding the fuzzer with `init_path`. The filter entries are an allowlist,
+/// or a blacklist if `blacklist` is set. Contracts deployed by the initialization are only fuzzed
+/// with `allContracts`, so it is enabled.
+pub fn echidna_config(
+    init_path: &str,
+    crytic_argument: Option<&str>,
+    blacklist: bool,
+    filter_functions: &[String],
+) -> String {
+    let quoted = |values: &[String]| {
+        values
+            .iter()
+            .map(|value| format!("{value:?}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut lines = vec![
+        "# Generated by foundry2echidna".to_string(),
+        format!("initialize: {init_path:?}"),
+        "allContracts: true".to_string(),
+    ];
+    if let Some(crytic_argument) = crytic_argument {
+        lines.push(format!(
+            "cryticArgs: [{}]",
+            quoted(&[crytic_argument.to_string()])
+        ));
+    }
+    lines.push(format!("filterBlacklist: {blacklist}"));
+    lines.push(format!("filterFunctions: [{}]", quoted(filter_functions)));
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod echidna_config_tests {
+    use super::*;
+    use crate::decoder::decode_broadcast;
+    use crate::deserialization::deserialize_broadcast;
+    use crate::file_handling::read_broadcast_file;
+
+    #[test]
+    fn it_should_detect_functions_only_called_by_owner() {
+        let broadcast = deserialize_broadcast(
+            &read_broadcast_file("test_json_files/test_broadcast.json").unwrap(),
+        )
+        .unwrap();
+        let registry = ContractRegistry::from_broadcast(&broadcast).unwrap();
+        let artifacts = [ContractArtifact {
+            name: "PrivacyFactory".to_string(),
+            abi: serde_json::from_str(
+                r#"[{"type": "function", "name": "transferOwnership", "inputs": [{"type": "address"}]}]"#,
+            )
+            .unwrap(),
+            bytecode: String::new(),
+        }];
+        let mut inventory = call_inventory(&decode_broadcast(&broadcast, &[]).unwrap());
+        let access = AccessControlSnapshot::from_broadcast(&broadcast, &[]).unwrap();
+
+        assert_eq!(
+            vec![
+                "Ethernaut.registerLevel(address)",
+                "Ethernaut.createLevelInstance(address)"
+            ],
+            inventory
+                .iter()
+                .map(CalledFunction::filter_entry)
+                .collect::<Vec<_>>()
+        );
+        // The deployer is Ethernaut's owner and the only sender, so nothing shows that other
+        // accounts can't call its functions.
+        let owner_only = |entry: &str| {
+            (
+                entry.to_string(),
+                "only called by privileged 0x90F79bf6EB2c4f870365E785982E1f101E93b906".to_string(),
+            )
+        };
+        let well_known = (
+            "PrivacyFactory.transferOwnership(address)".to_string(),
+            "well-known admin function".to_string(),
+        );
+        assert_eq!(
+            AdminFunctions {
+                admin_only: vec![well_known.clone()],
+                suggestions: vec![
+                    owner_only("Ethernaut.registerLevel(address)"),
+                    owner_only("Ethernaut.createLevelInstance(address)"),
+                ],
+            },
+            admin_only_functions(&inventory, &access, &registry, &artifacts).unwrap()
+        );
+
+        // A player creating a level instance shows that registering levels is restricted.
+        inventory[1]
+            .senders
+            .push("0x0000000000000000000000000000000000010000".to_string());
+        let (entry, reason) = owner_only("Ethernaut.registerLevel(address)");
+        assert_eq!(
+            AdminFunctions {
+                admin_only: vec![
+                    well_known,
+                    (
+                        entry,
+                        format!("{reason}, while 0x0000000000000000000000000000000000010000 called other functions")
+                    ),
+                ],
+                suggestions: vec![],
+            },
+            admin_only_functions(&inventory, &access, &registry, &artifacts).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_write_echidna_config() {
+        assert_eq!(
+            "# Generated by foundry2echidna\ninitialize: \"src/crytic/init.json\"\nallContracts: true\ncryticArgs: [\"--compile-libraries=(Math,0x5FbDB2315678afecb367f032d93F642f64180aa3)\"]\nfilterBlacklist: true\nfilterFunctions: [\"Counter.setNumber(uint256)\"]\n",
+            echidna_config(
+                "src/crytic/init.json",
+                Some("--compile-libraries=(Math,0x5FbDB2315678afecb367f032d93F642f64180aa3)"),
+                true,
+                &["Counter.setNumber(uint256)".to_string()]
+            )
+        );
+    }
+}
@@ -0,0 +1,1353 @@
+use crate::primitives::{create2_address, keccak256};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/*//////////////////////////////////////////////////////////////
+                        256-BIT INTEGERS
+////////////////////////////////////////////////////////////// */
+/// Unsigned 256-bit integer, as four 64-bit limbs, least significant first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct U256([u64; 4]);
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub fn from_u64(value: u64) -> Self {
+        U256([value, 0, 0, 0])
+    }
+
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = 32 - 8 * (i + 1);
+            *limb = u64::from_be_bytes(bytes[start..start + 8].try_into().unwrap_or_default());
+        }
+        U256(limbs)
+    }
+
+    /// Big-endian bytes, left padded to 32 bytes. Only the last 32 bytes of longer slices count.
+    pub fn from_be_slice(bytes: &[u8]) -> Self {
+        let bytes = &bytes[bytes.len().saturating_sub(32)..];
+        let mut word = [0u8; 32];
+        word[32 - bytes.len()..].copy_from_slice(bytes);
+        Self::from_be_bytes(&word)
+    }
+
+    pub fn to_be_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            let start = 32 - 8 * (i + 1);
+            bytes[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn is_zero(self) -> bool {
+        self == Self::ZERO
+    }
+
+    pub fn to_usize(self) -> Option<usize> {
+        if self.0[1..].iter().any(|limb| *limb != 0) {
+            return None;
+        }
+        usize::try_from(self.0[0]).ok()
+    }
+
+    fn bit(self, index: usize) -> bool {
+        index < 256 && (self.0[index / 64] >> (index % 64)) & 1 == 1
+    }
+
+    fn is_negative(self) -> bool {
+        self.bit(255)
+    }
+
+    fn overflowing_add(self, other: Self) -> (Self, bool) {
+        let mut sum = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in sum.iter_mut().enumerate() {
+            let (partial, first_overflow) = self.0[i].overflowing_add(other.0[i]);
+            let (partial, second_overflow) = partial.overflowing_add(carry as u64);
+            *limb = partial;
+            carry = first_overflow || second_overflow;
+        }
+        (U256(sum), carry)
+    }
+
+    pub fn wrapping_add(self, other: Self) -> Self {
+        self.overflowing_add(other).0
+    }
+
+    pub fn wrapping_sub(self, other: Self) -> Self {
+        self.wrapping_add(other.negate())
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (sum, overflow) = self.overflowing_add(other);
+        (!overflow).then_some(sum)
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let product = self.wrapping_mul(other);
+        (self.is_zero() || product.div_rem(self).0 == other).then_some(product)
+    }
+
+    pub fn wrapping_mul(self, other: Self) -> Self {
+        let mut product = [0u64; 4];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 - i {
+                let partial =
+                    product[i + j] as u128 + self.0[i] as u128 * other.0[j] as u128 + carry;
+                product[i + j] = partial as u64;
+                carry = partial >> 64;
+            }
+        }
+        U256(product)
+    }
+
+    /// Quotient and remainder. Division by zero returns zero for both, like the EVM.
+    pub fn div_rem(self, divisor: Self) -> (Self, Self) {
+        if divisor.is_zero() {
+            return (Self::ZERO, Self::ZERO);
+        }
+        let mut quotient = Self::ZERO;
+        let mut remainder = Self::ZERO;
+        for i in (0..256).rev() {
+            let carry = remainder.is_negative();
+            remainder = remainder.shl(1);
+            if self.bit(i) {
+                remainder.0[0] |= 1;
+            }
+            if carry || remainder >= divisor {
+                remainder = remainder.wrapping_sub(divisor);
+                quotient.0[i / 64] |= 1 << (i % 64);
+            }
+        }
+        (quotient, remainder)
+    }
+
+    fn negate(self) -> Self {
+        self.not().overflowing_add(Self::ONE).0
+    }
+
+    fn abs(self) -> Self {
+        if self.is_negative() {
+            self.negate()
+        } else {
+            self
+        }
+    }
+
+    fn signed_div(self, divisor: Self) -> Self {
+        let quotient = self.abs().div_rem(divisor.abs()).0;
+        if self.is_negative() != divisor.is_negative() {
+            quotient.negate()
+        } else {
+            quotient
+        }
+    }
+
+    fn signed_rem(self, divisor: Self) -> Self {
+        let remainder = self.abs().div_rem(divisor.abs()).1;
+        if self.is_negative() {
+            remainder.negate()
+        } else {
+            remainder
+        }
+    }
+
+    fn signed_cmp(self, other: Self) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => self.cmp(&other),
+        }
+    }
+
+    fn add_mod(self, other: Self, modulus: Self) -> Self {
+        if modulus.is_zero() {
+            return Self::ZERO;
+        }
+        let (sum, overflow) = self
+            .div_rem(modulus)
+            .1
+            .overflowing_add(other.div_rem(modulus).1);
+        if overflow || sum >= modulus {
+            sum.wrapping_sub(modulus)
+        } else {
+            sum
+        }
+    }
+
+    fn mul_mod(self, other: Self, modulus: Self) -> Self {
+        if modulus.is_zero() {
+            return Self::ZERO;
+        }
+        let base = self.div_rem(modulus).1;
+        let mut product = Self::ZERO;
+        for i in (0..256).rev() {
+            product = product.add_mod(product, modulus);
+            if other.bit(i) {
+                product = product.add_mod(base, modulus);
+            }
+        }
+        product
+    }
+
+    fn pow(self, exponent: Self) -> Self {
+        let mut result = Self::ONE;
+        for i in (0..256).rev() {
+            result = result.wrapping_mul(result);
+            if exponent.bit(i) {
+                result = result.wrapping_mul(self);
+            }
+        }
+        result
+    }
+
+    fn sign_extend(self, byte: Self) -> Self {
+        match byte.to_usize() {
+            Some(byte) if byte < 31 => {
+                let sign_bit = byte * 8 + 7;
+                let mask = Self::ONE.shl(sign_bit + 1).wrapping_sub(Self::ONE);
+                if self.bit(sign_bit) {
+                    self.or(mask.not())
+                } else {
+                    self.and(mask)
+                }
+            }
+            _ => self,
+        }
+    }
+
+    fn byte(self, index: Self) -> Self {
+        match index.to_usize() {
+            Some(index) if index < 32 => Self::from_u64(self.to_be_bytes()[index] as u64),
+            _ => Self::ZERO,
+        }
+    }
+
+    fn shl(self, shift: usize) -> Self {
+        if shift >= 256 {
+            return Self::ZERO;
+        }
+        let (limbs, bits) = (shift / 64, shift % 64);
+        let mut shifted = [0u64; 4];
+        for (i, limb) in shifted.iter_mut().enumerate().skip(limbs) {
+            *limb = self.0[i - limbs] << bits;
+            if bits > 0 && i > limbs {
+                *limb |= self.0[i - limbs - 1] >> (64 - bits);
+            }
+        }
+        U256(shifted)
+    }
+
+    fn shr(self, shift: usize) -> Self {
+        if shift >= 256 {
+            return Self::ZERO;
+        }
+        let (limbs, bits) = (shift / 64, shift % 64);
+        let mut shifted = [0u64; 4];
+        for (i, limb) in shifted.iter_mut().enumerate().take(4 - limbs) {
+            *limb = self.0[i + limbs] >> bits;
+            if bits > 0 && i + limbs < 3 {
+                *limb |= self.0[i + limbs + 1] << (64 - bits);
+            }
+        }
+        U256(shifted)
+    }
+
+    fn sar(self, shift: usize) -> Self {
+        match (self.is_negative(), shift >= 256) {
+            (true, true) => Self::MAX,
+            (true, false) => self.not().shr(shift).not(),
+            (false, _) => self.shr(shift),
+        }
+    }
+
+    fn and(self, other: Self) -> Self {
+        U256([0, 1, 2, 3].map(|i| self.0[i] & other.0[i]))
+    }
+
+    fn or(self, other: Self) -> Self {
+        U256([0, 1, 2, 3].map(|i| self.0[i] | other.0[i]))
+    }
+
+    fn xor(self, other: Self) -> Self {
+        U256([0, 1, 2, 3].map(|i| self.0[i] ^ other.0[i]))
+    }
+
+    fn not(self) -> Self {
+        U256(self.0.map(|limb| !limb))
+    }
+}
+
+/*//////////////////////////////////////////////////////////////
+                        EVM INTERPRETER
+////////////////////////////////////////////////////////////// */
+pub type Address = [u8; 20];
+
+/// Block number and timestamp of Echidna's initial block.
+pub const ECHIDNA_INITIAL_BLOCK: (u64, u64) = (4370000, 1524785992);
+const CHAIN_ID: u64 = 31337;
+const BLOCK_GAS_LIMIT: u64 = 30_000_000;
+const MAX_CALL_DEPTH: usize = 1024;
+const MAX_STACK_SIZE: usize = 1024;
+/// Memory is not paid with gas, so it is capped to stop runaway expansions.
+const MAX_MEMORY_SIZE: usize = 32 * 1024 * 1024;
+/// Gas is not metered, so transactions are capped in executed instructions instead.
+const MAX_STEPS_PER_TRANSACTION: usize = 50_000_000;
+const IDENTITY_PRECOMPILE: u8 = 0x04;
+const LAST_PRECOMPILE: u8 = 0x11;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Account {
+    pub nonce: u64,
+    pub balance: U256,
+    pub code: Vec<u8>,
+    pub storage: HashMap<U256, U256>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TransactionOutcome {
+    pub success: bool,
+    /// Return or revert data. Empty for successful creations.
+    pub output: Vec<u8>,
+    /// Address of the contract created by a successful creation.
+    pub created: Option<Address>,
+}
+
+/// A minimal in-memory EVM (Cancun) that executes transactions without gas accounting.
+///
+/// It is meant to replay deployments and compare the resulting states, not to be a reference
+/// implementation: calls to precompiles other than the identity are reported as errors.
+#[derive(Debug, Default)]
+pub struct Evm {
+    pub accounts: HashMap<Address, Account>,
+    pub block_number: u64,
+    pub timestamp: u64,
+    transient_storage: HashMap<(Address, U256), U256>,
+    created_in_transaction: HashSet<Address>,
+    origin: Address,
+    gas_price: U256,
+    steps: usize,
+    /// Undo log of the current transaction, unwound when a call or creation fails.
+    journal: Vec<JournalEntry>,
+}
+
+/// A state change, with the previous value to restore.
+#[derive(Debug)]
+enum JournalEntry {
+    AccountCreated(Address),
+    AccountRemoved(Address, Account),
+    Nonce(Address, u64),
+    Balance(Address, U256),
+    Code(Address, Vec<u8>),
+    Storage(Address, U256, Option<U256>),
+    TransientStorage(Address, U256, Option<U256>),
+    CreatedInTransaction(Address),
+}
+
+/// Length of the journal when the snapshot was taken.
+type Snapshot = usize;
+
+struct Frame<'a> {
+    caller: Address,
+    address: Address,
+    value: U256,
+    data: &'a [u8],
+    is_static: bool,
+    depth: usize,
+}
+
+enum Halt {
+    Return(Vec<u8>),
+    Revert(Vec<u8>),
+    /// Invalid instructions, stack errors and the like, which revert and consume all gas.
+    Exceptional,
+}
+
+impl Evm {
+    pub fn new() -> Self {
+        Self {
+            block_number: ECHIDNA_INITIAL_BLOCK.0,
+            timestamp: ECHIDNA_INITIAL_BLOCK.1,
+            ..Self::default()
+        }
+    }
+
+    pub fn code(&self, address: &Address) -> &[u8] {
+        self.accounts
+            .get(address)
+            .map(|account| account.code.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn storage(&self, address: &Address, slot: &U256) -> U256 {
+        self.accounts
+            .get(address)
+            .and_then(|account| account.storage.get(slot))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Executes a transaction. `to` is `None` for contract creations.
+    ///
+    /// Errors only for what the interpreter does not support. Reverts are reported in the outcome.
+    pub fn transact(
+        &mut self,
+        from: Address,
+        to: Option<Address>,
+        value: U256,
+        data: Vec<u8>,
+        gas_price: U256,
+    ) -> Result<TransactionOutcome, String> {
+        self.transient_storage.clear();
+        self.created_in_transaction.clear();
+        self.journal.clear();
+        self.origin = from;
+        self.gas_price = gas_price;
+        self.steps = 0;
+        match to {
+            None => {
+                let address = create_address(&from, self.nonce(&from));
+                let (success, output) = self.create(from, address, value, data, 0)?;
+                Ok(TransactionOutcome {
+                    success,
+                    output,
+                    created: success.then_some(address),
+                })
+            }
+            Some(to) => {
+                self.set_nonce(from, self.nonce(&from) + 1);
+                let (success, output) = self.call(
+                    Frame {
+                        caller: from,
+                        address: to,
+                        value,
+                        data: &data,
+                        is_static: false,
+                        depth: 0,
+                    },
+                    to,
+                    true,
+                )?;
+                Ok(TransactionOutcome {
+                    success,
+                    output,
+                    created: None,
+                })
+            }
+        }
+    }
+
+    /// Executes a call from the zero address and discards its state changes.
+    pub fn view_call(&mut self, to: Address, data: &[u8]) -> Result<(bool, Vec<u8>), String> {
+        let snapshot = self.snapshot();
+        self.steps = 0;
+        let result = self.call(
+            Frame {
+                caller: [0; 20],
+                address: to,
+                value: U256::ZERO,
+                data,
+                is_static: true,
+                depth: 0,
+            },
+            to,
+            false,
+        );
+        self.restore(snapshot);
+        result
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        self.journal.len()
+    }
+
+    /// Undoes the state changes made since the snapshot, latest first.
+    fn restore(&mut self, snapshot: Snapshot) {
+        while self.journal.len() > snapshot {
+            let Some(entry) = self.journal.pop() else {
+                break;
+            };
+            match entry {
+                JournalEntry::AccountCreated(address) => {
+                    self.accounts.remove(&address);
+                }
+                JournalEntry::AccountRemoved(address, account) => {
+                    self.accounts.insert(address, account);
+                }
+                JournalEntry::Nonce(address, nonce) => self.account_entry(address).nonce = nonce,
+                JournalEntry::Balance(address, balance) => {
+                    self.account_entry(address).balance = balance
+                }
+                JournalEntry::Code(address, code) => self.account_entry(address).code = code,
+                JournalEntry::Storage(address, slot, value) => {
+                    let storage = &mut self.account_entry(address).storage;
+                    match value {
+                        Some(value) => storage.insert(slot, value),
+                        None => storage.remove(&slot),
+                    };
+                }
+                JournalEntry::TransientStorage(address, slot, value) => {
+                    match value {
+                        Some(value) => self.transient_storage.insert((address, slot), value),
+                        None => self.transient_storage.remove(&(address, slot)),
+                    };
+                }
+                JournalEntry::CreatedInTransaction(address) => {
+                    self.created_in_transaction.remove(&address);
+                }
+            }
+        }
+    }
+
+    fn account_entry(&mut self, address: Address) -> &mut Account {
+        self.accounts.entry(address).or_default()
+    }
+
+    /// The account, created and journaled if it doesn't exist.
+    fn account_mut(&mut self, address: Address) -> &mut Account {
+        if !self.accounts.contains_key(&address) {
+            self.journal.push(JournalEntry::AccountCreated(address));
+        }
+        self.account_entry(address)
+    }
+
+    fn nonce(&self, address: &Address) -> u64 {
+        self.accounts
+            .get(address)
+            .map(|account| account.nonce)
+            .unwrap_or_default()
+    }
+
+    fn set_nonce(&mut self, address: Address, nonce: u64) {
+        let previous = std::mem::replace(&mut self.account_mut(address).nonce, nonce);
+        self.journal.push(JournalEntry::Nonce(address, previous));
+    }
+
+    fn set_balance(&mut self, address: Address, balance: U256) {
+        let previous = std::mem::replace(&mut self.account_mut(address).balance, balance);
+        self.journal.push(JournalEntry::Balance(address, previous));
+    }
+
+    fn set_code(&mut self, address: Address, code: Vec<u8>) {
+        let previous = std::mem::replace(&mut self.account_mut(address).code, code);
+        self.journal.push(JournalEntry::Code(address, previous));
+    }
+
+    fn set_storage(&mut self, address: Address, slot: U256, value: U256) {
+        let storage = &mut self.account_mut(address).storage;
+        let previous = if value.is_zero() {
+            storage.remove(&slot)
+        } else {
+            storage.insert(slot, value)
+        };
+        self.journal
+            .push(JournalEntry::Storage(address, slot, previous));
+    }
+
+    fn set_transient_storage(&mut self, address: Address, slot: U256, value: U256) {
+        let previous = self.transient_storage.insert((address, slot), value);
+        self.journal
+            .push(JournalEntry::TransientStorage(address, slot, previous));
+    }
+
+    fn mark_created(&mut self, address: Address) {
+        if self.created_in_transaction.insert(address) {
+            self.journal
+                .push(JournalEntry::CreatedInTransaction(address));
+        }
+    }
+
+    fn remove_account(&mut self, address: Address) {
+        if let Some(account) = self.accounts.remove(&address) {
+            self.journal
+                .push(JournalEntry::AccountRemoved(address, account));
+        }
+    }
+
+    fn balance(&self, address: &Address) -> U256 {
+        self.accounts
+            .get(address)
+            .map(|account| account.balance)
+            .unwrap_or_default()
+    }
+
+    fn transfer(&mut self, from: Address, to: Address, value: U256) -> bool {
+        if self.balance(&from) < value {
+            return false;
+        }
+        self.set_balance(from, self.balance(&from).wrapping_sub(value));
+        self.set_balance(to, self.balance(&to).wrapping_add(value));
+        true
+    }
+
+    /// Runs the code of `code_address` in the frame's context, transferring the value if
+    /// `transfer` is set. State changes are reverted if the call fails.
+    fn call(
+        &mut self,
+        frame: Frame,
+        code_address: Address,
+        transfer: bool,
+    ) -> Result<(bool, Vec<u8>), String> {
+        if frame.depth > MAX_CALL_DEPTH {
+            return Ok((false, vec![]));
+        }
+        let snapshot = self.snapshot();
+        if transfer && !self.transfer(frame.caller, frame.address, frame.value) {
+            return Ok((false, vec![]));
+        }
+        if code_address[..19].iter().all(|byte| *byte == 0)
+            && (1..=LAST_PRECOMPILE).contains(&code_address[19])
+        {
+            return match code_address[19] {
+                IDENTITY_PRECOMPILE => Ok((true, frame.data.to_vec())),
+                precompile => Err(format!("Precompile 0x{precompile:02x} is not supported")),
+            };
+        }
+        let code = self.code(&code_address).to_vec();
+        if code.is_empty() {
+            return Ok((true, vec![]));
+        }
+        match self.run(&frame, &code)? {
+            Halt::Return(output) => Ok((true, output)),
+            Halt::Revert(output) => {
+                self.restore(snapshot);
+                Ok((false, output))
+            }
+            Halt::Exceptional => {
+                self.restore(snapshot);
+                Ok((false, vec![]))
+            }
+        }
+    }
+
+    /// Deploys `init_code` at `address`. Returns whether it succeeded and the revert data.
+    fn create(
+        &mut self,
+        caller: Address,
+        address: Address,
+        value: U256,
+        init_code: Vec<u8>,
+        depth: usize,
+    ) -> Result<(bool, Vec<u8>), String> {
+        if depth > MAX_CALL_DEPTH || self.balance(&caller) < value {
+            return Ok((false, vec![]));
+        }
+        self.set_nonce(caller, self.nonce(&caller) + 1);
+        if self
+            .accounts
+            .get(&address)
+            .is_some_and(|account| account.nonce > 0 || !account.code.is_empty())
+        {
+            return Ok((false, vec![]));
+        }
+        let snapshot = self.snapshot();
+        self.set_nonce(address, 1);
+        self.mark_created(address);
+        self.transfer(caller, address, value);
+        let frame = Frame {
+            caller,
+            address,
+            value,
+            data: &[],
+            is_static: false,
+            depth,
+        };
+        match self.run(&frame, &init_code)? {
+            Halt::Return(code) if code.first() != Some(&0xef) => {
+                self.set_code(address, code);
+                Ok((true, vec![]))
+            }
+            Halt::Revert(output) => {
+                self.restore(snapshot);
+                Ok((false, output))
+            }
+            Halt::Return(_) | Halt::Exceptional => {
+                self.restore(snapshot);
+                Ok((false, vec![]))
+            }
+        }
+    }
+
+    fn run(&mut self, frame: &Frame, code: &[u8]) -> Result<Halt, String> {
+        let jump_destinations = jump_destinations(code);
+        let mut stack: Vec<U256> = Vec::with_capacity(32);
+        let mut memory: Vec<u8> = vec![];
+        let mut return_data: Vec<u8> = vec![];
+        let mut pc = 0;
+
+        macro_rules! pop {
+            () => {
+                match stack.pop() {
+                    Some(value) => value,
+                    None => return Ok(Halt::Exceptional),
+                }
+            };
+        }
+        macro_rules! push {
+            ($value:expr) => {{
+                let value = $value;
+                if stack.len() >= MAX_STACK_SIZE {
+                    return Ok(Halt::Exceptional);
+                }
+                stack.push(value);
+            }};
+        }
+        macro_rules! memory_range {
+            ($offset:expr, $size:expr) => {
+                match expand_memory(&mut memory, $offset, $size) {
+                    Some(range) => range,
+                    None => return Ok(Halt::Exceptional),
+                }
+            };
+        }
+        macro_rules! require_mutable {
+            () => {
+                if frame.is_static {
+                    return Ok(Halt::Exceptional);
+                }
+            };
+        }
+
+        while pc < code.len() {
+            self.steps += 1;
+            if self.steps > MAX_STEPS_PER_TRANSACTION {
+                return Err(format!(
+                    "Transaction exceeded {MAX_STEPS_PER_TRANSACTION} executed instructions"
+                ));
+            }
+            let opcode = code[pc];
+            pc += 1;
+            match opcode {
+                0x00 => return Ok(Halt::Return(vec![])),
+                0x01 => push!(pop!().wrapping_add(pop!())),
+                0x02 => push!(pop!().wrapping_mul(pop!())),
+                0x03 => push!(pop!().wrapping_sub(pop!())),
+                0x04 => push!(pop!().div_rem(pop!()).0),
+                0x05 => push!(pop!().signed_div(pop!())),
+                0x06 => push!(pop!().div_rem(pop!()).1),
+                0x07 => push!(pop!().signed_rem(pop!())),
+                0x08 => push!(pop!().add_mod(pop!(), pop!())),
+                0x09 => push!(pop!().mul_mod(pop!(), pop!())),
+                0x0a => push!(pop!().pow(pop!())),
+                0x0b => {
+                    let byte = pop!();
+                    push!(pop!().sign_extend(byte))
+                }
+                0x10 => push!(from_bool(pop!() < pop!())),
+                0x11 => push!(from_bool(pop!() > pop!())),
+                0x12 => push!(from_bool(pop!().signed_cmp(pop!()) == Ordering::Less)),
+                0x13 => push!(from_bool(pop!().signed_cmp(pop!()) == Ordering::Greater)),
+                0x14 => push!(from_bool(pop!() == pop!())),
+                0x15 => push!(from_bool(pop!().is_zero())),
+                0x16 => push!(pop!().and(pop!())),
+                0x17 => push!(pop!().or(pop!())),
+                0x18 => push!(pop!().xor(pop!())),
+                0x19 => push!(pop!().not()),
+                0x1a => {
+                    let index = pop!();
+                    push!(pop!().byte(index))
+                }
+                0x1b => {
+                    let shift = shift_amount(pop!());
+                    push!(pop!().shl(shift))
+                }
+                0x1c => {
+                    let shift = shift_amount(pop!());
+                    push!(pop!().shr(shift))
+                }
+                0x1d => {
+                    let shift = shift_amount(pop!());
+                    push!(pop!().sar(shift))
+                }
+                0x20 => {
+                    let (offset, size) = memory_range!(pop!(), pop!());
+                    push!(U256::from_be_bytes(&keccak256(
+                        &memory[offset..offset + size]
+                    )))
+                }
+                0x30 => push!(from_address(&frame.address)),
+                0x31 => push!(self.balance(&to_address(pop!()))),
+                0x32 => push!(from_address(&self.origin)),
+                0x33 => push!(from_address(&frame.caller)),
+                0x34 => push!(frame.value),
+                0x35 => {
+                    let offset = pop!();
+                    let mut word = [0u8; 32];
+                    copy_padded(frame.data, offset, &mut word);
+                    push!(U256::from_be_bytes(&word))
+                }
+                0x36 => push!(U256::from_u64(frame.data.len() as u64)),
+                0x37 | 0x39 => {
+                    let (destination, source, size) = (pop!(), pop!(), pop!());
+                    let (destination, size) = memory_range!(destination, size);
+                    let source_data = if opcode == 0x37 { frame.data } else { code };
+                    copy_padded(
+                        source_data,
+                        source,
+                        &mut memory[destination..destination + size],
+                    );
+                }
+                0x38 => push!(U256::from_u64(code.len() as u64)),
+                0x3a => push!(self.gas_price),
+                0x3b => push!(U256::from_u64(self.code(&to_address(pop!())).len() as u64)),
+                0x3c => {
+                    let address = to_address(pop!());
+                    let (destination, source, size) = (pop!(), pop!(), pop!());
+                    let (destination, size) = memory_range!(destination, size);
+                    let external_code = self.code(&address).to_vec();
+                    copy_padded(
+                        &external_code,
+                        source,
+                        &mut memory[destination..destination + size],
+                    );
+                }
+                0x3d => push!(U256::from_u64(return_data.len() as u64)),
+                0x3e => {
+                    let (destination, source, size) = (pop!(), pop!(), pop!());
+                    let end = match (source.to_usize(), size.to_usize()) {
+                        (Some(source), Some(size)) => source.checked_add(size),
+                        _ => None,
+                    };
+                    let Some(end) = end.filter(|end| *end <= return_data.len()) else {
+                        return Ok(Halt::Exceptional);
+                    };
+                    let (destination, size) = memory_range!(destination, size);
+                    memory[destination..destination + size]
+                        .copy_from_slice(&return_data[end - size..end]);
+                }
+                0x3f => {
+                    let address = to_address(pop!());
+                    push!(match self.accounts.get(&address) {
+                        Some(account)
+                            if account.nonce > 0
+                                || !account.balance.is_zero()
+                                || !account.code.is_empty() =>
+                        {
+                            U256::from_be_bytes(&keccak256(&account.code))
+                        }
+                        _ => U256::ZERO,
+                    })
+                }
+                0x40 => {
+                    pop!();
+                    push!(U256::ZERO)
+                }
+                0x41 | 0x44 | 0x48 => push!(U256::ZERO),
+                0x42 => push!(U256::from_u64(self.timestamp)),
+                0x43 => push!(U256::from_u64(self.block_number)),
+                0x45 | 0x5a => push!(U256::from_u64(BLOCK_GAS_LIMIT)),
+                0x46 => push!(U256::from_u64(CHAIN_ID)),
+                0x47 => push!(self.balance(&frame.address)),
+                0x49 => {
+                    pop!();
+                    push!(U256::ZERO)
+                }
+                0x4a => push!(U256::ONE),
+                0x50 => {
+                    pop!();
+                }
+                0x51 => {
+                    let (offset, _) = memory_range!(pop!(), U256::from_u64(32));
+                    push!(U256::from_be_slice(&memory[offset..offset + 32]))
+                }
+                0x52 => {
+                    let (offset, value) = (pop!(), pop!());
+                    let (offset, _) = memory_range!(offset, U256::from_u64(32));
+                    memory[offset..offset + 32].copy_from_slice(&value.to_be_bytes());
+                }
+                0x53 => {
+                    let (offset, value) = (pop!(), pop!());
+                    let (offset, _) = memory_range!(offset, U256::ONE);
+                    memory[offset] = value.to_be_bytes()[31];
+                }
+                0x54 => push!(self.storage(&frame.address, &pop!())),
+                0x55 => {
+                    require_mutable!();
+                    let (slot, value) = (pop!(), pop!());
+                    self.set_storage(frame.address, slot, value);
+                }
+                0x56 => {
+                    let destination = pop!();
+                    match destination.to_usize() {
+                        Some(destination) if jump_destinations.contains(&destination) => {
+                            pc = destination
+                        }
+                        _ => return Ok(Halt::Exceptional),
+                    }
+                }
+                0x57 => {
+                    let (destination, condition) = (pop!(), pop!());
+                    if !condition.is_zero() {
+                        match destination.to_usize() {
+                            Some(destination) if jump_destinations.contains(&destination) => {
+                                pc = destination
+                            }
+                            _ => return Ok(Halt::Exceptional),
+                        }
+                    }
+                }
+                0x58 => push!(U256::from_u64(pc as u64 - 1)),
+                0x59 => push!(U256::from_u64(memory.len() as u64)),
+                0x5b => {}
+                0x5c => {
+                    let slot = pop!();
+                    push!(self
+                        .transient_storage
+                        .get(&(frame.address, slot))
+                        .copied()
+                        .unwrap_or_default())
+                }
+                0x5d => {
+                    require_mutable!();
+                    let (slot, value) = (pop!(), pop!());
+                    self.set_transient_storage(frame.address, slot, value);
+                }
+                0x5e => {
+                    let (destination, source, size) = (pop!(), pop!(), pop!());
+                    let (source, _) = memory_range!(source, size);
+                    let (destination, size) = memory_range!(destination, size);
+                    memory.copy_within(source..source + size, destination);
+                }
+                0x5f..=0x7f => {
+                    let size = (opcode - 0x5f) as usize;
+                    let end = (pc + size).min(code.len());
+                    let mut word = [0u8; 32];
+                    word[32 - size..32 - size + end - pc].copy_from_slice(&code[pc..end]);
+                    push!(U256::from_be_bytes(&word));
+                    pc += size;
+                }
+                0x80..=0x8f => {
+                    let depth = (opcode - 0x80) as usize;
+                    if stack.len() <= depth {
+                        return Ok(Halt::Exceptional);
+                    }
+                    push!(stack[stack.len() - 1 - depth])
+                }
+                0x90..=0x9f => {
+                    let depth = (opcode - 0x8f) as usize;
+                    if stack.len() <= depth {
+                        return Ok(Halt::Exceptional);
+                    }
+                    let top = stack.len() - 1;
+                    stack.swap(top, top - depth);
+                }
+                0xa0..=0xa4 => {
+                    require_mutable!();
+                    memory_range!(pop!(), pop!());
+                    for _ in 0..opcode - 0xa0 {
+                        pop!();
+                    }
+                }
+                0xf0 | 0xf5 => {
+                    require_mutable!();
+                    let (value, offset, size) = (pop!(), pop!(), pop!());
+                    let (offset, size) = memory_range!(offset, size);
+                    let init_code = memory[offset..offset + size].to_vec();
+                    let address = if opcode == 0xf0 {
+                        create_address(&frame.address, self.nonce(&frame.address))
+                    } else {
+                        let salt = pop!().to_be_bytes();
+                        create2_address(&frame.address, &salt, &keccak256(&init_code))
+                    };
+                    let (success, output) =
+                        self.create(frame.address, address, value, init_code, frame.depth + 1)?;
+                    return_data = output;
+                    push!(if success {
+                        from_address(&address)
+                    } else {
+                        U256::ZERO
+                    })
+                }
+                0xf1 | 0xf2 | 0xf4 | 0xfa => {
+                    pop!();
+                    let to = to_address(pop!());
+                    let value = match opcode {
+                        0xf1 | 0xf2 => pop!(),
+                        _ => U256::ZERO,
+                    };
+                    let (input_offset, input_size, output_offset, output_size) =
+                        (pop!(), pop!(), pop!(), pop!());
+                    if opcode == 0xf1 && frame.is_static && !value.is_zero() {
+                        return Ok(Halt::Exceptional);
+                    }
+                    let (input_offset, input_size) = memory_range!(input_offset, input_size);
+                    let (output_offset, output_size) = memory_range!(output_offset, output_size);
+                    let input = memory[input_offset..input_offset + input_size].to_vec();
+                    let (caller, address, value, is_static) = match opcode {
+                        0xf1 => (frame.address, to, value, frame.is_static),
+                        0xf2 => (frame.address, frame.address, value, frame.is_static),
+                        0xf4 => (frame.caller, frame.address, frame.value, frame.is_static),
+                        _ => (frame.address, to, U256::ZERO, true),
+                    };
+                    if opcode == 0xf2 && self.balance(&frame.address) < value {
+                        return_data = vec![];
+                        push!(U256::ZERO);
+                        continue;
+                    }
+                    let (success, output) = self.call(
+                        Frame {
+                            caller,
+                            address,
+                            value,
+                            data: &input,
+                            is_static,
+                            depth: frame.depth + 1,
+                        },
+                        to,
+                        opcode == 0xf1,
+                    )?;
+                    let copied = output_size.min(output.len());
+                    memory[output_offset..output_offset + copied]
+                        .copy_from_slice(&output[..copied]);
+                    return_data = output;
+                    push!(from_bool(success))
+                }
+                0xf3 | 0xfd => {
+                    let (offset, size) = memory_range!(pop!(), pop!());
+                    let output = memory[offset..offset + size].to_vec();
+                    return Ok(if opcode == 0xf3 {
+                        Halt::Return(output)
+                    } else {
+                        Halt::Revert(output)
+                    });
+                }
+                0xff => {
+                    require_mutable!();
+                    let beneficiary = to_address(pop!());
+                    let balance = self.balance(&frame.address);
+                    self.transfer(frame.address, beneficiary, balance);
+                    if self.created_in_transaction.contains(&frame.address) {
+                        self.remove_account(frame.address);
+                    }
+                    return Ok(Halt::Return(vec![]));
+                }
+                _ => return Ok(Halt::Exceptional),
+            }
+        }
+        Ok(Halt::Return(vec![]))
+    }
+}
+
+/// Address of a contract created with CREATE: the last 20 bytes of `keccak256(rlp([sender, nonce]))`.
+pub fn create_address(sender: &Address, nonce: u64) -> Address {
+    let nonce_bytes: Vec<u8> = nonce
+        .to_be_bytes()
+        .into_iter()
+        .skip_while(|byte| *byte == 0)
+        .collect();
+    let mut encoded_nonce = match nonce_bytes.as_slice() {
+        [] => vec![0x80],
+        [byte] if *byte < 0x80 => vec![*byte],
+        bytes => vec![0x80 + bytes.len() as u8],
+    };
+    if encoded_nonce[0] > 0x80 {
+        encoded_nonce.extend(&nonce_bytes);
+    }
+    let mut rlp = vec![0xc0 + (21 + encoded_nonce.len()) as u8, 0x94];
+    rlp.extend_from_slice(sender);
+    rlp.extend(encoded_nonce);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&keccak256(&rlp)[12..]);
+    address
+}
+
+fn jump_destinations(code: &[u8]) -> HashSet<usize> {
+    let mut destinations = HashSet::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        match code[pc] {
+            0x5b => {
+                destinations.insert(pc);
+            }
+            opcode @ 0x60..=0x7f => pc += (opcode - 0x5f) as usize,
+            _ => {}
+        }
+        pc += 1;
+    }
+    destinations
+}
+
+/// Grows the memory to cover `size` bytes at `offset`, returning them as a `usize` range start
+/// and length. Fails when the range is out of the memory cap.
+fn expand_memory(memory: &mut Vec<u8>, offset: U256, size: U256) -> Option<(usize, usize)> {
+    let size = size.to_usize()?;
+    if size == 0 {
+        return Some((0, 0));
+    }
+    let offset = offset.to_usize()?;
+    let end = offset.checked_add(size)?;
+    if end > MAX_MEMORY_SIZE {
+        return None;
+    }
+    if end > memory.len() {
+        memory.resize(end.div_ceil(32) * 32, 0);
+    }
+    Some((offset, size))
+}
+
+/// Copies `source[offset..]` into `destination`, padding with zeros past the end of `source`.
+fn copy_padded(source: &[u8], offset: U256, destination: &mut [u8]) {
+    destination.fill(0);
+    if let Some(offset) = offset.to_usize().filter(|offset| *offset < source.len()) {
+        let copied = destination.len().min(source.len() - offset);
+        destination[..copied].copy_from_slice(&source[offset..offset + copied]);
+    }
+}
+
+fn shift_amount(shift: U256) -> usize {
+    shift.to_usize().unwrap_or(usize::MAX)
+}
+
+fn from_bool(value: bool) -> U256 {
+    if value {
+        U256::ONE
+    } else {
+        U256::ZERO
+    }
+}
+
+pub fn from_address(address: &Address) -> U256 {
+    U256::from_be_slice(address)
+}
+
+pub fn to_address(word: U256) -> Address {
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&word.to_be_bytes()[12..]);
+    address
+}
+
+#[cfg(test)]
+mod evm_tests {
+    use super::*;
+    use crate::primitives::{
+        create2_address, decode_hex, encode_hex, keccak256, parse_address, parse_word,
+    };
+
+    fn word(quantity: &str) -> U256 {
+        U256::from_be_bytes(&parse_word(quantity).unwrap())
+    }
+
+    #[test]
+    fn it_should_do_256_bit_arithmetic() {
+        let max = U256::MAX;
+        assert_eq!(word("-2"), max.wrapping_mul(word("2")));
+        assert_eq!(
+            (
+                word("0x1555555555555555555555555555555555555555555555555555555555555555"),
+                word("3")
+            ),
+            max.div_rem(word("12"))
+        );
+        assert_eq!(word("-3"), word("-7").signed_div(word("2")));
+        assert_eq!(word("-1"), word("-7").signed_rem(word("2")));
+        assert_eq!(word("1"), max.mul_mod(max, word("7")));
+        assert_eq!(word("1024"), word("2").pow(word("10")));
+        assert_eq!(word("-1"), word("0xff").sign_extend(word("0")));
+        assert_eq!(word("-1"), word("-16").sar(4));
+        assert_eq!(word("0x100000000000000000"), word("1").shl(68));
+        assert_eq!(word("1"), word("0x100000000000000000").shr(68));
+    }
+
+    #[test]
+    fn it_should_handle_signed_arithmetic_edge_cases() {
+        let min = word("0x8000000000000000000000000000000000000000000000000000000000000000");
+        assert_eq!(min, min.signed_div(word("-1")));
+        assert_eq!(U256::ZERO, word("-7").signed_div(U256::ZERO));
+        assert_eq!(U256::ZERO, word("-7").signed_rem(U256::ZERO));
+        assert_eq!(word("-1"), word("-7").signed_rem(word("-2")));
+        assert_eq!(word("1"), word("7").signed_rem(word("-2")));
+        assert_eq!(U256::MAX, word("-1").sar(255));
+        assert_eq!(U256::MAX, min.sar(shift_amount(word("256"))));
+        assert_eq!(U256::ZERO, word("1").sar(shift_amount(U256::MAX)));
+        assert_eq!(word("0x7f"), word("0x7f").sign_extend(U256::ZERO));
+        assert_eq!(word("0x80"), word("0x80").sign_extend(word("31")));
+        assert_eq!(word("-128"), word("0x80").sign_extend(U256::ZERO));
+        assert_eq!(word("0x80"), word("0x80").sign_extend(U256::MAX));
+    }
+
+    fn deploy_code(evm: &mut Evm, address: &str, code: &str) -> Address {
+        let address = parse_address(address).unwrap();
+        evm.accounts.insert(
+            address,
+            Account {
+                nonce: 1,
+                code: decode_hex(code).unwrap(),
+                ..Account::default()
+            },
+        );
+        address
+    }
+
+    #[test]
+    fn it_should_roll_back_failed_calls_and_creations() {
+        let sender = parse_address("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266").unwrap();
+        let mut evm = Evm::new();
+        evm.accounts.entry(sender).or_default().balance = word("100");
+        // Stores 1 in slot 0, then reverts.
+        let reverting = deploy_code(
+            &mut evm,
+            "0x1000000000000000000000000000000000000001",
+            "0x600160005560006000fd",
+        );
+
+        let outcome = evm
+            .transact(sender, Some(reverting), word("5"), vec![], U256::ZERO)
+            .unwrap();
+        assert!(!outcome.success);
+        assert_eq!(U256::ZERO, evm.storage(&reverting, &U256::ZERO));
+        assert_eq!(U256::ZERO, evm.accounts[&reverting].balance);
+        assert_eq!(word("100"), evm.accounts[&sender].balance);
+        assert_eq!(1, evm.accounts[&sender].nonce);
+
+        // A reverting creation still bumps the sender's nonce, but leaves no account behind.
+        let outcome = evm
+            .transact(
+                sender,
+                None,
+                U256::ZERO,
+                decode_hex("0x60006000fd").unwrap(),
+                U256::ZERO,
+            )
+            .unwrap();
+        assert_eq!(None, outcome.created);
+        assert_eq!(2, evm.accounts[&sender].nonce);
+        assert!(!evm.accounts.contains_key(&create_address(&sender, 1)));
+
+        // CREATE2 of empty init code with salt 0, storing the created address in slot 0.
+        let factory = deploy_code(
+            &mut evm,
+            "0x1000000000000000000000000000000000000002",
+            "0x6000600060006000f560005500",
+        );
+        let child = create2_address(&factory, &[0u8; 32], &keccak256(&[]));
+        evm.transact(sender, Some(factory), U256::ZERO, vec![], U256::ZERO)
+            .unwrap();
+        assert_eq!(from_address(&child), evm.storage(&factory, &U256::ZERO));
+        assert_eq!(2, evm.accounts[&factory].nonce);
+        // The second CREATE2 collides with the child, fails and still bumps the nonce.
+        evm.transact(sender, Some(factory), U256::ZERO, vec![], U256::ZERO)
+            .unwrap();
+        assert_eq!(U256::ZERO, evm.storage(&factory, &U256::ZERO));
+        assert_eq!(3, evm.accounts[&factory].nonce);
+    }
+
+    #[test]
+    fn it_should_run_delegate_and_static_calls_in_their_context() {
+        let sender = parse_address("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266").unwrap();
+        let mut evm = Evm::new();
+        // Stores the caller in slot 0.
+        let library = deploy_code(
+            &mut evm,
+            "0x1000000000000000000000000000000000000003",
+            "0x3360005500",
+        );
+        let call_library = |opcode: &str, store: &str| {
+            format!(
+                "0x600060006000600073{}6000{opcode}{store}00",
+                &encode_hex(&library)[2..]
+            )
+        };
+        let delegating = deploy_code(
+            &mut evm,
+            "0x1000000000000000000000000000000000000004",
+            &call_library("f4", "50"),
+        );
+        // Stores whether the static call failed in slot 1.
+        let static_caller = deploy_code(
+            &mut evm,
+            "0x1000000000000000000000000000000000000005",
+            &call_library("fa", "15600155"),
+        );
+
+        evm.transact(sender, Some(delegating), U256::ZERO, vec![], U256::ZERO)
+            .unwrap();
+        assert_eq!(from_address(&sender), evm.storage(&delegating, &U256::ZERO));
+        assert_eq!(U256::ZERO, evm.storage(&library, &U256::ZERO));
+
+        evm.transact(sender, Some(static_caller), U256::ZERO, vec![], U256::ZERO)
+            .unwrap();
+        assert_eq!(U256::ONE, evm.storage(&static_caller, &U256::ONE));
+        assert_eq!(U256::ZERO, evm.storage(&library, &U256::ZERO));
+    }
+
+    #[test]
+    fn it_should_cap_memory_and_reject_unsupported_precompiles() {
+        let sender = parse_address("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266").unwrap();
+        let mut evm = Evm::new();
+        // MLOAD at 32 MiB, past the memory cap.
+        let expanding = deploy_code(
+            &mut evm,
+            "0x1000000000000000000000000000000000000006",
+            "0x63020000005100",
+        );
+        let outcome = evm
+            .transact(sender, Some(expanding), U256::ZERO, vec![], U256::ZERO)
+            .unwrap();
+        assert!(!outcome.success);
+
+        let mut identity = [0u8; 20];
+        identity[19] = IDENTITY_PRECOMPILE;
+        let outcome = evm
+            .transact(sender, Some(identity), U256::ZERO, vec![0x2a], U256::ZERO)
+            .unwrap();
+        assert_eq!(vec![0x2a], outcome.output);
+        let mut ecrecover = [0u8; 20];
+        ecrecover[19] = 0x01;
+        assert_eq!(
+            Err("Precompile 0x01 is not supported".to_string()),
+            evm.transact(sender, Some(ecrecover), U256::ZERO, vec![], U256::ZERO)
+        );
+    }
+
+    #[test]
+    fn it_should_deploy_and_call_contract() {
+        let sender = parse_address("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266").unwrap();
+        assert_eq!(
+            "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+            encode_hex(&create_address(&sender, 0))
+        );
+        let mut evm = Evm::new();
+        // Init code returning a runtime code that stores its first calldata word in slot 0.
+        let init_code = decode_hex("0x6007600c60003960076000f360003560005500").unwrap();
+        let outcome = evm
+            .transact(sender, None, U256::ZERO, init_code, U256::ZERO)
+            .unwrap();
+        let contract = outcome.created.unwrap();
+        assert_eq!(decode_hex("0x60003560005500").unwrap(), evm.code(&contract));
+
+        let outcome = evm
+            .transact(
+                sender,
+                Some(contract),
+                U256::ZERO,
+                word("42").to_be_bytes().to_vec(),
+                U256::ZERO,
+            )
+            .unwrap();
+        assert!(outcome.success);
+        assert_eq!(word("42"), evm.storage(&contract, &U256::ZERO));
+        assert_eq!(2, evm.accounts[&sender].nonce);
+        assert_eq!(
+            "0xe7f1725e7734ce288f8367e1bb143e90bb3f0512",
+            encode_hex(&create_address(&sender, 1))
+        );
+    }
+}
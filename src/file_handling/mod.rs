@@ -28,6 +28,15 @@ pub fn write_transformed_broadcast_to_file(
     Ok(())
 }
 
+pub fn write_to_file(content: &str, path_to_file: &str) -> Result<(), String> {
+    let mut file = File::create(path_to_file)
+        .map_err(|err| format!("Error while creating the file: {err}"))?;
+
+    file.write_all(content.as_bytes())
+        .map_err(|err| format!("Error while writing to file: {err}"))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod file_handling_tests {
     use super::*;
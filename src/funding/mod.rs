@@ -0,0 +1,211 @@
+use crate::abi::AbiValue;
+use crate::data_model::EthenoEvent;
+use crate::evm::U256;
+use crate::primitives::{parse_word, to_checksum_address};
+
+/*//////////////////////////////////////////////////////////////
+                        SENDER FUNDING
+////////////////////////////////////////////////////////////// */
+#[derive(Debug, PartialEq, Eq)]
+pub struct SenderFunding {
+    /// EIP-55 checksummed address of the sender.
+    pub sender: String,
+    /// Sum of the values and fees (`gas_used × gas_price`) sent by the sender.
+    pub spent: U256,
+    /// The balance of its `AccountCreated` event, or the default initial balance.
+    pub balance: U256,
+    /// Index of the first event the sender can't pay for with the initial balance.
+    pub runs_dry_at: Option<usize>,
+}
+
+/// Adds up what every sender spends over the events, in order of first appearance, and finds
+/// the first event each one can't pay for. Senders start with the `balance` of their
+/// `AccountCreated` event, or `initial_balance` without one.
+pub fn funding_analysis(
+    events: &[EthenoEvent],
+    initial_balance: U256,
+) -> Result<Vec<SenderFunding>, String> {
+    let quantity = |quantity: &str| -> Result<U256, String> {
+        Ok(U256::from_be_bytes(&parse_word(quantity)?))
+    };
+    let mut balances: Vec<(String, U256)> = vec![];
+    for event in events {
+        if let EthenoEvent::AccountCreated {
+            address,
+            balance: Some(balance),
+        } = event
+        {
+            balances.push((to_checksum_address(address)?, quantity(balance)?));
+        }
+    }
+
+    let mut senders: Vec<SenderFunding> = vec![];
+    for (i, event) in events.iter().enumerate() {
+        let (from, value, gas_used, gas_price) = match event {
+            EthenoEvent::ContractCreated {
+                from,
+                value,
+                gas_used,
+                gas_price,
+                ..
+            }
+            | EthenoEvent::FunctionCall {
+                from,
+                value,
+                gas_used,
+                gas_price,
+                ..
+            } => (from, value, gas_used, gas_price),
+            EthenoEvent::AccountCreated { .. } | EthenoEvent::BlockMined { .. } => continue,
+        };
+        let sender = to_checksum_address(from)?;
+        let value = quantity(value)?;
+        let cost = quantity(gas_used)?
+            .checked_mul(quantity(gas_price)?)
+            .and_then(|fee| fee.checked_add(value))
+            .ok_or(format!(
+                "The cost of event [{i}] sent by {sender} overflows 256 bits"
+            ))?;
+
+        let index = match senders.iter().position(|funding| funding.sender == sender) {
+            Some(index) => index,
+            None => {
+                let balance = balances
+                    .iter()
+                    .rev()
+                    .find(|(address, _)| *address == sender)
+                    .map_or(initial_balance, |(_, balance)| *balance);
+                senders.push(SenderFunding {
+                    sender: sender.clone(),
+                    spent: U256::ZERO,
+                    balance,
+                    runs_dry_at: None,
+                });
+                senders.len() - 1
+            }
+        };
+        let funding = &mut senders[index];
+        funding.spent = funding.spent.checked_add(cost).ok_or(format!(
+            "What {sender} spends overflows 256 bits at event [{i}]"
+        ))?;
+        if funding.spent > funding.balance && funding.runs_dry_at.is_none() {
+            funding.runs_dry_at = Some(i);
+        }
+    }
+    Ok(senders)
+}
+
+/// What every sender spends, followed by a warning for every sender that runs dry.
+pub fn funding_report(senders: &[SenderFunding], events: &[EthenoEvent]) -> String {
+    let wei = |amount: U256| AbiValue::Uint(256, amount.to_be_bytes()).display();
+    let mut lines: Vec<String> = senders
+        .iter()
+        .map(|funding| {
+            format!(
+                "{} spends {} of {} wei",
+                funding.sender,
+                wei(funding.spent),
+                wei(funding.balance)
+            )
+        })
+        .collect();
+    for funding in senders {
+        let Some(index) = funding.runs_dry_at else {
+            continue;
+        };
+        let event = match &events[index] {
+            EthenoEvent::ContractCreated {
+                contract_address, ..
+            } => format!("the creation of {contract_address}"),
+            EthenoEvent::FunctionCall { to, .. } => format!("a call to {to}"),
+            _ => "an event".to_string(),
+        };
+        lines.push(format!(
+            "warning: sender {} runs dry at event [{index}], {event}",
+            funding.sender
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod funding_tests {
+    use super::*;
+
+    const SENDER: &str = "0x90F79bf6EB2c4f870365E785982E1f101E93b906";
+
+    fn call(value: &str, gas_used: &str) -> EthenoEvent {
+        EthenoEvent::FunctionCall {
+            from: SENDER.to_lowercase(),
+            to: "0x057ef64e23666f000b34ae31332854acbd1c8544".to_string(),
+            gas_used: gas_used.to_string(),
+            gas_price: "0x2".to_string(),
+            data: "0x".to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn it_should_find_the_event_a_sender_runs_dry_at() {
+        let other_sender = "0x0000000000000000000000000000000000010000";
+        let mut events = vec![
+            EthenoEvent::AccountCreated {
+                address: SENDER.to_string(),
+                balance: None,
+            },
+            EthenoEvent::AccountCreated {
+                address: other_sender.to_string(),
+                balance: Some("0x3".to_string()),
+            },
+            call("0x64", "0x5"),
+            call("0x0", "0xa"),
+            call("0x1", "0x0"),
+        ];
+        let mut other_call = call("0x2", "0x1");
+        if let EthenoEvent::FunctionCall { from, .. } = &mut other_call {
+            *from = other_sender.to_string();
+        }
+        events.push(other_call);
+        let balance = U256::from_u64(130);
+        let senders = funding_analysis(&events, balance).unwrap();
+
+        assert_eq!(
+            vec![
+                SenderFunding {
+                    sender: SENDER.to_string(),
+                    spent: U256::from_u64(131),
+                    balance,
+                    runs_dry_at: Some(4),
+                },
+                SenderFunding {
+                    sender: other_sender.to_string(),
+                    spent: U256::from_u64(4),
+                    balance: U256::from_u64(3),
+                    runs_dry_at: Some(5),
+                }
+            ],
+            senders
+        );
+        assert_eq!(
+            format!("{SENDER} spends 131 of 130 wei\n{other_sender} spends 4 of 3 wei\nwarning: sender {SENDER} runs dry at event [4], a call to 0x057ef64e23666f000b34ae31332854acbd1c8544\nwarning: sender {other_sender} runs dry at event [5], a call to 0x057ef64e23666f000b34ae31332854acbd1c8544"),
+            funding_report(&senders, &events)
+        );
+    }
+
+    #[test]
+    fn it_should_report_overflowing_costs() {
+        let max = format!("0x{}", "f".repeat(64));
+        assert_eq!(
+            Err(format!(
+                "The cost of event [0] sent by {SENDER} overflows 256 bits"
+            )),
+            funding_analysis(&[call("0x0", &max)], U256::ZERO)
+        );
+        assert_eq!(
+            Err(format!(
+                "What {SENDER} spends overflows 256 bits at event [1]"
+            )),
+            funding_analysis(&[call(&max, "0x0"), call("0x1", "0x0")], U256::ZERO)
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use crate::abi::{abi_value_from_json, encode, AbiEntry};
+use crate::data_model::{Broadcast, Log, Receipt, Transaction, TransactionDetails};
+use crate::file_handling::read_broadcast_file;
+use crate::primitives::{
+    create2_address, decode_hex, encode_hex, json_quantity_to_hex, json_quantity_to_u64, keccak256,
+    normalize_address, parse_address,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs::read_dir;
+use std::path::Path;
+
+/*//////////////////////////////////////////////////////////////
+                HARDHAT-DEPLOY DATA MODEL STRUCTS
+////////////////////////////////////////////////////////////// */
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct HardhatDeployment {
+    address: String,
+    transaction_hash: Option<String>,
+    receipt: Option<HardhatReceipt>,
+    #[serde(default)]
+    args: Vec<Value>,
+    bytecode: Option<String>,
+    #[serde(default)]
+    abi: Vec<AbiEntry>,
+    transaction: Option<HardhatTransaction>,
+}
+
+/// The deployment transaction, stored by deployments saved from a `TransactionResponse`.
+#[derive(Deserialize, Debug)]
+struct HardhatTransaction {
+    value: Option<Value>,
+    data: Option<String>,
+}
+
+/// hardhat-deploy stores quantities as JSON numbers or decimal strings, depending on its version.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct HardhatReceipt {
+    from: String,
+    /// The CREATE2 factory of deterministic deployments, `null` otherwise.
+    #[serde(default)]
+    to: Option<String>,
+    block_number: Value,
+    transaction_index: Value,
+    gas_used: Value,
+    effective_gas_price: Option<Value>,
+    value: Option<Value>,
+    #[serde(default)]
+    logs: Vec<Log>,
+}
+
+/*//////////////////////////////////////////////////////////////
+                    HARDHAT-DEPLOY IMPORT
+////////////////////////////////////////////////////////////// */
+/// Builds a broadcast from a `deployments/<network>` folder of hardhat-deploy.
+/// Deployments are ordered by the block number and transaction index of their receipts.
+/// The chain id is read from the `.chainId` file, if present.
+pub fn read_hardhat_deployments(deployments_dir: &Path) -> Result<Broadcast, String> {
+    let chain_id_path = deployments_dir.join(".chainId");
+    let chain_id = if chain_id_path.exists() {
+        let chain_id = read_broadcast_file(&chain_id_path.to_string_lossy())?;
+        Some(
+            chain_id
+                .trim()
+                .parse::<u64>()
+                .map_err(|err| format!("Invalid .chainId file: {err}"))?,
+        )
+    } else {
+        None
+    };
+
+    let mut paths: Vec<_> = read_dir(deployments_dir)
+        .map_err(|err| format!("Error while reading {}: {err}", deployments_dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    // Proxied deployments share their transaction with `<Name>_Proxy.json`, which holds the
+    // proxy's own bytecode and arguments, so those files are read first.
+    paths.sort_by_key(|path| {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        (!stem.ends_with("_Proxy"), stem.into_owned())
+    });
+
+    let mut seen_transactions = HashSet::new();
+    let mut deployments = vec![];
+    for path in paths {
+        let deployment: HardhatDeployment =
+            serde_json::from_str(&read_broadcast_file(&path.to_string_lossy())?)
+                .map_err(|err| format!("Failed to deserialize {}: {err}", path.display()))?;
+        let Some(receipt) = &deployment.receipt else {
+            continue;
+        };
+        if let Some(hash) = &deployment.transaction_hash {
+            if !seen_transactions.insert(hash.to_lowercase()) {
+                continue;
+            }
+        }
+        let position = (
+            json_quantity_to_u64(&receipt.block_number)?,
+            json_quantity_to_u64(&receipt.transaction_index)?,
+        );
+        deployments.push((position, path, deployment));
+    }
+    deployments.sort_by_key(|(position, _, _)| *position);
+
+    let mut broadcast = Broadcast {
+        transactions: vec![],
+        receipts: vec![],
+        libraries: vec![],
+        chain: chain_id,
+        commit: None,
+        timestamp: None,
+    };
+    for (_, path, deployment) in deployments {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned());
+        let (transaction, receipt) = deployment_to_transaction(deployment, name)
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+        broadcast.transactions.push(transaction);
+        broadcast.receipts.push(receipt);
+    }
+    Ok(broadcast)
+}
+
+fn deployment_to_transaction(
+    deployment: HardhatDeployment,
+    name: Option<String>,
+) -> Result<(Transaction, Receipt), String> {
+    let receipt = deployment.receipt.ok_or("Deployment has no receipt")?;
+    let bytecode = deployment.bytecode.ok_or("Deployment has no bytecode")?;
+    let constructor_types = deployment
+        .abi
+        .iter()
+        .find(|entry| entry.kind == "constructor")
+        .map(|constructor| {
+            constructor
+                .inputs
+                .iter()
+                .map(|input| input.abi_type())
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    if constructor_types.len() != deployment.args.len() {
+        return Err(format!(
+            "Constructor expects {} arguments, but {} were stored",
+            constructor_types.len(),
+            deployment.args.len()
+        ));
+    }
+    let arguments = deployment
+        .args
+        .iter()
+        .zip(&constructor_types)
+        .map(|(argument, abi_type)| abi_value_from_json(argument, abi_type))
+        .collect::<Result<Vec<_>, _>>()?;
+    let encoded_arguments = encode_hex(&encode(&arguments));
+    let init_code = format!("{bytecode}{}", &encoded_arguments[2..]);
+    let (stored_value, stored_data) = match deployment.transaction {
+        Some(transaction) => (transaction.value, transaction.data),
+        None => (None, None),
+    };
+    // Most deployments don't store the value they were sent with; those were sent without one.
+    let value = match stored_value.or(receipt.value) {
+        Some(value) => json_quantity_to_hex(&value)?,
+        None => "0x0".to_string(),
+    };
+    let (transaction_type, data) = match &receipt.to {
+        None => ("CREATE", init_code),
+        Some(factory) => (
+            "CREATE2",
+            deterministic_deployment_calldata(
+                &deployment.address,
+                factory,
+                &init_code,
+                stored_data,
+            )?,
+        ),
+    };
+
+    Ok((
+        Transaction {
+            transaction_type: transaction_type.to_string(),
+            contract_address: deployment.address,
+            contract_name: name,
+            additional_contracts: vec![],
+            function: None,
+            arguments: None,
+            transaction: TransactionDetails {
+                from: receipt.from,
+                to: receipt.to,
+                value,
+                data,
+            },
+        },
+        Receipt {
+            gas_used: json_quantity_to_hex(&receipt.gas_used)?,
+            effective_gas_price: match &receipt.effective_gas_price {
+                Some(price) => json_quantity_to_hex(price)?,
+                None => "0x0".to_string(),
+            },
+            logs: receipt.logs.clone(),
+        },
+    ))
+}
+
+/// Calldata sent to the CREATE2 factory by a `deterministicDeployment`: the salt followed by the
+/// init code. Deployments rarely store it, so the zero salt of `deterministicDeployment: true` is
+/// tried when they don't; deployments with another salt can't be reproduced.
+fn deterministic_deployment_calldata(
+    address: &str,
+    factory: &str,
+    init_code: &str,
+    stored_data: Option<String>,
+) -> Result<String, String> {
+    let calldata = match stored_data {
+        Some(data) => decode_hex(&data)?,
+        None => [vec![0u8; 32], decode_hex(init_code)?].concat(),
+    };
+    if calldata.len() < 32 {
+        return Err(format!(
+            "Deterministic deployment through {factory} has no salt in its calldata"
+        ));
+    }
+    let expected = encode_hex(&create2_address(
+        &parse_address(factory)?,
+        calldata[..32].try_into().unwrap_or(&[0; 32]),
+        &keccak256(&calldata[32..]),
+    ));
+    if normalize_address(address)? != expected {
+        return Err(format!(
+            "Deterministic deployment through {factory} lands at {expected}, not {address}; its salt is not stored in the deployment"
+        ));
+    }
+    Ok(encode_hex(&calldata))
+}
+
+#[cfg(test)]
+mod hardhat_deploy_tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    #[test]
+    fn it_should_read_deployments_ordered_by_block_and_index() {
+        let dir = std::env::temp_dir().join(format!(
+            "foundry2echidna_hardhat_deploy_test_{}",
+            std::process::id()
+        ));
+        let _ = remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        write(dir.join(".chainId"), "31337").unwrap();
+        write(
+            dir.join("Counter.json"),
+            r#"{
+                "address": "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad",
+                "abi": [{"type": "constructor", "inputs": [{"name": "start", "type": "uint256"}]}],
+                "transactionHash": "0x02",
+                "receipt": {
+                    "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+                    "to": null,
+                    "contractAddress": "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad",
+                    "transactionIndex": 0,
+                    "blockNumber": 2,
+                    "gasUsed": "463437"
+                },
+                "args": ["42"],
+                "bytecode": "0x6080"
+            }"#,
+        )
+        .unwrap();
+        write(
+            dir.join("Token.json"),
+            r#"{
+                "address": "0x057ef64E23666F000b34aE31332854aCBd1c8544",
+                "abi": [],
+                "transactionHash": "0x01",
+                "receipt": {
+                    "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+                    "to": null,
+                    "contractAddress": "0x057ef64E23666F000b34aE31332854aCBd1c8544",
+                    "transactionIndex": 0,
+                    "blockNumber": 1,
+                    "gasUsed": "0x6e675",
+                    "effectiveGasPrice": "0xe0fed783"
+                },
+                "args": [],
+                "bytecode": "0x6060",
+                "transaction": {"value": {"type": "BigNumber", "hex": "0x03e8"}}
+            }"#,
+        )
+        .unwrap();
+
+        let broadcast = read_hardhat_deployments(&dir).unwrap();
+
+        assert_eq!(Some(31337), broadcast.chain);
+        assert_eq!(
+            vec![
+                "0x057ef64E23666F000b34aE31332854aCBd1c8544",
+                "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad"
+            ],
+            broadcast
+                .transactions
+                .iter()
+                .map(|tx| tx.contract_address.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("0x3e8", broadcast.transactions[0].transaction.value);
+        assert_eq!("0x0", broadcast.transactions[1].transaction.value);
+        assert_eq!(
+            Some("Counter".to_string()),
+            broadcast.transactions[1].contract_name
+        );
+        assert_eq!(
+            "0x6080000000000000000000000000000000000000000000000000000000000000002a",
+            broadcast.transactions[1].transaction.data
+        );
+        assert_eq!(
+            Receipt {
+                gas_used: "0x7124d".to_string(),
+                effective_gas_price: "0x0".to_string(),
+                logs: vec![],
+            },
+            broadcast.receipts[1]
+        );
+    }
+
+    #[test]
+    fn it_should_read_deterministic_deployments_as_create2() {
+        let dir = std::env::temp_dir().join(format!(
+            "foundry2echidna_hardhat_deterministic_test_{}",
+            std::process::id()
+        ));
+        let _ = remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        let deployment = |address: &str| {
+            format!(
+                r#"{{
+                    "address": "{address}",
+                    "abi": [],
+                    "transactionHash": "0x01",
+                    "receipt": {{
+                        "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+                        "to": "0x4e59b44847b379578588920ca78fbf26c0b4956c",
+                        "contractAddress": null,
+                        "transactionIndex": 0,
+                        "blockNumber": 1,
+                        "gasUsed": "0xb3bd"
+                    }},
+                    "args": [],
+                    "bytecode": "0x6080"
+                }}"#
+            )
+        };
+        write(
+            dir.join("Counter.json"),
+            deployment("0x716d32a5431e16117f60b623aa94dd0b65812382"),
+        )
+        .unwrap();
+
+        let broadcast = read_hardhat_deployments(&dir).unwrap();
+
+        let transaction = &broadcast.transactions[0];
+        assert_eq!("CREATE2", transaction.transaction_type);
+        assert_eq!(
+            Some("0x4e59b44847b379578588920ca78fbf26c0b4956c".to_string()),
+            transaction.transaction.to
+        );
+        assert_eq!(
+            format!("0x{}6080", "00".repeat(32)),
+            transaction.transaction.data
+        );
+
+        // Deployed with the salt 0x..01, which is not stored.
+        write(
+            dir.join("Counter.json"),
+            deployment("0xd89bb721b3fef54b1b654d149c3e6fed28879f67"),
+        )
+        .unwrap();
+        assert!(read_hardhat_deployments(&dir)
+            .unwrap_err()
+            .contains("its salt is not stored in the deployment"));
+    }
+}
@@ -0,0 +1,344 @@
+use crate::data_model::{Broadcast, Log, Receipt, Transaction, TransactionDetails};
+use crate::file_handling::read_broadcast_file;
+use crate::primitives::{json_quantity_to_hex, normalize_address};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/*//////////////////////////////////////////////////////////////
+                HARDHAT IGNITION JOURNAL MESSAGES
+////////////////////////////////////////////////////////////// */
+/// The subset of `journal.jsonl` messages needed to rebuild the sent transactions.
+/// Every other message (execution state updates, fee bumps, timeouts...) is ignored.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+enum JournalMessage {
+    #[serde(rename_all = "camelCase")]
+    DeploymentInitialize { chain_id: u64 },
+    #[serde(rename_all = "camelCase")]
+    DeploymentExecutionStateInitialize {
+        future_id: String,
+        contract_name: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    NetworkInteractionRequest {
+        future_id: String,
+        network_interaction: NetworkInteraction,
+    },
+    #[serde(rename_all = "camelCase")]
+    TransactionSend { transaction: SentTransaction },
+    #[serde(rename_all = "camelCase")]
+    TransactionConfirm {
+        future_id: String,
+        network_interaction_id: u64,
+        hash: String,
+        receipt: ConfirmedReceipt,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+struct NetworkInteraction {
+    id: u64,
+    #[serde(rename = "type")]
+    kind: String,
+    from: Option<String>,
+    to: Option<String>,
+    data: Option<String>,
+    value: Option<Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SentTransaction {
+    hash: String,
+    fees: Fees,
+}
+
+/// Legacy transactions pay `gasPrice`, EIP-1559 ones at most `maxFeePerGas`, the effective
+/// price being known only from the receipt.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Fees {
+    gas_price: Option<Value>,
+    max_fee_per_gas: Option<Value>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ConfirmedReceipt {
+    block_number: u64,
+    contract_address: Option<String>,
+    status: String,
+    /// Only recorded by recent Ignition versions.
+    gas_used: Option<Value>,
+    effective_gas_price: Option<Value>,
+    #[serde(default)]
+    logs: Vec<Log>,
+}
+
+/*//////////////////////////////////////////////////////////////
+                    HARDHAT IGNITION IMPORT
+////////////////////////////////////////////////////////////// */
+/// Builds a broadcast from an `ignition/deployments/<deployment id>` folder.
+///
+/// Successful on-chain interactions of `journal.jsonl` are ordered by the block they were
+/// confirmed in. Contracts created by deployment futures are checked against
+/// `deployed_addresses.json`, if present.
+///
+/// Gas used and the effective gas price are read from the journal receipts. Journals that don't
+/// record them get `0x0` gas used and the sent gas price, or for EIP-1559 transactions the
+/// `maxFeePerGas` upper bound. The chain id is taken from the journal.
+pub fn read_ignition_deployment(deployment_dir: &Path) -> Result<Broadcast, String> {
+    let journal_path = deployment_dir.join("journal.jsonl");
+    let journal = read_broadcast_file(&journal_path.to_string_lossy())?;
+    let deployed_addresses_path = deployment_dir.join("deployed_addresses.json");
+    let deployed_addresses: HashMap<String, String> = if deployed_addresses_path.exists() {
+        serde_json::from_str(&read_broadcast_file(
+            &deployed_addresses_path.to_string_lossy(),
+        )?)
+        .map_err(|err| format!("Failed to deserialize deployed_addresses.json: {err}"))?
+    } else {
+        HashMap::new()
+    };
+
+    let mut chain_id = None;
+    let mut contract_names = HashMap::new();
+    let mut interactions = HashMap::new();
+    let mut fees = HashMap::new();
+    let mut confirmed = vec![];
+    for (i, line) in journal.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: JournalMessage = serde_json::from_str(line)
+            .map_err(|err| format!("Failed to deserialize journal line {}: {err}", i + 1))?;
+        match message {
+            JournalMessage::DeploymentInitialize { chain_id: id } => chain_id = Some(id),
+            JournalMessage::DeploymentExecutionStateInitialize {
+                future_id,
+                contract_name,
+            } => {
+                if let Some(contract_name) = contract_name {
+                    contract_names.insert(future_id, contract_name);
+                }
+            }
+            JournalMessage::NetworkInteractionRequest {
+                future_id,
+                network_interaction,
+            } => {
+                if network_interaction.kind == "ONCHAIN_INTERACTION" {
+                    interactions.insert((future_id, network_interaction.id), network_interaction);
+                }
+            }
+            JournalMessage::TransactionSend { transaction } => {
+                fees.insert(transaction.hash.to_lowercase(), transaction.fees);
+            }
+            JournalMessage::TransactionConfirm {
+                future_id,
+                network_interaction_id,
+                hash,
+                receipt,
+            } => {
+                if receipt.status == "SUCCESS" {
+                    confirmed.push((future_id, network_interaction_id, hash, receipt));
+                }
+            }
+            JournalMessage::Other => {}
+        }
+    }
+    // The sort is stable, so transactions of the same block keep their journal order.
+    confirmed.sort_by_key(|(_, _, _, receipt)| receipt.block_number);
+
+    let mut broadcast = Broadcast {
+        transactions: vec![],
+        receipts: vec![],
+        libraries: vec![],
+        chain: chain_id,
+        commit: None,
+        timestamp: None,
+    };
+    for (future_id, network_interaction_id, hash, receipt) in confirmed {
+        let interaction = interactions
+            .remove(&(future_id.clone(), network_interaction_id))
+            .ok_or(format!(
+                "Transaction {hash} of {future_id} has no network interaction request"
+            ))?;
+        let from = interaction
+            .from
+            .ok_or(format!("Transaction {hash} of {future_id} has no sender"))?;
+        let value = match &interaction.value {
+            Some(value) => json_quantity_to_hex(value)?,
+            None => "0x0".to_string(),
+        };
+        let data = interaction.data.unwrap_or("0x".to_string());
+        let (transaction_type, contract_address, to, contract_name) = match interaction.to {
+            Some(to) => ("CALL", to.clone(), Some(to), None),
+            None => {
+                let contract_address = receipt.contract_address.ok_or(format!(
+                    "Deployment {hash} of {future_id} has no contract address"
+                ))?;
+                if let Some(deployed_address) = deployed_addresses.get(&future_id) {
+                    if normalize_address(deployed_address)? != normalize_address(&contract_address)?
+                    {
+                        return Err(format!(
+                            "{future_id} was deployed at {contract_address} according to the journal, but at {deployed_address} according to deployed_addresses.json"
+                        ));
+                    }
+                }
+                (
+                    "CREATE",
+                    contract_address,
+                    None,
+                    contract_names.get(&future_id).cloned(),
+                )
+            }
+        };
+        let gas_used = match &receipt.gas_used {
+            Some(gas_used) => json_quantity_to_hex(gas_used)?,
+            None => "0x0".to_string(),
+        };
+        let gas_price = match (&receipt.effective_gas_price, fees.get(&hash.to_lowercase())) {
+            (Some(price), _) => json_quantity_to_hex(price)?,
+            (
+                None,
+                Some(Fees {
+                    gas_price: Some(price),
+                    ..
+                }),
+            )
+            | (
+                None,
+                Some(Fees {
+                    max_fee_per_gas: Some(price),
+                    ..
+                }),
+            ) => json_quantity_to_hex(price)?,
+            _ => "0x0".to_string(),
+        };
+
+        broadcast.transactions.push(Transaction {
+            transaction_type: transaction_type.to_string(),
+            contract_address,
+            contract_name,
+            additional_contracts: vec![],
+            function: None,
+            arguments: None,
+            transaction: TransactionDetails {
+                from,
+                to,
+                value,
+                data,
+            },
+        });
+        broadcast.receipts.push(Receipt {
+            gas_used,
+            effective_gas_price: gas_price,
+            logs: receipt.logs,
+        });
+    }
+    Ok(broadcast)
+}
+
+#[cfg(test)]
+mod ignition_tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    const JOURNAL: &str = r#"{"chainId":31337,"type":"DEPLOYMENT_INITIALIZE"}
+{"futureId":"Apollo#Rocket","futureType":"NAMED_ARTIFACT_CONTRACT_DEPLOYMENT","strategy":"basic","dependencies":[],"artifactId":"Apollo#Rocket","contractName":"Rocket","constructorArgs":["Saturn V"],"libraries":{},"value":{"_kind":"bigint","value":"0"},"from":"0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266","type":"DEPLOYMENT_EXECUTION_STATE_INITIALIZE"}
+{"futureId":"Apollo#Rocket","networkInteraction":{"data":"0x6080","from":"0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266","id":1,"type":"ONCHAIN_INTERACTION","value":{"_kind":"bigint","value":"0"}},"type":"NETWORK_INTERACTION_REQUEST"}
+{"futureId":"Apollo#Rocket","networkInteractionId":1,"nonce":0,"type":"TRANSACTION_PREPARE_SEND"}
+{"futureId":"Apollo#Rocket","networkInteractionId":1,"transaction":{"fees":{"maxFeePerGas":{"_kind":"bigint","value":"2000000000"},"maxPriorityFeePerGas":{"_kind":"bigint","value":"1000000000"}},"hash":"0xaa"},"type":"TRANSACTION_SEND"}
+{"futureId":"Apollo#Rocket","hash":"0xaa","networkInteractionId":1,"receipt":{"blockHash":"0x01","blockNumber":1,"contractAddress":"0x5FbDB2315678afecb367f032d93F642f64180aa3","effectiveGasPrice":{"_kind":"bigint","value":"1500000000"},"gasUsed":{"_kind":"bigint","value":"463437"},"logs":[],"status":"SUCCESS"},"type":"TRANSACTION_CONFIRM"}
+{"futureId":"Apollo#Rocket","result":{"address":"0x5FbDB2315678afecb367f032d93F642f64180aa3","type":"SUCCESS"},"type":"DEPLOYMENT_EXECUTION_STATE_COMPLETE"}
+{"futureId":"Apollo#Rocket.launch","networkInteraction":{"data":"0x01339c8f","from":"0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266","id":1,"to":"0x5FbDB2315678afecb367f032d93F642f64180aa3","type":"ONCHAIN_INTERACTION","value":{"_kind":"bigint","value":"1000"}},"type":"NETWORK_INTERACTION_REQUEST"}
+{"futureId":"Apollo#Rocket.launch","networkInteractionId":1,"transaction":{"fees":{"gasPrice":{"_kind":"bigint","value":"1875000000"}},"hash":"0xbb"},"type":"TRANSACTION_SEND"}
+{"futureId":"Apollo#Rocket.launch","hash":"0xbb","networkInteractionId":1,"receipt":{"blockHash":"0x02","blockNumber":2,"logs":[],"status":"SUCCESS"},"type":"TRANSACTION_CONFIRM"}
+"#;
+
+    fn write_deployment(name: &str, deployed_address: &str) -> std::path::PathBuf {
+        // Per process, so concurrent test runs don't share the journal.
+        let dir = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        write(dir.join("journal.jsonl"), JOURNAL).unwrap();
+        write(
+            dir.join("deployed_addresses.json"),
+            format!(r#"{{"Apollo#Rocket": "{deployed_address}"}}"#),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn it_should_read_confirmed_transactions_from_journal() {
+        let dir = write_deployment(
+            "foundry2echidna_ignition_test",
+            "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+        );
+
+        let broadcast = read_ignition_deployment(&dir).unwrap();
+
+        assert_eq!(Some(31337), broadcast.chain);
+        assert_eq!(
+            vec![
+                Transaction {
+                    transaction_type: "CREATE".to_string(),
+                    contract_address: "0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string(),
+                    contract_name: Some("Rocket".to_string()),
+                    additional_contracts: vec![],
+                    function: None,
+                    arguments: None,
+                    transaction: TransactionDetails {
+                        from: "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string(),
+                        to: None,
+                        value: "0x0".to_string(),
+                        data: "0x6080".to_string(),
+                    },
+                },
+                Transaction {
+                    transaction_type: "CALL".to_string(),
+                    contract_address: "0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string(),
+                    contract_name: None,
+                    additional_contracts: vec![],
+                    function: None,
+                    arguments: None,
+                    transaction: TransactionDetails {
+                        from: "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string(),
+                        to: Some("0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string()),
+                        value: "0x3e8".to_string(),
+                        data: "0x01339c8f".to_string(),
+                    },
+                },
+            ],
+            broadcast.transactions
+        );
+        assert_eq!(
+            vec![
+                Receipt {
+                    gas_used: "0x7124d".to_string(),
+                    effective_gas_price: "0x59682f00".to_string(),
+                    logs: vec![],
+                },
+                Receipt {
+                    gas_used: "0x0".to_string(),
+                    effective_gas_price: "0x6fc23ac0".to_string(),
+                    logs: vec![],
+                },
+            ],
+            broadcast.receipts
+        );
+    }
+
+    #[test]
+    fn it_should_reject_journal_disagreeing_with_deployed_addresses() {
+        let dir = write_deployment(
+            "foundry2echidna_ignition_mismatch_test",
+            "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512",
+        );
+
+        assert!(read_ignition_deployment(&dir).is_err());
+    }
+}
@@ -19,10 +19,41 @@
 //! `initialize: init.json`
 //!
 //! If you have any problems with the software, please open an issue on the [GitHub repo](https://github.com/ChmielewskiKamil/foundry2echidna)
+mod abi;
+mod access_control;
+mod address_book;
+mod address_lint;
+mod artifacts;
+mod cast_script;
 pub mod cli;
+mod collisions;
 mod data_model;
+mod decoder;
 mod deserialization;
+mod diff;
+mod echidna;
+mod echidna_config;
+mod evm;
 mod file_handling;
+mod funding;
+mod hardhat_deploy;
+mod ignition;
+mod libraries;
+mod minimize;
+mod primitives;
+mod proxy;
+mod proxy_map;
+mod registry;
+mod replay;
+mod reproducer;
+mod rpc;
+mod rpc_dump;
+mod rpc_import;
+mod schema;
+mod sender_remap;
 mod serialization;
+mod slicer;
+mod token_balances;
+mod validation;
 
 pub use self::cli::transform_broadcast;
@@ -0,0 +1,144 @@
+use crate::data_model::Broadcast;
+use crate::primitives::{normalize_address, to_checksum_address};
+use std::collections::HashSet;
+
+/*//////////////////////////////////////////////////////////////
+                        LINKED LIBRARIES
+////////////////////////////////////////////////////////////// */
+#[derive(Debug, PartialEq, Eq)]
+pub struct LinkedLibrary {
+    pub path: String,
+    pub name: String,
+    /// EIP-55 checksummed address.
+    pub address: String,
+}
+
+/// Parses the broadcast's `libraries` entries, formatted as `path:Name:0xaddress`.
+pub fn parse_linked_libraries(libraries: &[String]) -> Result<Vec<LinkedLibrary>, String> {
+    libraries
+        .iter()
+        .map(|library| {
+            // The path comes first, so split from the right in case it contains a colon.
+            let mut parts = library.rsplitn(3, ':');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(address), Some(name), Some(path)) => Ok(LinkedLibrary {
+                    path: path.to_string(),
+                    name: name.to_string(),
+                    address: to_checksum_address(address)?,
+                }),
+                _ => Err(format!(
+                    "Invalid library '{library}', expected 'path:Name:0xaddress'"
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Warns about the libraries the written events don't create, at the top level or from a
+/// factory. Top-level CREATE2 transactions are not written, so neither they nor their children
+/// count. Echidna would link the harness against addresses without code, unless the libraries
+/// are deployed some other way, e.g. already on the forked chain.
+pub fn undeployed_libraries_warning(
+    libraries: &[LinkedLibrary],
+    broadcast: &Broadcast,
+) -> Result<Option<String>, String> {
+    let mut deployed_addresses = HashSet::new();
+    for transaction in &broadcast.transactions {
+        match transaction.transaction_type.as_str() {
+            "CREATE" => {
+                deployed_addresses.insert(normalize_address(&transaction.contract_address)?);
+            }
+            "CALL" => {}
+            _ => continue,
+        }
+        for contract in &transaction.additional_contracts {
+            deployed_addresses.insert(normalize_address(&contract.address)?);
+        }
+    }
+    let missing: Vec<String> = libraries
+        .iter()
+        .filter(|library| {
+            normalize_address(&library.address)
+                .is_ok_and(|address| !deployed_addresses.contains(&address))
+        })
+        .map(|library| format!("{} at {}", library.name, library.address))
+        .collect();
+    if missing.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "warning: linked libraries are not deployed by the broadcast, make sure they have code when Echidna runs: {}",
+        missing.join(", ")
+    )))
+}
+
+/// crytic-compile argument linking the harness against the deployed libraries, meant for the
+/// `cryticArgs` of the Echidna config. Returns `None` if no library is linked.
+pub fn compile_libraries_argument(libraries: &[LinkedLibrary]) -> Option<String> {
+    if libraries.is_empty() {
+        return None;
+    }
+    let libraries: Vec<String> = libraries
+        .iter()
+        .map(|library| format!("({},{})", library.name, library.address))
+        .collect();
+    Some(format!("--compile-libraries={}", libraries.join(",")))
+}
+
+#[cfg(test)]
+mod libraries_tests {
+    use super::*;
+    use serde_json::json;
+
+    fn libraries() -> Vec<LinkedLibrary> {
+        parse_linked_libraries(&[
+            "src/libraries/Math.sol:Math:0x5fbdb2315678afecb367f032d93f642f64180aa3".to_string(),
+            "C:/project/src/Strings.sol:Strings:0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512"
+                .to_string(),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn it_should_parse_libraries_and_build_compile_argument() {
+        let libraries = libraries();
+
+        assert_eq!("C:/project/src/Strings.sol", libraries[1].path);
+        assert_eq!(
+            Some("--compile-libraries=(Math,0x5FbDB2315678afecb367f032d93F642f64180aa3),(Strings,0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512)".to_string()),
+            compile_libraries_argument(&libraries)
+        );
+        assert_eq!(None, compile_libraries_argument(&[]));
+    }
+
+    #[test]
+    fn it_should_warn_about_libraries_the_broadcast_does_not_create() {
+        let transaction = |transaction_type: &str, address: &str| {
+            json!({
+                "transactionType": transaction_type,
+                "contractAddress": address,
+                "transaction": {"from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "to": null, "value": "0x0", "data": "0x"},
+            })
+        };
+        let mut broadcast: Broadcast = serde_json::from_value(json!({
+            "transactions": [
+                transaction("CREATE2", "0x5FbDB2315678afecb367f032d93F642f64180aa3"),
+                transaction("CALL", "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512"),
+            ],
+            "receipts": [],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            Some("warning: linked libraries are not deployed by the broadcast, make sure they have code when Echidna runs: Math at 0x5FbDB2315678afecb367f032d93F642f64180aa3, Strings at 0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512".to_string()),
+            undeployed_libraries_warning(&libraries(), &broadcast).unwrap()
+        );
+
+        broadcast.transactions[0].transaction_type = "CREATE".to_string();
+        broadcast.transactions[1].transaction_type = "CREATE".to_string();
+        assert_eq!(
+            None,
+            undeployed_libraries_warning(&libraries(), &broadcast).unwrap()
+        );
+    }
+}
@@ -1,10 +1,9 @@
-use foundry2echidna::cli::{transform_broadcast, Args};
+use foundry2echidna::cli::Args;
 
 fn main() {
     Args::new()
-        .map(|args| (args.input_path.unwrap(), args.output_path.unwrap()))
-        .and_then(|(input_path, output_path)| transform_broadcast(&input_path, &output_path))
-        .map(|_| println!("Transformed broadcast successfully!"))
+        .and_then(|args| args.run())
+        .map(|message| println!("{message}"))
         .unwrap_or_else(|e| {
             eprintln!("Error: {e}");
             std::process::exit(1);
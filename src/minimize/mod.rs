@@ -0,0 +1,321 @@
+use crate::abi::function_selector;
+use crate::data_model::EthenoEvent;
+use crate::evm::{Account, Address, Evm, U256};
+use crate::primitives::{decode_hex, encode_hex, parse_address, parse_word};
+use crate::serialization::ANVIL_ACCOUNT_BALANCE;
+use std::collections::HashSet;
+
+/*//////////////////////////////////////////////////////////////
+                    EVM-CHECKED MINIMIZATION
+////////////////////////////////////////////////////////////// */
+/// A value of the replayed state that must not change when events are removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Probe {
+    Slot {
+        address: Address,
+        slot: U256,
+    },
+    /// A call from the zero address whose success and return data must not change.
+    Call {
+        address: Address,
+        data: Vec<u8>,
+    },
+}
+
+impl Probe {
+    /// Parses `ADDRESS:SLOT`, the slot being a decimal or `0x` prefixed hex quantity.
+    pub fn slot(probe: &str) -> Result<Self, String> {
+        let (address, slot) = split_probe(probe)?;
+        Ok(Probe::Slot {
+            address,
+            slot: U256::from_be_bytes(&parse_word(slot)?),
+        })
+    }
+
+    /// Parses `ADDRESS:CALLDATA`, the calldata being `0x` prefixed hex or the signature of a
+    /// function without arguments, like `totalSupply()`.
+    pub fn call(probe: &str) -> Result<Self, String> {
+        let (address, call) = split_probe(probe)?;
+        let data = match call.strip_prefix("0x") {
+            Some(_) => decode_hex(call)?,
+            None if call.ends_with("()") => function_selector(call).to_vec(),
+            None => {
+                return Err(format!(
+                    "Invalid call {call}: expected hex calldata or a signature like totalSupply()"
+                ))
+            }
+        };
+        Ok(Probe::Call { address, data })
+    }
+}
+
+fn split_probe(probe: &str) -> Result<(Address, &str), String> {
+    let (address, value) = probe
+        .split_once(':')
+        .ok_or(format!("Invalid probe {probe}: expected ADDRESS:VALUE"))?;
+    Ok((parse_address(address)?, value))
+}
+
+/// What the replay must reproduce: the code at the target addresses and the probed values.
+#[derive(Debug, PartialEq, Eq)]
+struct Observation {
+    codes: Vec<Vec<u8>>,
+    probes: Vec<(bool, Vec<u8>)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Minimization {
+    pub events: Vec<EthenoEvent>,
+    /// Indexes of the removed events in the original sequence.
+    pub removed: Vec<usize>,
+    /// Number of replays run to minimize the events.
+    pub replays: usize,
+}
+
+/// Removes the contract creations and function calls that don't matter, with delta debugging.
+///
+/// Every candidate sequence is replayed in an embedded EVM, and a removal is kept only if the
+/// replay still creates every contract at its recorded address, leaves the same code at the
+/// `targets` (every created contract if empty) and gives the same values for the `probes`.
+pub fn minimize_events(
+    events: &[EthenoEvent],
+    targets: &[Address],
+    probes: &[Probe],
+) -> Result<Minimization, String> {
+    let targets = match targets {
+        [] => events
+            .iter()
+            .filter_map(|event| match event {
+                EthenoEvent::ContractCreated {
+                    contract_address, ..
+                } => Some(parse_address(contract_address)),
+                _ => None,
+            })
+            .collect::<Result<Vec<_>, String>>()?,
+        targets => targets.to_vec(),
+    };
+    let expected = observe(&events.iter().collect::<Vec<_>>(), &targets, probes)
+        .map_err(|err| format!("The Etheno events do not replay: {err}"))?;
+    let mut replays = 1;
+
+    let removable: Vec<usize> = events
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| {
+            matches!(
+                event,
+                EthenoEvent::ContractCreated { .. } | EthenoEvent::FunctionCall { .. }
+            )
+        })
+        .map(|(i, _)| i)
+        .collect();
+    let mut kept = removable.clone();
+    let mut chunks = 2;
+    while !kept.is_empty() {
+        let chunk_size = kept.len().div_ceil(chunks);
+        let mut reduced = false;
+        for start in (0..kept.len()).step_by(chunk_size) {
+            let mut candidate = kept[..start].to_vec();
+            candidate.extend(&kept[(start + chunk_size).min(kept.len())..]);
+            replays += 1;
+            let candidate_events = kept_events(events, &removable, &candidate);
+            if observe(&candidate_events, &targets, probes).as_ref() == Ok(&expected) {
+                kept = candidate;
+                chunks = (chunks - 1).max(2);
+                reduced = true;
+                break;
+            }
+        }
+        if !reduced {
+            if chunk_size == 1 {
+                break;
+            }
+            chunks = (chunks * 2).min(kept.len());
+        }
+    }
+
+    let events = kept_events(events, &removable, &kept)
+        .into_iter()
+        .cloned()
+        .collect();
+    let kept: HashSet<usize> = kept.into_iter().collect();
+    Ok(Minimization {
+        events,
+        removed: removable
+            .into_iter()
+            .filter(|i| !kept.contains(i))
+            .collect(),
+        replays,
+    })
+}
+
+/// The events that are not removable, and the removable ones that are kept.
+fn kept_events<'a>(
+    events: &'a [EthenoEvent],
+    removable: &[usize],
+    kept: &[usize],
+) -> Vec<&'a EthenoEvent> {
+    let mut included = vec![true; events.len()];
+    for i in removable {
+        included[*i] = false;
+    }
+    for i in kept {
+        included[*i] = true;
+    }
+    events
+        .iter()
+        .zip(included)
+        .filter(|(_, included)| *included)
+        .map(|(event, _)| event)
+        .collect()
+}
+
+/// Replays the events in a fresh EVM. Fails if a contract is not created at its recorded address.
+fn observe(
+    events: &[&EthenoEvent],
+    targets: &[Address],
+    probes: &[Probe],
+) -> Result<Observation, String> {
+    let mut evm = Evm::new();
+    let default_balance = U256::from_be_bytes(&parse_word(ANVIL_ACCOUNT_BALANCE)?);
+    for event in events {
+        let (from, to, data, value, gas_price, expected_address) = match event {
+            EthenoEvent::AccountCreated { address, balance } => {
+                let balance = match balance {
+                    Some(balance) => U256::from_be_bytes(&parse_word(balance)?),
+                    None => default_balance,
+                };
+                evm.accounts
+                    .entry(parse_address(address)?)
+                    .or_default()
+                    .balance = balance;
+                continue;
+            }
+            EthenoEvent::BlockMined { number, timestamp } => {
+                evm.block_number = *number;
+                evm.timestamp = *timestamp;
+                continue;
+            }
+            EthenoEvent::ContractCreated {
+                from,
+                contract_address,
+                data,
+                value,
+                gas_price,
+                ..
+            } => (
+                from,
+                None,
+                data,
+                value,
+                gas_price,
+                Some(parse_address(contract_address)?),
+            ),
+            EthenoEvent::FunctionCall {
+                from,
+                to,
+                data,
+                value,
+                gas_price,
+                ..
+            } => (from, Some(parse_address(to)?), data, value, gas_price, None),
+        };
+        let from = parse_address(from)?;
+        evm.accounts.entry(from).or_insert_with(|| Account {
+            balance: default_balance,
+            ..Default::default()
+        });
+        let outcome = evm.transact(
+            from,
+            to,
+            U256::from_be_bytes(&parse_word(value)?),
+            decode_hex(data)?,
+            U256::from_be_bytes(&parse_word(gas_price)?),
+        )?;
+        if let Some(expected_address) = expected_address {
+            if outcome.created != Some(expected_address) {
+                return Err(format!(
+                    "{} is not created at its recorded address",
+                    encode_hex(&expected_address)
+                ));
+            }
+        }
+    }
+
+    let mut observation = Observation {
+        codes: targets
+            .iter()
+            .map(|target| evm.code(target).to_vec())
+            .collect(),
+        probes: vec![],
+    };
+    for probe in probes {
+        observation.probes.push(match probe {
+            Probe::Slot { address, slot } => {
+                (true, evm.storage(address, slot).to_be_bytes().to_vec())
+            }
+            Probe::Call { address, data } => evm.view_call(*address, data)?,
+        });
+    }
+    Ok(observation)
+}
+
+#[cfg(test)]
+mod minimize_tests {
+    use super::*;
+
+    const SENDER: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+    const FIRST_CONTRACT: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+    const SECOND_CONTRACT: &str = "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512";
+    /// Deploys a contract that stores its first calldata word in slot 0.
+    const STORE_INIT_CODE: &str = "0x6007600c60003960076000f360003560005500";
+
+    fn creation(contract_address: &str) -> EthenoEvent {
+        EthenoEvent::ContractCreated {
+            from: SENDER.to_string(),
+            contract_address: contract_address.to_string(),
+            gas_used: "0x0".to_string(),
+            gas_price: "0x0".to_string(),
+            data: STORE_INIT_CODE.to_string(),
+            value: "0x0".to_string(),
+        }
+    }
+
+    fn store(to: &str, value: u8) -> EthenoEvent {
+        EthenoEvent::FunctionCall {
+            from: SENDER.to_string(),
+            to: to.to_string(),
+            gas_used: "0x0".to_string(),
+            gas_price: "0x0".to_string(),
+            data: format!("0x{value:064x}"),
+            value: "0x0".to_string(),
+        }
+    }
+
+    #[test]
+    fn it_should_remove_events_that_do_not_change_probes() {
+        let events = vec![
+            EthenoEvent::AccountCreated {
+                address: SENDER.to_string(),
+                balance: None,
+            },
+            creation(FIRST_CONTRACT),
+            creation(SECOND_CONTRACT),
+            store(FIRST_CONTRACT, 1),
+            store(SECOND_CONTRACT, 9),
+            store(FIRST_CONTRACT, 7),
+        ];
+        let first_contract = parse_address(FIRST_CONTRACT).unwrap();
+        let probes = [Probe::slot(&format!("{FIRST_CONTRACT}:0")).unwrap()];
+
+        let minimization = minimize_events(&events, &[first_contract], &probes).unwrap();
+
+        // Only the first contract is checked, so the second one goes, and so does the
+        // overwritten value.
+        assert_eq!(vec![2, 3, 4], minimization.removed);
+        assert_eq!(
+            vec![events[0].clone(), events[1].clone(), events[5].clone()],
+            minimization.events
+        );
+    }
+}
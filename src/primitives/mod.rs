@@ -0,0 +1,300 @@
+use serde_json::Value;
+use tiny_keccak::{Hasher, Keccak};
+
+/*//////////////////////////////////////////////////////////////
+                        HASHING & HEX HELPERS
+////////////////////////////////////////////////////////////// */
+/// Mints come from and burns go to this address.
+pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    hasher.update(bytes);
+    hasher.finalize(&mut output);
+    output
+}
+
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("Hex string has an odd length: 0x{hex}"));
+    }
+    // `from_str_radix` accepts a sign and slicing by byte panics on multi-byte characters, so the
+    // digits are checked first.
+    if let Some(invalid) = hex.chars().find(|char| !char.is_ascii_hexdigit()) {
+        return Err(format!(
+            "Invalid hex string 0x{hex}: invalid character {invalid:?}"
+        ));
+    }
+    let nibble = |digit: u8| (digit as char).to_digit(16).unwrap_or_default() as u8;
+    Ok(hex
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| (nibble(pair[0]) << 4) | nibble(pair[1]))
+        .collect())
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("0x{hex}")
+}
+
+/// Parses a 20 byte address, with or without the `0x` prefix and in any letter case.
+pub fn parse_address(address: &str) -> Result<[u8; 20], String> {
+    let bytes = decode_hex(address)?;
+    bytes
+        .try_into()
+        .map_err(|_| format!("Invalid address length: {address}"))
+}
+
+pub fn parse_hex_u64(quantity: &str) -> Result<u64, String> {
+    let digits = quantity.strip_prefix("0x").unwrap_or(quantity);
+    if !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return Err(format!("Invalid hex quantity {quantity}"));
+    }
+    u64::from_str_radix(digits, 16).map_err(|err| format!("Invalid hex quantity {quantity}: {err}"))
+}
+
+/// Address of a contract created with CREATE2, see EIP-1014.
+pub fn create2_address(
+    deployer: &[u8; 20],
+    salt: &[u8; 32],
+    init_code_hash: &[u8; 32],
+) -> [u8; 20] {
+    let mut preimage = Vec::with_capacity(85);
+    preimage.push(0xff);
+    preimage.extend_from_slice(deployer);
+    preimage.extend_from_slice(salt);
+    preimage.extend_from_slice(init_code_hash);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&keccak256(&preimage)[12..]);
+    address
+}
+
+/// Pads an address written without leading zeros, like Echidna's `0x10000` senders.
+pub fn pad_short_address(address: &str) -> String {
+    format!("0x{:0>40}", address.trim_start_matches("0x"))
+}
+
+/// Lowercase, `0x` prefixed form of an address, used to compare addresses from different sources.
+pub fn normalize_address(address: &str) -> Result<String, String> {
+    Ok(encode_hex(&parse_address(address)?))
+}
+
+/// Returns the EIP-55 mixed-case checksum encoding of an address.
+/// Solidity only accepts address literals in this form.
+pub fn to_checksum_address(address: &str) -> Result<String, String> {
+    let address = encode_hex(&parse_address(address)?);
+    let lowercase = &address[2..];
+    let hash = keccak256(lowercase.as_bytes());
+    let checksummed: String = lowercase
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    Ok(format!("0x{checksummed}"))
+}
+
+/*//////////////////////////////////////////////////////////////
+                        256-BIT WORD HELPERS
+////////////////////////////////////////////////////////////// */
+/// Parses a decimal or `0x` prefixed hex quantity into a big-endian 32 byte word.
+/// A leading `-` produces the two's complement representation used for signed integers.
+pub fn parse_word(quantity: &str) -> Result<[u8; 32], String> {
+    let quantity = quantity.trim();
+    let (negative, digits) = match quantity.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, quantity),
+    };
+    let (radix, digits) = match digits.strip_prefix("0x") {
+        Some(hex) => (16, hex),
+        None => (10, digits),
+    };
+    if digits.is_empty() {
+        return Err(format!("Invalid quantity: '{quantity}'"));
+    }
+    let mut word = [0u8; 32];
+    for c in digits.chars() {
+        let digit = c
+            .to_digit(radix)
+            .ok_or_else(|| format!("Invalid quantity: '{quantity}'"))?;
+        let mut carry = digit;
+        for byte in word.iter_mut().rev() {
+            let product = *byte as u32 * radix + carry;
+            *byte = (product & 0xff) as u8;
+            carry = product >> 8;
+        }
+        if carry != 0 {
+            return Err(format!("Quantity does not fit in 256 bits: '{quantity}'"));
+        }
+    }
+    if negative {
+        word = negate_word(word);
+    }
+    Ok(word)
+}
+
+/// Minimal `0x` prefixed hex form of a word, as used for JSON-RPC quantities.
+pub fn format_word_hex(word: &[u8; 32]) -> String {
+    let hex = encode_hex(word);
+    let digits = hex[2..].trim_start_matches('0');
+    if digits.is_empty() {
+        "0x0".to_string()
+    } else {
+        format!("0x{digits}")
+    }
+}
+
+/// Re-emits an unsigned value or balance as a hex quantity, so that nothing from `init.json`
+/// reaches a node or a script unparsed.
+pub fn validated_quantity(quantity: &str) -> Result<String, String> {
+    if quantity.trim().starts_with('-') {
+        return Err(format!("Invalid quantity: '{quantity}'"));
+    }
+    Ok(format_word_hex(&parse_word(quantity)?))
+}
+
+/// Hex form of a quantity stored by JavaScript tooling: a JSON number, a decimal or hex string,
+/// an ethers `BigNumber` (`{"hex": ..}`) or a serialized `bigint` (`{"_kind": "bigint", "value": ..}`).
+pub fn json_quantity_to_hex(quantity: &Value) -> Result<String, String> {
+    let word = match quantity {
+        Value::Number(number) => parse_word(&number.to_string())?,
+        Value::String(number) => parse_word(number)?,
+        Value::Object(object) => parse_word(
+            ["hex", "_hex", "value"]
+                .iter()
+                .find_map(|key| object.get(*key).and_then(Value::as_str))
+                .ok_or(format!("Invalid quantity: {quantity}"))?,
+        )?,
+        _ => return Err(format!("Invalid quantity: {quantity}")),
+    };
+    Ok(format_word_hex(&word))
+}
+
+pub fn json_quantity_to_u64(quantity: &Value) -> Result<u64, String> {
+    parse_hex_u64(&json_quantity_to_hex(quantity)?)
+}
+
+pub fn negate_word(word: [u8; 32]) -> [u8; 32] {
+    let mut negated = word.map(|byte| !byte);
+    for byte in negated.iter_mut().rev() {
+        let (sum, overflow) = byte.overflowing_add(1);
+        *byte = sum;
+        if !overflow {
+            break;
+        }
+    }
+    negated
+}
+
+/// Unsigned 256-bit addition, `None` on overflow.
+pub fn checked_add_words(a: &[u8; 32], b: &[u8; 32]) -> Option<[u8; 32]> {
+    let mut sum = [0u8; 32];
+    let mut carry = 0u16;
+    for i in (0..32).rev() {
+        let digit = a[i] as u16 + b[i] as u16 + carry;
+        sum[i] = (digit & 0xff) as u8;
+        carry = digit >> 8;
+    }
+    (carry == 0).then_some(sum)
+}
+
+/// Unsigned 256-bit subtraction, `None` if `b` is larger than `a`.
+pub fn checked_sub_words(a: &[u8; 32], b: &[u8; 32]) -> Option<[u8; 32]> {
+    // Big-endian words compare like the numbers they hold.
+    if a < b {
+        return None;
+    }
+    let mut difference = [0u8; 32];
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let digit = a[i] as i16 - b[i] as i16 - borrow;
+        difference[i] = digit.rem_euclid(256) as u8;
+        borrow = (digit < 0) as i16;
+    }
+    Some(difference)
+}
+
+#[cfg(test)]
+mod primitives_tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_hash_empty_input_with_keccak256() {
+        assert_eq!(
+            "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            encode_hex(&keccak256(&[]))
+        );
+    }
+
+    #[test]
+    fn it_should_reject_odd_length_hex() {
+        assert!(decode_hex("0x6080604").is_err());
+        assert_eq!(vec![0x60, 0x80], decode_hex("0x6080").unwrap());
+    }
+
+    #[test]
+    fn it_should_compute_create2_address() {
+        // Example 5 of EIP-1014.
+        let address = create2_address(
+            &parse_address("0x00000000000000000000000000000000deadbeef").unwrap(),
+            &parse_word("0xcafebabe").unwrap(),
+            &keccak256(&decode_hex("0xdeadbeef").unwrap()),
+        );
+        assert_eq!(
+            "0x60f3f640a8508fc6a86d45df051962668e1e8ac7",
+            encode_hex(&address)
+        );
+    }
+
+    #[test]
+    fn it_should_checksum_address() {
+        assert_eq!(
+            "0x90F79bf6EB2c4f870365E785982E1f101E93b906",
+            to_checksum_address("0x90f79bf6eb2c4f870365e785982e1f101e93b906").unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_parse_decimal_hex_and_negative_words() {
+        let mut forty_two = [0u8; 32];
+        forty_two[31] = 42;
+        assert_eq!(forty_two, parse_word("42").unwrap());
+        assert_eq!(forty_two, parse_word("0x2a").unwrap());
+        assert_eq!([0xff; 32], parse_word("-1").unwrap());
+    }
+
+    #[test]
+    fn it_should_convert_json_quantities_to_hex() {
+        assert_eq!("0x7124d", json_quantity_to_hex(&json!(463437)).unwrap());
+        assert_eq!("0x7124d", json_quantity_to_hex(&json!("463437")).unwrap());
+        assert_eq!(
+            "0x7124d",
+            json_quantity_to_hex(&json!({"type": "BigNumber", "hex": "0x07124d"})).unwrap()
+        );
+        assert_eq!(
+            "0x7124d",
+            json_quantity_to_hex(&json!({"_kind": "bigint", "value": "463437"})).unwrap()
+        );
+        assert!(json_quantity_to_hex(&json!(true)).is_err());
+    }
+
+    #[test]
+    fn it_should_add_and_subtract_words() {
+        let a = parse_word("0xff").unwrap();
+        let b = parse_word("1").unwrap();
+        assert_eq!(Some(parse_word("256").unwrap()), checked_add_words(&a, &b));
+        assert_eq!(Some(parse_word("0xfe").unwrap()), checked_sub_words(&a, &b));
+        assert_eq!(None, checked_add_words(&[0xff; 32], &b));
+        assert_eq!(None, checked_sub_words(&b, &a));
+    }
+}
@@ -0,0 +1,235 @@
+use crate::data_model::{Broadcast, RpcReceipt, RpcTransaction};
+use crate::deserialization::broadcast_transaction_from_rpc;
+use crate::rpc::{read_http_request, write_http_response, RpcClient};
+use crate::serialization::serialize_broadcast;
+use serde_json::{json, Value};
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::sleep;
+use std::time::Duration;
+
+/*//////////////////////////////////////////////////////////////
+                    RECORDING JSON-RPC PROXY
+////////////////////////////////////////////////////////////// */
+const RECORDED_METHODS: [&str; 2] = ["eth_sendTransaction", "eth_sendRawTransaction"];
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// Requests are served one at a time, so an idle client must not hold the proxy.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Forwards every JSON-RPC request to the upstream node and records the transactions sent
+/// through it, like Etheno's proxy mode.
+pub struct RecordingProxy {
+    upstream: RpcClient,
+    /// Hashes of the sent transactions whose receipts were not fetched yet, in sending order.
+    pending_hashes: Vec<String>,
+    /// Serialized events of the mined transactions.
+    recorded_events: Vec<Value>,
+    sent_transactions: usize,
+}
+
+impl RecordingProxy {
+    pub fn new(upstream: RpcClient) -> Self {
+        Self {
+            upstream,
+            pending_hashes: vec![],
+            recorded_events: vec![],
+            sent_transactions: 0,
+        }
+    }
+
+    /// Number of transactions sent through the proxy so far.
+    pub fn sent_transactions(&self) -> usize {
+        self.sent_transactions
+    }
+
+    /// Hashes of the sent transactions that were not mined at the last `recorded_events`.
+    pub fn pending_transactions(&self) -> &[String] {
+        &self.pending_hashes
+    }
+
+    /// Serves requests until `stop` is set. `after_request` is called after every request with
+    /// the problem the request had, if any, and can save what was recorded so far.
+    pub fn serve(
+        &mut self,
+        listener: &TcpListener,
+        stop: &AtomicBool,
+        after_request: &mut dyn FnMut(&mut Self, Result<(), String>),
+    ) -> Result<(), String> {
+        listener
+            .set_nonblocking(true)
+            .map_err(|err| err.to_string())?;
+        while !stop.load(Ordering::SeqCst) {
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    sleep(ACCEPT_POLL_INTERVAL);
+                    continue;
+                }
+                Err(err) => return Err(format!("Failed to accept connection: {err}")),
+            };
+            stream
+                .set_nonblocking(false)
+                .and_then(|_| stream.set_read_timeout(Some(CLIENT_TIMEOUT)))
+                .and_then(|_| stream.set_write_timeout(Some(CLIENT_TIMEOUT)))
+                .map_err(|err| err.to_string())?;
+            let outcome = match read_http_request(&stream) {
+                // Browsers send a CORS preflight before the actual request.
+                Ok((method, _)) if method == "OPTIONS" => write_http_response(&stream, ""),
+                Ok((_, body)) => {
+                    let response = self.handle(&body).unwrap_or_else(|err| {
+                        json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32603, "message": err}})
+                            .to_string()
+                    });
+                    write_http_response(&stream, &response)
+                }
+                Err(err) => Err(format!("Skipping malformed request: {err}")),
+            };
+            after_request(self, outcome);
+        }
+        Ok(())
+    }
+
+    /// Forwards a single request or a batch and records the transactions it sent.
+    pub fn handle(&mut self, body: &str) -> Result<String, String> {
+        let response = self.upstream.post(body)?;
+        let (Ok(request), Ok(response_json)) = (
+            serde_json::from_str::<Value>(body),
+            serde_json::from_str::<Value>(&response),
+        ) else {
+            return Ok(response);
+        };
+        let requests = match request {
+            Value::Array(requests) => requests,
+            request => vec![request],
+        };
+        let responses = match response_json {
+            Value::Array(responses) => responses,
+            response => vec![response],
+        };
+        for request in requests {
+            let method = request.get("method").and_then(Value::as_str);
+            if !method.is_some_and(|method| RECORDED_METHODS.contains(&method)) {
+                continue;
+            }
+            let hash = responses
+                .iter()
+                .find(|response| response.get("id") == request.get("id"))
+                .and_then(|response| response.get("result"))
+                .and_then(Value::as_str);
+            if let Some(hash) = hash {
+                self.pending_hashes.push(hash.to_string());
+                self.sent_transactions += 1;
+            }
+        }
+        Ok(response)
+    }
+
+    /// Fetches the receipts of the pending transactions from the upstream node and returns the
+    /// events of the successful transactions mined so far, in the order they were sent.
+    /// Transactions that are not mined yet stay pending, with the ones sent after them.
+    pub fn recorded_events(&mut self) -> Result<Vec<Value>, String> {
+        while let Some(hash) = self.pending_hashes.first() {
+            let receipt = self
+                .upstream
+                .request("eth_getTransactionReceipt", json!([hash]))?;
+            if receipt.is_null() {
+                break;
+            }
+            let receipt: RpcReceipt = serde_json::from_value(receipt)
+                .map_err(|err| format!("Failed to deserialize receipt of {hash}: {err}"))?;
+            if receipt.status.as_deref() != Some("0x0") {
+                let transaction = self
+                    .upstream
+                    .request("eth_getTransactionByHash", json!([hash]))?;
+                let transaction: RpcTransaction = serde_json::from_value(transaction)
+                    .map_err(|err| format!("Failed to deserialize transaction {hash}: {err}"))?;
+                let (transaction, receipt) = broadcast_transaction_from_rpc(transaction, receipt)?;
+                self.recorded_events.extend(serialize_broadcast(Broadcast {
+                    transactions: vec![transaction],
+                    receipts: vec![receipt],
+                    libraries: vec![],
+                    chain: None,
+                    commit: None,
+                    timestamp: None,
+                })?);
+            }
+            self.pending_hashes.remove(0);
+        }
+        Ok(self.recorded_events.clone())
+    }
+}
+
+#[cfg(test)]
+mod proxy_tests {
+    use super::*;
+    use crate::rpc::mock_node::spawn_mock_node;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn spawn_upstream() -> String {
+        spawn_mock_node(|method, params| match method {
+            "eth_chainId" => Ok(json!("0x7a69")),
+            "eth_sendTransaction" => Ok(json!("0xaa")),
+            "eth_sendRawTransaction" => Ok(json!("0xbb")),
+            "eth_getTransactionByHash" => Ok(json!({
+                "hash": params[0],
+                "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+                "to": if params[0] == "0xaa" { Value::Null } else { json!("0x057ef64e23666f000b34ae31332854acbd1c8544") },
+                "value": "0x0",
+                "input": if params[0] == "0xaa" { "0x6080" } else { "0x202023" },
+                "gasPrice": "0xe0fed783"
+            })),
+            "eth_getTransactionReceipt" => Ok(json!({
+                "transactionHash": params[0],
+                "contractAddress": if params[0] == "0xaa" { json!("0x057ef64E23666F000b34aE31332854aCBd1c8544") } else { Value::Null },
+                "gasUsed": "0xb3bd",
+                "status": "0x1"
+            })),
+            _ => Err(format!("unexpected method {method}")),
+        })
+    }
+
+    #[test]
+    fn it_should_forward_requests_and_record_sent_transactions() {
+        let upstream = RpcClient::new(&spawn_upstream()).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+        let stop = Arc::new(AtomicBool::new(false));
+        let server_stop = stop.clone();
+        let server = thread::spawn(move || {
+            let mut proxy = RecordingProxy::new(upstream);
+            let mut problems = vec![];
+            proxy
+                .serve(&listener, &server_stop, &mut |_, outcome| {
+                    problems.extend(outcome.err())
+                })
+                .unwrap();
+            assert_eq!(Vec::<String>::new(), problems);
+            proxy
+        });
+
+        let client = RpcClient::new(&proxy_url).unwrap();
+        assert_eq!(
+            json!("0x7a69"),
+            client.request("eth_chainId", json!([])).unwrap()
+        );
+        client
+            .request("eth_sendTransaction", json!([{"data": "0x6080"}]))
+            .unwrap();
+        let batch = client
+            .post(r#"[{"jsonrpc":"2.0","id":7,"method":"eth_sendRawTransaction","params":["0x02f8"]}]"#)
+            .unwrap();
+        assert!(batch.contains("0xbb"));
+        stop.store(true, Ordering::SeqCst);
+        let mut proxy = server.join().unwrap();
+        assert_eq!(2, proxy.sent_transactions());
+
+        let expected_events = vec![
+            json!({"event":"ContractCreated","from":"0x90f79bf6eb2c4f870365e785982e1f101e93b906","contract_address":"0x057ef64E23666F000b34aE31332854aCBd1c8544","gas_used":"0xb3bd","gas_price":"0xe0fed783","data":"0x6080","value":"0x0"}),
+            json!({"event":"FunctionCall","from":"0x90f79bf6eb2c4f870365e785982e1f101e93b906","to":"0x057ef64e23666f000b34ae31332854acbd1c8544","gas_used":"0xb3bd","gas_price":"0xe0fed783","data":"0x202023","value":"0x0"}),
+        ];
+        assert_eq!(expected_events, proxy.recorded_events().unwrap());
+        assert!(proxy.pending_transactions().is_empty());
+    }
+}
@@ -0,0 +1,232 @@
+use crate::abi::{decode, encode, AbiType, AbiValue};
+use crate::data_model::{Broadcast, Log};
+use crate::primitives::{decode_hex, encode_hex, keccak256, to_checksum_address};
+use crate::registry::ContractRegistry;
+
+/*//////////////////////////////////////////////////////////////
+                        PROXY DETECTION
+////////////////////////////////////////////////////////////// */
+/// OpenZeppelin proxies and the number of addresses their constructor takes before the
+/// initialization calldata: the implementation (or beacon), then the admin of transparent proxies.
+const KNOWN_PROXIES: [(&str, usize); 3] = [
+    ("ERC1967Proxy", 1),
+    ("TransparentUpgradeableProxy", 2),
+    ("BeaconProxy", 1),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyRecord {
+    /// EIP-55 checksummed address of the proxy.
+    pub address: String,
+    pub implementation: Option<String>,
+    pub implementation_name: Option<String>,
+    pub admin: Option<String>,
+    /// Beacon of a `BeaconProxy`, which holds the implementation instead of the proxy.
+    pub beacon: Option<String>,
+}
+
+/// Finds the proxies of a broadcast and maps them to their implementation and admin.
+///
+/// Proxies are recognized by the constructor arguments of known OpenZeppelin proxy contracts and
+/// by the ERC-1967 `Upgraded`, `AdminChanged` and `BeaconUpgraded` events of the receipts. Later
+/// events override earlier ones, so the map reflects the state after the broadcast.
+pub fn detect_proxies(
+    broadcast: &Broadcast,
+    registry: &ContractRegistry,
+) -> Result<Vec<ProxyRecord>, String> {
+    let upgraded_topic = encode_hex(&keccak256(b"Upgraded(address)"));
+    let admin_changed_topic = encode_hex(&keccak256(b"AdminChanged(address,address)"));
+    let beacon_upgraded_topic = encode_hex(&keccak256(b"BeaconUpgraded(address)"));
+
+    let mut proxies: Vec<ProxyRecord> = vec![];
+    for (transaction, receipt) in broadcast.transactions.iter().zip(&broadcast.receipts) {
+        let known_proxy = KNOWN_PROXIES
+            .iter()
+            .find(|(name, _)| transaction.contract_name.as_deref() == Some(*name));
+        if let (Some((name, address_count)), "CREATE") =
+            (known_proxy, transaction.transaction_type.as_str())
+        {
+            let init_code = decode_hex(&transaction.transaction.data)?;
+            let arguments = trailing_proxy_arguments(&init_code, *address_count).ok_or(format!(
+                "Failed to find the constructor arguments of {name} at {}",
+                transaction.contract_address
+            ))?;
+            let proxy = proxy_entry(&mut proxies, &transaction.contract_address)?;
+            let target = Some(arguments[0].display());
+            match *name {
+                "BeaconProxy" => proxy.beacon = target,
+                _ => proxy.implementation = target,
+            }
+            if *address_count == 2 {
+                proxy.admin = Some(arguments[1].display());
+            }
+        }
+
+        for log in &receipt.logs {
+            let Some(topic) = log.topics.first() else {
+                continue;
+            };
+            let topic = topic.to_lowercase();
+            if topic == upgraded_topic {
+                let implementation = indexed_address(log)?;
+                proxy_entry(&mut proxies, &log.address)?.implementation = Some(implementation);
+            } else if topic == beacon_upgraded_topic {
+                let beacon = indexed_address(log)?;
+                proxy_entry(&mut proxies, &log.address)?.beacon = Some(beacon);
+            } else if topic == admin_changed_topic {
+                let values = decode(
+                    &[AbiType::Address, AbiType::Address],
+                    &decode_hex(&log.data)?,
+                )
+                .map_err(|err| format!("Invalid AdminChanged event of {}: {err}", log.address))?;
+                proxy_entry(&mut proxies, &log.address)?.admin = Some(values[1].display());
+            }
+        }
+    }
+
+    for proxy in &mut proxies {
+        proxy.implementation_name = proxy.implementation.as_ref().and_then(|implementation| {
+            registry
+                .contracts
+                .iter()
+                .find(|contract| contract.address == *implementation)
+                .and_then(|contract| contract.name.clone())
+        });
+    }
+    Ok(proxies)
+}
+
+fn proxy_entry<'a>(
+    proxies: &'a mut Vec<ProxyRecord>,
+    address: &str,
+) -> Result<&'a mut ProxyRecord, String> {
+    let address = to_checksum_address(address)?;
+    let index = match proxies.iter().position(|proxy| proxy.address == address) {
+        Some(index) => index,
+        None => {
+            proxies.push(ProxyRecord {
+                address,
+                implementation: None,
+                implementation_name: None,
+                admin: None,
+                beacon: None,
+            });
+            proxies.len() - 1
+        }
+    };
+    Ok(&mut proxies[index])
+}
+
+fn indexed_address(log: &Log) -> Result<String, String> {
+    let topic = log
+        .topics
+        .get(1)
+        .ok_or(format!("Event of {} has no indexed address", log.address))?;
+    let word = decode_hex(topic)?;
+    if word.len() != 32 {
+        return Err(format!("Invalid topic {topic} of {}", log.address));
+    }
+    to_checksum_address(&encode_hex(&word[12..]))
+}
+
+/// Finds the `(address..., bytes)` constructor arguments appended to the init code.
+///
+/// The length of the bytecode is unknown, so every word aligned suffix is tried, shortest first,
+/// until one is the canonical encoding of such arguments.
+fn trailing_proxy_arguments(init_code: &[u8], address_count: usize) -> Option<Vec<AbiValue>> {
+    let mut types = vec![AbiType::Address; address_count];
+    types.push(AbiType::Bytes);
+    ((address_count + 2) * 32..=init_code.len())
+        .step_by(32)
+        .find_map(|length| {
+            let arguments = &init_code[init_code.len() - length..];
+            let values = decode(&types, arguments).ok()?;
+            (encode(&values) == arguments).then_some(values)
+        })
+}
+
+#[cfg(test)]
+mod proxy_map_tests {
+    use super::*;
+    use crate::deserialization::deserialize_broadcast;
+
+    const TOKEN: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+    const TOKEN_V2: &str = "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512";
+    const PROXY: &str = "0x9fE46736679d2D9a65F0992F2272dE9f3c7fa6e0";
+    const TRANSPARENT_PROXY: &str = "0xCf7Ed3AccA5a467e9e704C703E8D87F634fB0Fc9";
+    const ADMIN: &str = "0x90F79bf6EB2c4f870365E785982E1f101E93b906";
+
+    fn proxy_broadcast() -> Broadcast {
+        let word = |address: &str| format!("{:0>64}", &address[2..].to_lowercase());
+        // `(address, address, bytes)` arguments with a 4 byte `initialize()` calldata.
+        let transparent_init_code = format!(
+            "0x60806040{}{}{:0>64x}{:0>64x}{:0<64}",
+            word(TOKEN),
+            word(ADMIN),
+            0x60,
+            4,
+            "8129fc1c"
+        );
+        let transaction = |kind: &str, name: &str, address: &str, data: &str| {
+            format!(
+                r#"{{"transactionType": "{kind}", "contractName": "{name}", "contractAddress": "{address}",
+                    "transaction": {{"from": "{ADMIN}", "to": null, "value": "0x0", "data": "{data}"}}}}"#
+            )
+        };
+        deserialize_broadcast(&format!(
+            r#"{{
+                "transactions": [{}, {}, {}, {}],
+                "receipts": [
+                    {{"gasUsed": "0x1", "effectiveGasPrice": "0x1"}},
+                    {{"gasUsed": "0x1", "effectiveGasPrice": "0x1"}},
+                    {{"gasUsed": "0x1", "effectiveGasPrice": "0x1", "logs": [
+                        {{"address": "{PROXY}", "topics": ["{}", "0x{}"], "data": "0x"}},
+                        {{"address": "{PROXY}", "topics": ["{}"], "data": "0x{}{}"}}
+                    ]}},
+                    {{"gasUsed": "0x1", "effectiveGasPrice": "0x1"}}
+                ]
+            }}"#,
+            transaction("CREATE", "Token", TOKEN, "0x6080"),
+            transaction("CREATE", "Token", TOKEN_V2, "0x6080"),
+            transaction("CREATE", "UUPSProxy", PROXY, "0x6080"),
+            transaction(
+                "CREATE",
+                "TransparentUpgradeableProxy",
+                TRANSPARENT_PROXY,
+                &transparent_init_code
+            ),
+            encode_hex(&keccak256(b"Upgraded(address)")),
+            word(TOKEN_V2),
+            encode_hex(&keccak256(b"AdminChanged(address,address)")),
+            word("0x0000000000000000000000000000000000000000"),
+            word(ADMIN),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_should_map_proxies_from_logs_and_constructor_arguments() {
+        let broadcast = proxy_broadcast();
+        let registry = ContractRegistry::from_broadcast(&broadcast).unwrap();
+
+        assert_eq!(
+            vec![
+                ProxyRecord {
+                    address: PROXY.to_string(),
+                    implementation: Some(TOKEN_V2.to_string()),
+                    implementation_name: Some("Token".to_string()),
+                    admin: Some(ADMIN.to_string()),
+                    beacon: None,
+                },
+                ProxyRecord {
+                    address: TRANSPARENT_PROXY.to_string(),
+                    implementation: Some(TOKEN.to_string()),
+                    implementation_name: Some("Token".to_string()),
+                    admin: Some(ADMIN.to_string()),
+                    beacon: None,
+                },
+            ],
+            detect_proxies(&broadcast, &registry).unwrap()
+        );
+    }
+}
@@ -0,0 +1,317 @@
+use crate::data_model::Broadcast;
+use crate::primitives::{
+    create2_address, decode_hex, encode_hex, keccak256, normalize_address, parse_address,
+    to_checksum_address,
+};
+
+/*//////////////////////////////////////////////////////////////
+                        CONTRACT REGISTRY
+////////////////////////////////////////////////////////////// */
+/// The deterministic deployment proxy used by Foundry for `new Contract{salt: ...}()` in scripts.
+/// Its calldata is the salt followed by the init code.
+pub const CREATE2_DEPLOYER: &str = "0x4e59b44847b379578588920ca78fbf26c0b4956c";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ContractRecord {
+    /// EIP-55 checksummed address.
+    pub address: String,
+    pub name: Option<String>,
+    /// `CREATE` or `CREATE2`.
+    pub creation_kind: String,
+    /// The sender of a top-level creation, or the factory that created a child contract.
+    pub deployer: String,
+    /// Index of the creating transaction in the broadcast.
+    pub transaction_index: usize,
+    /// Whether the contract comes from `additionalContracts`, i.e. was created by a factory.
+    pub from_factory: bool,
+}
+
+/// Every contract created by a broadcast, including the children of factories.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ContractRegistry {
+    pub contracts: Vec<ContractRecord>,
+    /// Problems that did not prevent building the registry, like CREATE2 children whose salt
+    /// could not be found.
+    pub warnings: Vec<String>,
+}
+
+impl ContractRegistry {
+    /// Registers the contracts created by the broadcast in creation order.
+    ///
+    /// CREATE2 addresses are verified against the EIP-1014 formula. The salt is known for the
+    /// deterministic deployment proxy, so a mismatch there is an error. For other factories,
+    /// the salt is searched in the calldata, and a warning is recorded if none matches.
+    pub fn from_broadcast(broadcast: &Broadcast) -> Result<Self, String> {
+        let mut registry = Self::default();
+        for (i, transaction) in broadcast.transactions.iter().enumerate() {
+            let details = &transaction.transaction;
+            let to = details.to.as_deref().map(normalize_address).transpose()?;
+            // Contracts of this transaction that may have created the following children.
+            let mut factories = vec![];
+
+            match transaction.transaction_type.as_str() {
+                "CREATE" => {
+                    registry.register(
+                        &transaction.contract_address,
+                        transaction.contract_name.clone(),
+                        "CREATE",
+                        &details.from,
+                        i,
+                        false,
+                    )?;
+                    factories.push(normalize_address(&transaction.contract_address)?);
+                }
+                "CREATE2" => {
+                    let deployer = to.clone().unwrap_or(CREATE2_DEPLOYER.to_string());
+                    let data = decode_hex(&details.data)?;
+                    if data.len() < 32 {
+                        return Err(format!(
+                            "CREATE2 transaction {i} has no salt in its calldata"
+                        ));
+                    }
+                    verify_create2_address(
+                        &transaction.contract_address,
+                        &deployer,
+                        data[..32].try_into().unwrap_or_default(),
+                        &data[32..],
+                    )?;
+                    registry.register(
+                        &transaction.contract_address,
+                        transaction.contract_name.clone(),
+                        "CREATE2",
+                        &details.from,
+                        i,
+                        false,
+                    )?;
+                    factories.push(normalize_address(&transaction.contract_address)?);
+                }
+                _ => factories.extend(to.clone()),
+            }
+
+            for child in &transaction.additional_contracts {
+                let deployer = match child.transaction_type.as_str() {
+                    "CREATE2" => {
+                        let init_code = decode_hex(&child.init_code)?;
+                        match find_create2_deployer(
+                            &child.address,
+                            &factories,
+                            &decode_hex(&details.data)?,
+                            &init_code,
+                        )? {
+                            Some(deployer) => deployer,
+                            None if to.as_deref() == Some(CREATE2_DEPLOYER) => {
+                                return Err(format!(
+                                    "CREATE2 child {} of transaction {i} does not match the salt and init code sent to the deployment proxy",
+                                    child.address
+                                ))
+                            }
+                            None => {
+                                registry.warnings.push(format!(
+                                    "Could not verify the CREATE2 address of {}: its salt was not found in the calldata of transaction {i}",
+                                    child.address
+                                ));
+                                factories.first().cloned().unwrap_or(details.from.clone())
+                            }
+                        }
+                    }
+                    // The nonce of the factory is unknown, so CREATE children are not verified.
+                    _ => factories.first().cloned().unwrap_or(details.from.clone()),
+                };
+                registry.register(
+                    &child.address,
+                    child.contract_name.clone(),
+                    &child.transaction_type,
+                    &deployer,
+                    i,
+                    true,
+                )?;
+                factories.push(normalize_address(&child.address)?);
+            }
+        }
+        Ok(registry)
+    }
+
+    fn register(
+        &mut self,
+        address: &str,
+        name: Option<String>,
+        creation_kind: &str,
+        deployer: &str,
+        transaction_index: usize,
+        from_factory: bool,
+    ) -> Result<(), String> {
+        self.contracts.push(ContractRecord {
+            address: to_checksum_address(address)?,
+            name,
+            creation_kind: creation_kind.to_string(),
+            deployer: to_checksum_address(deployer)?,
+            transaction_index,
+            from_factory,
+        });
+        Ok(())
+    }
+
+    /// Human readable listing of the registered contracts followed by the warnings.
+    pub fn report(&self) -> String {
+        let mut lines: Vec<String> = self
+            .contracts
+            .iter()
+            .map(|contract| {
+                format!(
+                    "[{}] {} {} {} ({} by {})",
+                    contract.transaction_index,
+                    contract.creation_kind,
+                    contract.address,
+                    contract.name.as_deref().unwrap_or("<unnamed>"),
+                    if contract.from_factory {
+                        "created"
+                    } else {
+                        "deployed"
+                    },
+                    contract.deployer
+                )
+            })
+            .collect();
+        lines.extend(
+            self.warnings
+                .iter()
+                .map(|warning| format!("warning: {warning}")),
+        );
+        lines.join("\n")
+    }
+}
+
+fn verify_create2_address(
+    address: &str,
+    deployer: &str,
+    salt: [u8; 32],
+    init_code: &[u8],
+) -> Result<(), String> {
+    let expected = encode_hex(&create2_address(
+        &parse_address(deployer)?,
+        &salt,
+        &keccak256(init_code),
+    ));
+    if normalize_address(address)? != expected {
+        return Err(format!(
+            "CREATE2 address mismatch: {address} was recorded, but {deployer} deploys that init code with salt {} to {expected}",
+            encode_hex(&salt)
+        ));
+    }
+    Ok(())
+}
+
+/// Searches the factories and every 32 byte window of the calldata for the deployer and salt
+/// that produce `address`.
+fn find_create2_deployer(
+    address: &str,
+    factories: &[String],
+    calldata: &[u8],
+    init_code: &[u8],
+) -> Result<Option<String>, String> {
+    let address = parse_address(address)?;
+    let init_code_hash = keccak256(init_code);
+    for factory in factories {
+        let factory_bytes = parse_address(factory)?;
+        let found = calldata.windows(32).any(|salt| {
+            let salt: [u8; 32] = salt.try_into().unwrap_or_default();
+            create2_address(&factory_bytes, &salt, &init_code_hash) == address
+        });
+        if found {
+            return Ok(Some(factory.clone()));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+    use crate::deserialization::deserialize_broadcast;
+    use crate::file_handling::read_broadcast_file;
+
+    const FACTORY: &str = "0x057ef64e23666f000b34ae31332854acbd1c8544";
+    const SALT: &str = "0x000000000000000000000000000000000000000000000000000000000000002a";
+
+    fn create2_child_broadcast(child_address: &str, calldata: &str) -> Broadcast {
+        deserialize_broadcast(&format!(
+            r#"{{
+                "transactions": [{{
+                    "transactionType": "CALL",
+                    "contractName": "Factory",
+                    "contractAddress": "{FACTORY}",
+                    "transaction": {{
+                        "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+                        "to": "{FACTORY}",
+                        "value": "0x0",
+                        "data": "{calldata}"
+                    }},
+                    "additionalContracts": [{{
+                        "transactionType": "CREATE2",
+                        "contractName": "Child",
+                        "address": "{child_address}",
+                        "initCode": "0x6080"
+                    }}]
+                }}],
+                "receipts": [{{"gasUsed": "0xb3bd", "effectiveGasPrice": "0x1"}}]
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    fn expected_child_address() -> String {
+        encode_hex(&create2_address(
+            &parse_address(FACTORY).unwrap(),
+            &decode_hex(SALT).unwrap().try_into().unwrap(),
+            &keccak256(&decode_hex("0x6080").unwrap()),
+        ))
+    }
+
+    #[test]
+    fn it_should_register_factory_children_of_broadcast() {
+        let broadcast = deserialize_broadcast(
+            &read_broadcast_file("test_json_files/test_broadcast.json").unwrap(),
+        )
+        .unwrap();
+
+        let registry = ContractRegistry::from_broadcast(&broadcast).unwrap();
+
+        assert_eq!(
+            ContractRecord {
+                address: "0xF3dfB0A70010735B0A14B4A69aFC242b19600049".to_string(),
+                name: None,
+                creation_kind: "CREATE".to_string(),
+                deployer: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+                transaction_index: 3,
+                from_factory: true,
+            },
+            registry.contracts[2]
+        );
+        assert_eq!(
+            Some("PrivacyFactory"),
+            registry.contracts[1].name.as_deref()
+        );
+        assert!(registry.warnings.is_empty());
+    }
+
+    #[test]
+    fn it_should_verify_create2_child_with_salt_from_calldata() {
+        let calldata = format!("0x12345678{}", &SALT[2..]);
+        let broadcast = create2_child_broadcast(&expected_child_address(), &calldata);
+
+        let registry = ContractRegistry::from_broadcast(&broadcast).unwrap();
+
+        assert_eq!(1, registry.contracts.len());
+        assert_eq!("CREATE2", registry.contracts[0].creation_kind);
+        assert!(registry.warnings.is_empty());
+    }
+
+    #[test]
+    fn it_should_warn_about_create2_child_with_unknown_salt() {
+        let broadcast = create2_child_broadcast(&expected_child_address(), "0x12345678");
+
+        let registry = ContractRegistry::from_broadcast(&broadcast).unwrap();
+
+        assert_eq!(1, registry.warnings.len());
+    }
+}
@@ -0,0 +1,319 @@
+use crate::data_model::EthenoEvent;
+use crate::primitives::{decode_hex, encode_hex, normalize_address, validated_quantity};
+use crate::rpc::RpcClient;
+use crate::serialization::ANVIL_ACCOUNT_BALANCE;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::thread::sleep;
+use std::time::Duration;
+
+/*//////////////////////////////////////////////////////////////
+                    REPLAY AGAINST A JSON-RPC NODE
+////////////////////////////////////////////////////////////// */
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const RECEIPT_POLL_ATTEMPTS: usize = 300;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    pub sent_transactions: usize,
+    /// Human readable descriptions of every event that did not reproduce.
+    pub mismatches: Vec<String>,
+}
+
+/// Sends the events to the node, with their values and data re-encoded, and checks that every
+/// contract lands at its recorded address. Senders get the balance of their `AccountCreated`
+/// event, if any.
+pub fn replay_events(client: &RpcClient, events: &[EthenoEvent]) -> Result<ReplayReport, String> {
+    let mut report = ReplayReport::default();
+    let unlocked_accounts = unlocked_accounts(client)?;
+    let mut prepared_senders = HashSet::new();
+    let mut balances: HashMap<String, String> = HashMap::new();
+    for event in events {
+        if let EthenoEvent::AccountCreated {
+            address,
+            balance: Some(balance),
+        } = event
+        {
+            balances.insert(normalize_address(address)?, validated_quantity(balance)?);
+        }
+    }
+
+    for (i, event) in events.iter().enumerate() {
+        let (from, transaction, expected_address) = match event {
+            EthenoEvent::ContractCreated {
+                from,
+                contract_address,
+                data,
+                value,
+                ..
+            } => (
+                from,
+                json!({
+                    "from": normalize_address(from)?,
+                    "data": encode_hex(&decode_hex(data)?),
+                    "value": validated_quantity(value)?,
+                }),
+                Some(normalize_address(contract_address)?),
+            ),
+            EthenoEvent::FunctionCall {
+                from,
+                to,
+                data,
+                value,
+                ..
+            } => (
+                from,
+                json!({
+                    "from": normalize_address(from)?,
+                    "to": normalize_address(to)?,
+                    "data": encode_hex(&decode_hex(data)?),
+                    "value": validated_quantity(value)?,
+                }),
+                None,
+            ),
+            EthenoEvent::AccountCreated { .. } | EthenoEvent::BlockMined { .. } => continue,
+        };
+
+        let from = normalize_address(from)?;
+        if prepared_senders.insert(from.clone()) {
+            prepare_sender(
+                client,
+                &from,
+                unlocked_accounts.contains(&from),
+                balances.get(&from).map(String::as_str),
+            )?;
+        }
+
+        let hash = client.request("eth_sendTransaction", json!([transaction]))?;
+        report.sent_transactions += 1;
+        let receipt = wait_for_receipt(client, &hash)?;
+
+        if receipt.get("status").and_then(Value::as_str) == Some("0x0") {
+            report
+                .mismatches
+                .push(format!("[{i}] transaction {hash} reverted"));
+        }
+        if let Some(expected_address) = expected_address {
+            let actual_address = receipt
+                .get("contractAddress")
+                .and_then(Value::as_str)
+                .map(normalize_address)
+                .transpose()?;
+            if actual_address.as_ref() != Some(&expected_address) {
+                report.mismatches.push(format!(
+                    "[{i}] expected contract at {expected_address}, got {}",
+                    actual_address.unwrap_or("no contract".to_string())
+                ));
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn unlocked_accounts(client: &RpcClient) -> Result<HashSet<String>, String> {
+    let accounts = client.request("eth_accounts", json!([]))?;
+    accounts
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(Value::as_str)
+        .map(normalize_address)
+        .collect()
+}
+
+/// Impersonates senders the node holds no key for. Senders get their `AccountCreated` balance,
+/// or anvil's balance if they have none.
+fn prepare_sender(
+    client: &RpcClient,
+    sender: &str,
+    unlocked: bool,
+    balance: Option<&str>,
+) -> Result<(), String> {
+    if !unlocked {
+        client.request("anvil_impersonateAccount", json!([sender]))?;
+    }
+    if let Some(balance) = balance {
+        client.request("anvil_setBalance", json!([sender, balance]))?;
+        return Ok(());
+    }
+    let balance = client.request("eth_getBalance", json!([sender, "latest"]))?;
+    if matches!(balance.as_str(), Some("0x0") | Some("0x")) {
+        client.request("anvil_setBalance", json!([sender, ANVIL_ACCOUNT_BALANCE]))?;
+    }
+    Ok(())
+}
+
+fn wait_for_receipt(client: &RpcClient, hash: &Value) -> Result<Value, String> {
+    for _ in 0..RECEIPT_POLL_ATTEMPTS {
+        let receipt = client.request("eth_getTransactionReceipt", json!([hash]))?;
+        if !receipt.is_null() {
+            return Ok(receipt);
+        }
+        sleep(RECEIPT_POLL_INTERVAL);
+    }
+    Err(format!("Timed out waiting for the receipt of {hash}"))
+}
+
+#[cfg(test)]
+mod replay_tests {
+    use super::*;
+    use crate::rpc::mock_node::spawn_mock_node;
+    use std::sync::{Arc, Mutex};
+
+    const SENDER: &str = "0x90f79bf6eb2c4f870365e785982e1f101e93b906";
+    const CONTRACT: &str = "0x057ef64e23666f000b34ae31332854acbd1c8544";
+
+    fn events() -> Vec<EthenoEvent> {
+        vec![
+            EthenoEvent::AccountCreated {
+                address: SENDER.to_string(),
+                balance: None,
+            },
+            EthenoEvent::ContractCreated {
+                from: SENDER.to_string(),
+                contract_address: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+                gas_used: "0x6e675".to_string(),
+                gas_price: "0xe0fed783".to_string(),
+                data: "0x6080".to_string(),
+                value: "0x0".to_string(),
+            },
+            EthenoEvent::FunctionCall {
+                from: SENDER.to_string(),
+                to: CONTRACT.to_string(),
+                gas_used: "0xb3bd".to_string(),
+                gas_price: "0xe0fed783".to_string(),
+                data: "0x202023".to_string(),
+                value: "0x0".to_string(),
+            },
+        ]
+    }
+
+    /// Mock node on which `SENDER` is locked and empty, and which deploys to `deployed_address`.
+    fn spawn_node(
+        deployed_address: &'static str,
+        calls: Arc<Mutex<Vec<(String, Value)>>>,
+    ) -> String {
+        spawn_mock_node(move |method, params| {
+            calls
+                .lock()
+                .unwrap()
+                .push((method.to_string(), params.clone()));
+            match method {
+                "eth_accounts" => Ok(json!([])),
+                "anvil_impersonateAccount" | "anvil_setBalance" => Ok(Value::Null),
+                "eth_getBalance" => Ok(json!("0x0")),
+                "eth_sendTransaction" => Ok(if params[0].get("to").is_some() {
+                    json!("0x02")
+                } else {
+                    json!("0x01")
+                }),
+                "eth_getTransactionReceipt" => Ok(if params[0] == json!("0x01") {
+                    json!({"status": "0x1", "contractAddress": deployed_address})
+                } else {
+                    json!({"status": "0x1", "contractAddress": null})
+                }),
+                _ => Err(format!("unexpected method {method}")),
+            }
+        })
+    }
+
+    #[test]
+    fn it_should_replay_events_and_impersonate_locked_senders() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let client = RpcClient::new(&spawn_node(CONTRACT, calls.clone())).unwrap();
+
+        let report = replay_events(&client, &events()).unwrap();
+
+        assert_eq!(
+            ReplayReport {
+                sent_transactions: 2,
+                mismatches: vec![],
+            },
+            report
+        );
+        let calls = calls.lock().unwrap();
+        assert_eq!(
+            vec![
+                "eth_accounts",
+                "anvil_impersonateAccount",
+                "eth_getBalance",
+                "anvil_setBalance",
+                "eth_sendTransaction",
+                "eth_getTransactionReceipt",
+                "eth_sendTransaction",
+                "eth_getTransactionReceipt",
+            ],
+            calls
+                .iter()
+                .map(|(method, _)| method.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_should_normalize_values_and_seed_account_balances() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let client = RpcClient::new(&spawn_node(CONTRACT, calls.clone())).unwrap();
+        let mut events = events();
+        events[0] = EthenoEvent::AccountCreated {
+            address: SENDER.to_string(),
+            balance: Some("1000".to_string()),
+        };
+        if let EthenoEvent::FunctionCall { value, data, .. } = &mut events[2] {
+            *value = "256".to_string();
+            *data = "0x20ABCD".to_string();
+        }
+
+        replay_events(&client, &events).unwrap();
+
+        {
+            let calls = calls.lock().unwrap();
+            let params = |method: &str| -> Vec<&Value> {
+                calls
+                    .iter()
+                    .filter(|(called, _)| called == method)
+                    .map(|(_, params)| params)
+                    .collect()
+            };
+            assert_eq!(vec![&json!([SENDER, "0x3e8"])], params("anvil_setBalance"));
+            assert!(params("eth_getBalance").is_empty());
+            assert_eq!(
+                json!({"from": SENDER, "to": CONTRACT, "data": "0x20abcd", "value": "0x100"}),
+                params("eth_sendTransaction")[1][0]
+            );
+        }
+
+        events[2] = EthenoEvent::FunctionCall {
+            from: SENDER.to_string(),
+            to: CONTRACT.to_string(),
+            gas_used: "0x0".to_string(),
+            gas_price: "0x0".to_string(),
+            data: "0x".to_string(),
+            value: "-1".to_string(),
+        };
+        assert_eq!(
+            Err("Invalid quantity: '-1'".to_string()),
+            replay_events(&client, &events)
+        );
+    }
+
+    #[test]
+    fn it_should_report_contract_address_mismatch() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let client = RpcClient::new(&spawn_node(
+            "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad",
+            calls,
+        ))
+        .unwrap();
+
+        let report = replay_events(&client, &events()).unwrap();
+
+        assert_eq!(
+            vec![format!(
+                "[1] expected contract at {CONTRACT}, got 0x261d8c5e9742e6f7f1076fa1f560894524e19cad"
+            )],
+            report.mismatches
+        );
+    }
+}
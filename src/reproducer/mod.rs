@@ -0,0 +1,516 @@
+use crate::abi::{encode_function_call, AbiType, AbiValue};
+use crate::data_model::EthenoEvent;
+use crate::primitives::{
+    decode_hex, encode_hex, normalize_address, parse_address, parse_word, to_checksum_address,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/*//////////////////////////////////////////////////////////////
+                    ECHIDNA REPRODUCER MODEL
+////////////////////////////////////////////////////////////// */
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct EchidnaTx {
+    pub call: EchidnaCall,
+    pub src: String,
+    pub dst: String,
+    #[serde(default)]
+    pub value: Quantity,
+    #[serde(default)]
+    pub delay: (Quantity, Quantity),
+}
+
+/// Echidna writes numbers either as JSON numbers or as decimal/hex strings,
+/// depending on the version and on the size of the value. JSON numbers keep all their digits,
+/// so uint256 values beyond 2^64 are exact.
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Quantity {
+    Number(serde_json::Number),
+    Text(String),
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "tag", content = "contents")]
+pub enum EchidnaCall {
+    SolCall(String, Vec<EchidnaAbiValue>),
+    SolCalldata(String),
+    NoCall,
+}
+
+/// Variant names mirror the tags Echidna (hevm) uses in its JSON output.
+#[allow(clippy::enum_variant_names)]
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "tag", content = "contents")]
+pub enum EchidnaAbiValue {
+    AbiUInt(usize, Quantity),
+    AbiInt(usize, Quantity),
+    AbiAddress(String),
+    AbiBool(bool),
+    AbiBytes(usize, String),
+    AbiBytesDynamic(String),
+    AbiString(String),
+    AbiArrayDynamic(EchidnaAbiType, Vec<EchidnaAbiValue>),
+    AbiArray(usize, EchidnaAbiType, Vec<EchidnaAbiValue>),
+    AbiTuple(Vec<EchidnaAbiValue>),
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "tag", content = "contents")]
+pub enum EchidnaAbiType {
+    AbiUIntType(usize),
+    AbiIntType(usize),
+    AbiAddressType,
+    AbiBoolType,
+    AbiBytesType(usize),
+    AbiBytesDynamicType,
+    AbiStringType,
+    AbiArrayDynamicType(Box<EchidnaAbiType>),
+    AbiArrayType(usize, Box<EchidnaAbiType>),
+    AbiTupleType(Vec<EchidnaAbiType>),
+}
+
+impl Default for Quantity {
+    fn default() -> Self {
+        Quantity::Number(0.into())
+    }
+}
+
+impl Quantity {
+    fn to_word(&self) -> Result<[u8; 32], String> {
+        match self {
+            Quantity::Number(number) => parse_word(&number.to_string()),
+            Quantity::Text(text) => parse_word(text),
+        }
+    }
+
+    fn is_zero(&self) -> Result<bool, String> {
+        Ok(self.to_word()? == [0u8; 32])
+    }
+
+    /// Validated literal that can be pasted into Solidity source as is. Quantities are unsigned,
+    /// so negative ones are rejected.
+    fn to_solidity_literal(&self) -> Result<String, String> {
+        let literal = match self {
+            Quantity::Number(number) => number.to_string(),
+            Quantity::Text(text) => text.trim().to_string(),
+        };
+        if literal.starts_with('-') {
+            return Err(format!("Invalid quantity: '{literal}'"));
+        }
+        self.to_word()?;
+        Ok(literal)
+    }
+}
+
+impl EchidnaAbiType {
+    fn to_abi_type(&self) -> AbiType {
+        match self {
+            EchidnaAbiType::AbiUIntType(bits) => AbiType::Uint(*bits),
+            EchidnaAbiType::AbiIntType(bits) => AbiType::Int(*bits),
+            EchidnaAbiType::AbiAddressType => AbiType::Address,
+            EchidnaAbiType::AbiBoolType => AbiType::Bool,
+            EchidnaAbiType::AbiBytesType(size) => AbiType::FixedBytes(*size),
+            EchidnaAbiType::AbiBytesDynamicType => AbiType::Bytes,
+            EchidnaAbiType::AbiStringType => AbiType::String,
+            EchidnaAbiType::AbiArrayDynamicType(inner) => {
+                AbiType::Array(Box::new(inner.to_abi_type()))
+            }
+            EchidnaAbiType::AbiArrayType(size, inner) => {
+                AbiType::FixedArray(Box::new(inner.to_abi_type()), *size)
+            }
+            EchidnaAbiType::AbiTupleType(types) => {
+                AbiType::Tuple(types.iter().map(EchidnaAbiType::to_abi_type).collect())
+            }
+        }
+    }
+}
+
+impl EchidnaAbiValue {
+    fn to_abi_value(&self) -> Result<AbiValue, String> {
+        Ok(match self {
+            EchidnaAbiValue::AbiUInt(bits, value) => AbiValue::Uint(*bits, value.to_word()?),
+            EchidnaAbiValue::AbiInt(bits, value) => AbiValue::Int(*bits, value.to_word()?),
+            EchidnaAbiValue::AbiAddress(address) => AbiValue::Address(parse_address(address)?),
+            EchidnaAbiValue::AbiBool(value) => AbiValue::Bool(*value),
+            EchidnaAbiValue::AbiBytes(size, bytes) => {
+                let mut bytes = decode_bytes(bytes)?;
+                bytes.resize(*size, 0);
+                AbiValue::FixedBytes(bytes)
+            }
+            EchidnaAbiValue::AbiBytesDynamic(bytes) => AbiValue::Bytes(decode_bytes(bytes)?),
+            EchidnaAbiValue::AbiString(value) => {
+                AbiValue::String(String::from_utf8_lossy(&decode_bytes(value)?).into_owned())
+            }
+            EchidnaAbiValue::AbiArrayDynamic(inner, values) => {
+                AbiValue::Array(inner.to_abi_type(), to_abi_values(values)?)
+            }
+            EchidnaAbiValue::AbiArray(_, inner, values) => {
+                AbiValue::FixedArray(inner.to_abi_type(), to_abi_values(values)?)
+            }
+            EchidnaAbiValue::AbiTuple(values) => AbiValue::Tuple(to_abi_values(values)?),
+        })
+    }
+}
+
+fn to_abi_values(values: &[EchidnaAbiValue]) -> Result<Vec<AbiValue>, String> {
+    values.iter().map(EchidnaAbiValue::to_abi_value).collect()
+}
+
+/// Byte strings in reproducers are hex encoded when prefixed with `0x`, raw text otherwise.
+fn decode_bytes(bytes: &str) -> Result<Vec<u8>, String> {
+    if bytes.starts_with("0x") {
+        decode_hex(bytes)
+    } else {
+        Ok(bytes.as_bytes().to_vec())
+    }
+}
+
+pub fn deserialize_reproducer(reproducer_to_deserialize: &str) -> Result<Vec<EchidnaTx>, String> {
+    serde_json::from_str(reproducer_to_deserialize)
+        .map_err(|err| format!("Failed to deserialize Echidna reproducer: {err}"))
+}
+
+/*//////////////////////////////////////////////////////////////
+                    FOUNDRY TEST GENERATION
+////////////////////////////////////////////////////////////// */
+pub struct ReproducerTestConfig {
+    /// Name of the generated Foundry test contract.
+    pub test_name: String,
+    /// Artifact identifier of the Echidna test contract, e.g. `EchidnaTest.sol:EchidnaTest`.
+    pub harness: String,
+    /// Address at which Echidna deployed the test contract.
+    pub harness_address: String,
+    /// Property that the reproducer falsifies. `None` means assertion mode,
+    /// where the test fails if the last call of the sequence reverts.
+    pub property: Option<String>,
+}
+
+pub fn generate_foundry_test(
+    init_events: &[EthenoEvent],
+    reproducer: &[EchidnaTx],
+    config: &ReproducerTestConfig,
+) -> Result<String, String> {
+    let harness_address = to_checksum_address(&config.harness_address)?;
+    let mut test = String::new();
+    test.push_str("// SPDX-License-Identifier: UNLICENSED\n");
+    test.push_str("pragma solidity ^0.8.13;\n\n");
+    test.push_str("import \"forge-std/Test.sol\";\n\n");
+    test.push_str("/// Generated by foundry2echidna from an Echidna reproducer.\n");
+    test.push_str(&format!("contract {} is Test {{\n", config.test_name));
+    test.push_str(&format!(
+        "    address constant ECHIDNA_TEST_CONTRACT = {harness_address};\n\n"
+    ));
+
+    test.push_str("    function setUp() public {\n");
+    test.push_str("        // Replay of the initialization (init.json)\n");
+    let mut nonces = HashMap::new();
+    for event in init_events {
+        test.push_str(&replay_init_event(event, &mut nonces)?);
+    }
+    test.push_str("\n        // Deployment of the Echidna test contract\n");
+    test.push_str(&format!(
+        "        deployCodeTo(\"{}\", ECHIDNA_TEST_CONTRACT);\n",
+        config.harness
+    ));
+    test.push_str("    }\n\n");
+
+    test.push_str("    function test_reproducer() public {\n");
+    for (i, tx) in reproducer.iter().enumerate() {
+        let is_last = i + 1 == reproducer.len();
+        test.push_str(&replay_reproducer_tx(
+            i,
+            tx,
+            is_last && config.property.is_none(),
+        )?);
+    }
+    if let Some(property) = &config.property {
+        test.push_str("\n        // Failing property\n");
+        test.push_str(&format!(
+            "        (bool success, bytes memory result) =\n            ECHIDNA_TEST_CONTRACT.call(abi.encodeWithSignature(\"{property}()\"));\n"
+        ));
+        test.push_str(&format!(
+            "        assertTrue(success && abi.decode(result, (bool)), \"{property}\");\n"
+        ));
+    }
+    test.push_str("    }\n\n");
+
+    test.push_str(HELPERS);
+    test.push_str("}\n");
+    Ok(test)
+}
+
+/// `vm.prank` doesn't advance the nonce of the sender on a call, so the nonce every sender has
+/// after its events (`nonces`) is set before each creation, to deploy at the recorded address.
+fn replay_init_event(
+    event: &EthenoEvent,
+    nonces: &mut HashMap<String, u64>,
+) -> Result<String, String> {
+    let mut next_nonce = |from: &str| -> Result<u64, String> {
+        let nonce = nonces.entry(normalize_address(from)?).or_default();
+        *nonce += 1;
+        Ok(*nonce - 1)
+    };
+    Ok(match event {
+        EthenoEvent::AccountCreated { address, balance } => {
+            let balance = match balance {
+                Some(balance) => Quantity::Text(balance.clone()).to_solidity_literal()?,
+                None => "10000 ether".to_string(),
+            };
+            format!(
+                "        vm.deal({}, {balance});\n",
+                to_checksum_address(address)?
+            )
+        }
+        EthenoEvent::ContractCreated {
+            from,
+            contract_address,
+            data,
+            value,
+            ..
+        } => {
+            let sender = to_checksum_address(from)?;
+            format!(
+            "        vm.setNonce({sender}, {});\n        vm.prank({sender});\n        _create({}, {}, hex\"{}\");\n",
+            next_nonce(from)?,
+            to_checksum_address(contract_address)?,
+            Quantity::Text(value.clone()).to_solidity_literal()?,
+            &encode_hex(&decode_hex(data)?)[2..]
+            )
+        }
+        EthenoEvent::FunctionCall {
+            from,
+            to,
+            data,
+            value,
+            ..
+        } => {
+            next_nonce(from)?;
+            format!(
+                "        vm.prank({});\n        _call({}, {}, hex\"{}\");\n",
+                to_checksum_address(from)?,
+                to_checksum_address(to)?,
+                Quantity::Text(value.clone()).to_solidity_literal()?,
+                &encode_hex(&decode_hex(data)?)[2..]
+            )
+        }
+        EthenoEvent::BlockMined { number, timestamp } => {
+            format!("        vm.roll({number});\n        vm.warp({timestamp});\n")
+        }
+    })
+}
+
+fn replay_reproducer_tx(
+    index: usize,
+    tx: &EchidnaTx,
+    must_succeed: bool,
+) -> Result<String, String> {
+    let mut replay = String::new();
+    let (description, calldata) = match &tx.call {
+        EchidnaCall::SolCall(name, arguments) => {
+            let arguments = to_abi_values(arguments)?;
+            let (_, calldata) = encode_function_call(name, &arguments);
+            let rendered: Vec<String> = arguments.iter().map(AbiValue::display).collect();
+            (format!("{name}({})", rendered.join(", ")), Some(calldata))
+        }
+        EchidnaCall::SolCalldata(calldata) => {
+            ("raw calldata".to_string(), Some(decode_hex(calldata)?))
+        }
+        EchidnaCall::NoCall => ("delay only".to_string(), None),
+    };
+    replay.push_str(&format!("        // Call {}: {description}\n", index + 1));
+
+    let (time_delay, block_delay) = &tx.delay;
+    if !time_delay.is_zero()? {
+        replay.push_str(&format!(
+            "        vm.warp(block.timestamp + {});\n",
+            time_delay.to_solidity_literal()?
+        ));
+    }
+    if !block_delay.is_zero()? {
+        replay.push_str(&format!(
+            "        vm.roll(block.number + {});\n",
+            block_delay.to_solidity_literal()?
+        ));
+    }
+
+    if let Some(calldata) = calldata {
+        let src = to_checksum_address(&tx.src)?;
+        let dst = to_checksum_address(&tx.dst)?;
+        let value = tx.value.to_solidity_literal()?;
+        if !tx.value.is_zero()? {
+            replay.push_str(&format!(
+                "        vm.deal({src}, {src}.balance + {value});\n"
+            ));
+        }
+        replay.push_str(&format!("        vm.prank({src});\n"));
+        let call = format!(
+            "_reproduce({dst}, {value}, hex\"{}\")",
+            &encode_hex(&calldata)[2..]
+        );
+        if must_succeed {
+            replay.push_str(&format!(
+                "        assertTrue({call}, \"assertion failure\");\n"
+            ));
+        } else {
+            replay.push_str(&format!("        {call};\n"));
+        }
+    }
+    Ok(replay)
+}
+
+const HELPERS: &str = r#"    function _create(address expected, uint256 value, bytes memory initCode) internal {
+        address deployed;
+        assembly {
+            deployed := create(value, add(initCode, 0x20), mload(initCode))
+        }
+        assertEq(deployed, expected, "init: contract deployed at an unexpected address");
+    }
+
+    function _call(address target, uint256 value, bytes memory data) internal {
+        (bool success,) = target.call{value: value}(data);
+        require(success, "init: call reverted");
+    }
+
+    function _reproduce(address target, uint256 value, bytes memory data) internal returns (bool success) {
+        (success,) = target.call{value: value}(data);
+    }
+"#;
+
+#[cfg(test)]
+mod reproducer_tests {
+    use super::*;
+    use crate::echidna::DEFAULT_ECHIDNA_CONTRACT_ADDRESS;
+
+    const REPRODUCER: &str = r#"[
+    {
+        "call": {
+            "tag": "SolCall",
+            "contents": ["setNumber", [{"tag": "AbiUInt", "contents": [256, "42"]}]]
+        },
+        "src": "0x0000000000000000000000000000000000010000",
+        "dst": "0x00a329c0648769a73afac7f9381e08fb43dbea72",
+        "gas": 12500000,
+        "gasprice": "0x0",
+        "value": "0x0",
+        "delay": ["0x3c", "0x1"]
+    },
+    {
+        "call": {"tag": "NoCall"},
+        "src": "0x0000000000000000000000000000000000010000",
+        "dst": "0x0000000000000000000000000000000000000000",
+        "value": "0x0",
+        "delay": ["0x0", "0x5"]
+    }
+]"#;
+
+    #[test]
+    fn it_should_deserialize_reproducer() {
+        let reproducer = deserialize_reproducer(REPRODUCER).unwrap();
+        assert_eq!(2, reproducer.len());
+        assert_eq!(
+            EchidnaCall::SolCall(
+                "setNumber".to_string(),
+                vec![EchidnaAbiValue::AbiUInt(
+                    256,
+                    Quantity::Text("42".to_string())
+                )]
+            ),
+            reproducer[0].call
+        );
+        assert_eq!(EchidnaCall::NoCall, reproducer[1].call);
+
+        let value: Quantity =
+            serde_json::from_str("340282366920938463463374607431768211456").unwrap();
+        assert_eq!(
+            parse_word("340282366920938463463374607431768211456").unwrap(),
+            value.to_word().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_generate_foundry_test_with_init_calls_and_property() {
+        let init_events = vec![
+            EthenoEvent::AccountCreated {
+                address: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+                balance: None,
+            },
+            EthenoEvent::ContractCreated {
+                from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+                contract_address: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+                gas_used: "0x6e675".to_string(),
+                gas_price: "0xe0fed783".to_string(),
+                data: "0x6080".to_string(),
+                value: "0x0".to_string(),
+            },
+            EthenoEvent::FunctionCall {
+                from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+                to: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+                gas_used: "0x6e675".to_string(),
+                gas_price: "0xe0fed783".to_string(),
+                data: "0x".to_string(),
+                value: "0x0".to_string(),
+            },
+            EthenoEvent::ContractCreated {
+                from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+                contract_address: "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad".to_string(),
+                gas_used: "0x6e675".to_string(),
+                gas_price: "0xe0fed783".to_string(),
+                data: "0x6080".to_string(),
+                value: "0x0".to_string(),
+            },
+        ];
+        let config = ReproducerTestConfig {
+            test_name: "CounterReproducerTest".to_string(),
+            harness: "EchidnaCounter.sol:EchidnaCounter".to_string(),
+            harness_address: DEFAULT_ECHIDNA_CONTRACT_ADDRESS.to_string(),
+            property: Some("echidna_number_is_small".to_string()),
+        };
+        let reproducer = deserialize_reproducer(REPRODUCER).unwrap();
+
+        let test = generate_foundry_test(&init_events, &reproducer, &config).unwrap();
+
+        assert!(test.contains("contract CounterReproducerTest is Test {"));
+        assert!(test.contains("vm.deal(0x90F79bf6EB2c4f870365E785982E1f101E93b906, 10000 ether);"));
+        assert!(
+            test.contains("_create(0x057ef64E23666F000b34aE31332854aCBd1c8544, 0x0, hex\"6080\");")
+        );
+        // The call in between doesn't advance the nonce under `vm.prank`.
+        assert!(test.contains("vm.setNonce(0x90F79bf6EB2c4f870365E785982E1f101E93b906, 0);"));
+        assert!(test.contains("vm.setNonce(0x90F79bf6EB2c4f870365E785982E1f101E93b906, 2);"));
+        assert!(test.contains(
+            "deployCodeTo(\"EchidnaCounter.sol:EchidnaCounter\", ECHIDNA_TEST_CONTRACT);"
+        ));
+        assert!(test.contains("// Call 1: setNumber(42)"));
+        assert!(test.contains("vm.warp(block.timestamp + 0x3c);"));
+        assert!(test.contains("vm.roll(block.number + 0x1);"));
+        assert!(test.contains("vm.prank(0x0000000000000000000000000000000000010000);"));
+        assert!(test.contains(
+            "_reproduce(0x00a329c0648769A73afAc7F9381E08FB43dBEA72, 0x0, hex\"3fb5c1cb000000000000000000000000000000000000000000000000000000000000002a\");"
+        ));
+        assert!(test.contains("vm.roll(block.number + 0x5);"));
+        assert!(test.contains("abi.encodeWithSignature(\"echidna_number_is_small()\")"));
+    }
+
+    #[test]
+    fn it_should_expect_last_call_to_succeed_in_assertion_mode() {
+        let config = ReproducerTestConfig {
+            test_name: "AssertionReproducerTest".to_string(),
+            harness: "EchidnaCounter".to_string(),
+            harness_address: DEFAULT_ECHIDNA_CONTRACT_ADDRESS.to_string(),
+            property: None,
+        };
+        let reproducer = deserialize_reproducer(REPRODUCER).unwrap();
+        let reproducer = &reproducer[..1];
+
+        let test = generate_foundry_test(&[], reproducer, &config).unwrap();
+
+        assert!(test.contains("assertTrue(_reproduce("));
+        assert!(!test.contains("abi.encodeWithSignature"));
+        assert_eq!(
+            Err("Invalid quantity: '-1'".to_string()),
+            Quantity::Text(" -1".to_string()).to_solidity_literal()
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use serde_json::{json, Value};
+use std::cell::Cell;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/*//////////////////////////////////////////////////////////////
+                        JSON-RPC CLIENT
+////////////////////////////////////////////////////////////// */
+/// Minimal JSON-RPC client over plain HTTP, meant for local nodes like anvil.
+pub struct RpcClient {
+    host: String,
+    port: u16,
+    path: String,
+    next_id: Cell<u64>,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Result<Self, String> {
+        let without_scheme = url.strip_prefix("http://").ok_or(format!(
+            "Only plain http:// JSON-RPC endpoints are supported, got: {url}"
+        ))?;
+        let (authority, path) = match without_scheme.find('/') {
+            Some(index) => without_scheme.split_at(index),
+            None => (without_scheme, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse::<u16>()
+                    .map_err(|err| format!("Invalid port in {url}: {err}"))?,
+            ),
+            None => (authority, 80),
+        };
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+            next_id: Cell::new(1),
+        })
+    }
+
+    /// Sends a single JSON-RPC request and returns its `result`.
+    pub fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        let body = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        let response = self.post(&body.to_string())?;
+        let mut response: Value = serde_json::from_str(&response)
+            .map_err(|err| format!("Invalid JSON-RPC response to {method}: {err}"))?;
+        if let Some(error) = response.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or(error.to_string());
+            return Err(format!("{method} failed: {message}"));
+        }
+        Ok(response
+            .get_mut("result")
+            .map(Value::take)
+            .unwrap_or(Value::Null))
+    }
+
+    /// Posts a raw JSON-RPC body (a single request or a batch) and returns the raw response body.
+    pub fn post(&self, body: &str) -> Result<String, String> {
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port))
+            .map_err(|err| format!("Failed to connect to {}:{}: {err}", self.host, self.port))?;
+        stream
+            .set_read_timeout(Some(Duration::from_secs(30)))
+            .map_err(|err| err.to_string())?;
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.path,
+            self.host,
+            self.port,
+            body.len()
+        );
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|err| format!("Failed to send JSON-RPC request: {err}"))?;
+        let mut response = vec![];
+        stream
+            .read_to_end(&mut response)
+            .map_err(|err| format!("Failed to read JSON-RPC response: {err}"))?;
+        parse_http_response(&response)
+    }
+}
+
+fn parse_http_response(response: &[u8]) -> Result<String, String> {
+    let separator = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or("Malformed HTTP response")?;
+    let head = String::from_utf8_lossy(&response[..separator]);
+    let body = &response[separator + 4..];
+    let mut lines = head.lines();
+    let status = lines.next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("Unexpected HTTP status: {status}"));
+    }
+    let chunked = lines.any(|line| {
+        let line = line.to_ascii_lowercase();
+        line.starts_with("transfer-encoding:") && line.contains("chunked")
+    });
+    let body = if chunked {
+        decode_chunked_body(body)?
+    } else {
+        body.to_vec()
+    };
+    String::from_utf8(body).map_err(|err| format!("JSON-RPC response is not UTF-8: {err}"))
+}
+
+/// Chunk sizes count bytes, so the body is de-chunked before it is decoded as UTF-8.
+fn decode_chunked_body(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoded = vec![];
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .ok_or("Malformed chunked body")?;
+        let size = String::from_utf8_lossy(&body[..line_end]);
+        let size = usize::from_str_radix(size.trim(), 16)
+            .map_err(|err| format!("Malformed chunk size: {err}"))?;
+        let rest = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+        decoded.extend_from_slice(rest.get(..size).ok_or("Truncated chunked body")?);
+        body = rest.get(size + 2..).ok_or("Truncated chunked body")?;
+    }
+}
+
+/*//////////////////////////////////////////////////////////////
+                        JSON-RPC SERVER SIDE
+////////////////////////////////////////////////////////////// */
+/// Reads one HTTP request from the stream and returns its method (e.g. `POST`) and body.
+pub fn read_http_request(stream: &TcpStream) -> Result<(String, String), String> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|err| format!("Failed to read HTTP request: {err}"))?;
+    let method = request_line
+        .split_whitespace()
+        .next()
+        .ok_or("Empty HTTP request")?
+        .to_string();
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|err| format!("Failed to read HTTP header: {err}"))?;
+        if line == "\r\n" || line.is_empty() {
+            break;
+        }
+        if let Some(length) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+            content_length = length
+                .trim()
+                .parse()
+                .map_err(|err| format!("Invalid Content-Length: {err}"))?;
+        }
+    }
+    let mut body = vec![0u8; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|err| format!("Failed to read HTTP body: {err}"))?;
+    Ok((method, String::from_utf8_lossy(&body).into_owned()))
+}
+
+/// Writes a JSON response. CORS is allowed so that browser frontends can talk to the server.
+pub fn write_http_response(mut stream: &TcpStream, body: &str) -> Result<(), String> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Headers: *\r\nAccess-Control-Allow-Methods: POST, OPTIONS\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .map_err(|err| format!("Failed to write HTTP response: {err}"))
+}
+
+#[cfg(test)]
+pub mod mock_node {
+    use super::{read_http_request, write_http_response};
+    use serde_json::{json, Value};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Spawns a local JSON-RPC server answering every request with `handler(method, params)`.
+    /// Returns the URL of the server.
+    pub fn spawn_mock_node<F>(handler: F) -> String
+    where
+        F: Fn(&str, &Value) -> Result<Value, String> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = stream.unwrap();
+                let (_, body) = read_http_request(&stream).unwrap();
+                let request: Value = serde_json::from_str(&body).unwrap();
+                let respond = |request: &Value| {
+                    let method = request["method"].as_str().unwrap_or_default();
+                    match handler(method, &request["params"]) {
+                        Ok(result) => {
+                            json!({"jsonrpc": "2.0", "id": request["id"], "result": result})
+                        }
+                        Err(message) => {
+                            json!({"jsonrpc": "2.0", "id": request["id"], "error": {"code": -32000, "message": message}})
+                        }
+                    }
+                };
+                let response = match &request {
+                    Value::Array(batch) => Value::Array(batch.iter().map(respond).collect()),
+                    request => respond(request),
+                };
+                let _ = write_http_response(&stream, &response.to_string());
+            }
+        });
+        url
+    }
+}
+
+#[cfg(test)]
+mod rpc_tests {
+    use super::mock_node::spawn_mock_node;
+    use super::*;
+
+    #[test]
+    fn it_should_return_result_of_json_rpc_request() {
+        let url = spawn_mock_node(|method, _| match method {
+            "eth_chainId" => Ok(json!("0x7a69")),
+            _ => Err("method not found".to_string()),
+        });
+        let client = RpcClient::new(&url).unwrap();
+
+        assert_eq!(
+            json!("0x7a69"),
+            client.request("eth_chainId", json!([])).unwrap()
+        );
+        assert_eq!(
+            Err("eth_foo failed: method not found".to_string()),
+            client.request("eth_foo", json!([]))
+        );
+    }
+
+    #[test]
+    fn it_should_decode_chunked_http_response() {
+        let response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n{\"a\"\r\n3\r\n:1}\r\n0\r\n\r\n";
+        assert_eq!("{\"a\":1}", parse_http_response(response).unwrap());
+
+        // A chunk boundary in the middle of `é`.
+        let response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n7\r\n{\"a\":\"\xc3\r\n3\r\n\xa9\"}\r\n0\r\n\r\n";
+        assert_eq!("{\"a\":\"é\"}", parse_http_response(response).unwrap());
+    }
+
+    #[test]
+    fn it_should_reject_non_http_urls() {
+        assert!(RpcClient::new("https://mainnet.infura.io").is_err());
+        assert!(RpcClient::new("http://127.0.0.1:8545").is_ok());
+    }
+}
@@ -0,0 +1,215 @@
+use crate::data_model::{Broadcast, RpcReceipt, RpcTransaction};
+use crate::deserialization::broadcast_transaction_from_rpc;
+use crate::primitives::parse_hex_u64;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/*//////////////////////////////////////////////////////////////
+                    RAW JSON-RPC DUMP IMPORT
+////////////////////////////////////////////////////////////// */
+/// Splits a dump into JSON objects. The dump is either a single JSON document (an object or an
+/// array, possibly nested like batch responses) or NDJSON, one document per line.
+/// JSON-RPC response envelopes are unwrapped to their `result`.
+pub fn parse_rpc_dump(content: &str) -> Result<Vec<Value>, String> {
+    let documents = match serde_json::from_str::<Value>(content) {
+        Ok(document) => vec![document],
+        Err(_) => content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .map_err(|err| format!("Failed to parse line {} of the dump: {err}", i + 1))
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    let mut objects = vec![];
+    for document in documents {
+        flatten_document(document, &mut objects);
+    }
+    Ok(objects)
+}
+
+fn flatten_document(document: Value, objects: &mut Vec<Value>) {
+    match document {
+        Value::Array(documents) => {
+            for document in documents {
+                flatten_document(document, objects);
+            }
+        }
+        Value::Object(mut object) if object.contains_key("jsonrpc") => {
+            if let Some(result) = object.remove("result") {
+                flatten_document(result, objects);
+            }
+        }
+        Value::Null => {}
+        document => objects.push(document),
+    }
+}
+
+/// Pairs `eth_getTransactionByHash` objects with their `eth_getTransactionReceipt` objects and
+/// builds a broadcast of the successful ones.
+///
+/// Transactions are ordered by block number and transaction index when every transaction has
+/// them, otherwise they keep the order of the dump.
+pub fn broadcast_from_rpc_dump(objects: Vec<Value>) -> Result<Broadcast, String> {
+    let mut transactions: Vec<RpcTransaction> = vec![];
+    let mut receipts = HashMap::new();
+    for object in objects {
+        if object.get("transactionHash").is_some() && object.get("gasUsed").is_some() {
+            let receipt: RpcReceipt = serde_json::from_value(object)
+                .map_err(|err| format!("Failed to deserialize receipt: {err}"))?;
+            receipts.insert(receipt.transaction_hash.to_lowercase(), receipt);
+        } else if object.get("hash").is_some() && object.get("input").is_some() {
+            let transaction: RpcTransaction = serde_json::from_value(object)
+                .map_err(|err| format!("Failed to deserialize transaction: {err}"))?;
+            if !transactions
+                .iter()
+                .any(|seen| seen.hash.eq_ignore_ascii_case(&transaction.hash))
+            {
+                transactions.push(transaction);
+            }
+        } else {
+            return Err(format!(
+                "Neither a transaction nor a receipt object: {object}"
+            ));
+        }
+    }
+
+    let positions = transactions
+        .iter()
+        .map(
+            |transaction| match (&transaction.block_number, &transaction.transaction_index) {
+                (Some(block_number), Some(transaction_index)) => Ok(Some((
+                    parse_hex_u64(block_number)?,
+                    parse_hex_u64(transaction_index)?,
+                ))),
+                _ => Ok(None),
+            },
+        )
+        .collect::<Result<Option<Vec<_>>, String>>()?;
+    if let Some(positions) = positions {
+        let mut positioned: Vec<_> = positions.into_iter().zip(transactions).collect();
+        positioned.sort_by_key(|(position, _)| *position);
+        transactions = positioned
+            .into_iter()
+            .map(|(_, transaction)| transaction)
+            .collect();
+    }
+
+    let mut broadcast = Broadcast {
+        transactions: vec![],
+        receipts: vec![],
+        libraries: vec![],
+        chain: None,
+        commit: None,
+        timestamp: None,
+    };
+    for transaction in transactions {
+        let receipt = receipts
+            .remove(&transaction.hash.to_lowercase())
+            .ok_or(format!("Transaction {} has no receipt", transaction.hash))?;
+        if receipt.status.as_deref() == Some("0x0") {
+            continue;
+        }
+        let (transaction, receipt) = broadcast_transaction_from_rpc(transaction, receipt)?;
+        broadcast.transactions.push(transaction);
+        broadcast.receipts.push(receipt);
+    }
+    if let Some(hash) = receipts.keys().next() {
+        return Err(format!("Receipt of {hash} has no matching transaction"));
+    }
+    Ok(broadcast)
+}
+
+#[cfg(test)]
+mod rpc_dump_tests {
+    use super::*;
+    use crate::data_model::{Receipt, Transaction, TransactionDetails};
+
+    const CALL: &str = r#"{"hash":"0xbb","from":"0x90f79bf6eb2c4f870365e785982e1f101e93b906","to":"0x057ef64e23666f000b34ae31332854acbd1c8544","value":"0x0","input":"0x202023","gasPrice":"0x1","blockNumber":"0x2","transactionIndex":"0x0"}"#;
+    const CREATE: &str = r#"{"hash":"0xaa","from":"0x90f79bf6eb2c4f870365e785982e1f101e93b906","to":null,"value":"0x0","input":"0x6080","gasPrice":"0x1","blockNumber":"0x1","transactionIndex":"0x0"}"#;
+    const CALL_RECEIPT: &str = r#"{"transactionHash":"0xbb","contractAddress":null,"gasUsed":"0xb3bd","effectiveGasPrice":"0xe0fed783","status":"0x1"}"#;
+    const CREATE_RECEIPT: &str = r#"{"transactionHash":"0xaa","contractAddress":"0x057ef64E23666F000b34aE31332854aCBd1c8544","gasUsed":"0x6e675","effectiveGasPrice":"0xe0fed783","status":"0x1"}"#;
+
+    fn expected_broadcast() -> Broadcast {
+        Broadcast {
+            transactions: vec![
+                Transaction {
+                    transaction_type: "CREATE".to_string(),
+                    contract_address: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+                    contract_name: None,
+                    additional_contracts: vec![],
+                    function: None,
+                    arguments: None,
+                    transaction: TransactionDetails {
+                        from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+                        to: None,
+                        value: "0x0".to_string(),
+                        data: "0x6080".to_string(),
+                    },
+                },
+                Transaction {
+                    transaction_type: "CALL".to_string(),
+                    contract_address: "0x057ef64e23666f000b34ae31332854acbd1c8544".to_string(),
+                    contract_name: None,
+                    additional_contracts: vec![],
+                    function: None,
+                    arguments: None,
+                    transaction: TransactionDetails {
+                        from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+                        to: Some("0x057ef64e23666f000b34ae31332854acbd1c8544".to_string()),
+                        value: "0x0".to_string(),
+                        data: "0x202023".to_string(),
+                    },
+                },
+            ],
+            receipts: vec![
+                Receipt {
+                    gas_used: "0x6e675".to_string(),
+                    effective_gas_price: "0xe0fed783".to_string(),
+                    logs: vec![],
+                },
+                Receipt {
+                    gas_used: "0xb3bd".to_string(),
+                    effective_gas_price: "0xe0fed783".to_string(),
+                    logs: vec![],
+                },
+            ],
+            libraries: vec![],
+            chain: None,
+            commit: None,
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn it_should_import_ndjson_dump_ordered_by_block() {
+        let dump = format!("{CALL}\n{CALL_RECEIPT}\n\n{CREATE}\n{CREATE_RECEIPT}\n");
+
+        let broadcast = broadcast_from_rpc_dump(parse_rpc_dump(&dump).unwrap()).unwrap();
+
+        assert_eq!(expected_broadcast(), broadcast);
+    }
+
+    #[test]
+    fn it_should_import_array_dump_of_json_rpc_responses() {
+        let dump = format!(
+            r#"[{{"jsonrpc":"2.0","id":1,"result":{CREATE}}},{{"jsonrpc":"2.0","id":2,"result":{CREATE_RECEIPT}}},[{CALL},{CALL_RECEIPT}]]"#
+        );
+
+        let broadcast = broadcast_from_rpc_dump(parse_rpc_dump(&dump).unwrap()).unwrap();
+
+        assert_eq!(expected_broadcast(), broadcast);
+    }
+
+    #[test]
+    fn it_should_reject_transaction_without_receipt() {
+        let objects = parse_rpc_dump(&format!("[{CREATE},{CALL},{CALL_RECEIPT}]")).unwrap();
+
+        assert_eq!(
+            Err("Transaction 0xaa has no receipt".to_string()),
+            broadcast_from_rpc_dump(objects)
+        );
+    }
+}
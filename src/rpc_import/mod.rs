@@ -0,0 +1,151 @@
+use crate::data_model::{Broadcast, RpcBlock, RpcReceipt};
+use crate::deserialization::broadcast_transaction_from_rpc;
+use crate::primitives::parse_hex_u64;
+use crate::rpc::RpcClient;
+use crate::serialization::serialize_broadcast;
+use serde_json::{json, Value};
+
+/*//////////////////////////////////////////////////////////////
+                    IMPORT FROM A JSON-RPC NODE
+////////////////////////////////////////////////////////////// */
+/// Walks the node's blocks in `[from_block, to_block]` and serializes their successful
+/// transactions like a broadcast. Each block with transactions is preceded by a `BlockMined`
+/// event, so that Echidna replays the transactions with their original block number and time.
+///
+/// If `to_block` is `None`, the latest block is used.
+pub fn import_events_from_node(
+    client: &RpcClient,
+    from_block: u64,
+    to_block: Option<u64>,
+) -> Result<Vec<Value>, String> {
+    let to_block = match to_block {
+        Some(to_block) => to_block,
+        None => parse_hex_u64(
+            client
+                .request("eth_blockNumber", json!([]))?
+                .as_str()
+                .ok_or("eth_blockNumber returned no block number")?,
+        )?,
+    };
+
+    let mut events = vec![];
+    for number in from_block..=to_block {
+        let block = client.request(
+            "eth_getBlockByNumber",
+            json!([format!("{number:#x}"), true]),
+        )?;
+        if block.is_null() {
+            return Err(format!("Block {number} does not exist on the node"));
+        }
+        let block: RpcBlock = serde_json::from_value(block)
+            .map_err(|err| format!("Failed to deserialize block {number}: {err}"))?;
+
+        let mut broadcast = Broadcast {
+            transactions: vec![],
+            receipts: vec![],
+            libraries: vec![],
+            chain: None,
+            commit: None,
+            timestamp: None,
+        };
+        for transaction in block.transactions {
+            let receipt = client.request("eth_getTransactionReceipt", json!([transaction.hash]))?;
+            let receipt: RpcReceipt = serde_json::from_value(receipt).map_err(|err| {
+                format!(
+                    "Failed to deserialize receipt of {}: {err}",
+                    transaction.hash
+                )
+            })?;
+            if receipt.status.as_deref() == Some("0x0") {
+                continue;
+            }
+            let (transaction, receipt) = broadcast_transaction_from_rpc(transaction, receipt)?;
+            broadcast.transactions.push(transaction);
+            broadcast.receipts.push(receipt);
+        }
+        if broadcast.transactions.is_empty() {
+            continue;
+        }
+        events.push(json!({
+            "event": "BlockMined",
+            "number": parse_hex_u64(&block.number)?,
+            "timestamp": parse_hex_u64(&block.timestamp)?,
+        }));
+        events.extend(serialize_broadcast(broadcast)?);
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod rpc_import_tests {
+    use super::*;
+    use crate::rpc::mock_node::spawn_mock_node;
+
+    #[test]
+    fn it_should_import_blocks_with_transactions() {
+        let url = spawn_mock_node(|method, params| match method {
+            "eth_blockNumber" => Ok(json!("0x2")),
+            "eth_getBlockByNumber" => Ok(match params[0].as_str().unwrap() {
+                "0x1" => json!({"number": "0x1", "timestamp": "0x64", "transactions": []}),
+                "0x2" => json!({
+                    "number": "0x2",
+                    "timestamp": "0x65",
+                    "transactions": [
+                        {
+                            "hash": "0xaa",
+                            "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+                            "to": null,
+                            "value": "0x0",
+                            "input": "0x6080",
+                            "gasPrice": "0x1"
+                        },
+                        {
+                            "hash": "0xbb",
+                            "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+                            "to": "0x057ef64e23666f000b34ae31332854acbd1c8544",
+                            "value": "0x0",
+                            "input": "0x202023",
+                            "gasPrice": "0x1"
+                        },
+                        {
+                            "hash": "0xcc",
+                            "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+                            "to": "0x057ef64e23666f000b34ae31332854acbd1c8544",
+                            "value": "0x0",
+                            "input": "0xdead",
+                            "gasPrice": "0x1"
+                        }
+                    ]
+                }),
+                _ => Value::Null,
+            }),
+            "eth_getTransactionReceipt" => Ok(match params[0].as_str().unwrap() {
+                "0xaa" => json!({
+                    "transactionHash": "0xaa",
+                    "contractAddress": "0x057ef64E23666F000b34aE31332854aCBd1c8544",
+                    "gasUsed": "0x6e675",
+                    "effectiveGasPrice": "0xe0fed783",
+                    "status": "0x1"
+                }),
+                hash => json!({
+                    "transactionHash": hash,
+                    "contractAddress": null,
+                    "gasUsed": "0xb3bd",
+                    "effectiveGasPrice": "0xe0fed783",
+                    "status": if hash == "0xcc" { "0x0" } else { "0x1" }
+                }),
+            }),
+            _ => Err(format!("unexpected method {method}")),
+        });
+        let client = RpcClient::new(&url).unwrap();
+
+        let events = import_events_from_node(&client, 1, None).unwrap();
+
+        let expected_events = vec![
+            json!({"event":"BlockMined","number":2,"timestamp":101}),
+            json!({"event":"ContractCreated","from":"0x90f79bf6eb2c4f870365e785982e1f101e93b906","contract_address":"0x057ef64E23666F000b34aE31332854aCBd1c8544","gas_used":"0x6e675","gas_price":"0xe0fed783","data":"0x6080","value":"0x0"}),
+            json!({"event":"FunctionCall","from":"0x90f79bf6eb2c4f870365e785982e1f101e93b906","to":"0x057ef64e23666f000b34ae31332854acbd1c8544","gas_used":"0xb3bd","gas_price":"0xe0fed783","data":"0x202023","value":"0x0"}),
+        ];
+        assert_eq!(expected_events, events);
+    }
+}
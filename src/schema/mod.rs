@@ -0,0 +1,352 @@
+use crate::data_model::{
+    AdditionalContract, Broadcast, EthenoEvent, Log, Receipt, Transaction, TransactionDetails,
+};
+use crate::validation::{FieldKind, Fields, EVENT_SCHEMA};
+use serde_json::{json, Map, Value};
+
+/*//////////////////////////////////////////////////////////////
+                        JSON SCHEMA
+////////////////////////////////////////////////////////////// */
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A type of the data model, with the JSON Schema of the form serde reads or writes.
+pub trait JsonSchema {
+    /// Name of the type's definition in `$defs`.
+    const NAME: &'static str;
+    /// Schema of the type, nested data model types being `$ref`s to their definitions.
+    fn schema() -> Value;
+}
+
+/// The subset of a Foundry broadcast the crate accepts. Foundry writes more fields, which are
+/// allowed and ignored. Like the deserializer, it only requires strings where Foundry writes
+/// addresses, quantities and hex data; they are checked when they are used.
+pub fn broadcast_schema() -> Value {
+    let mut schema = Broadcast::schema();
+    schema["$schema"] = json!(JSON_SCHEMA_DIALECT);
+    schema["title"] = json!("Foundry broadcast accepted by foundry2echidna");
+    schema["$defs"] = definitions(vec![
+        (Transaction::NAME, Transaction::schema()),
+        (TransactionDetails::NAME, TransactionDetails::schema()),
+        (AdditionalContract::NAME, AdditionalContract::schema()),
+        (Receipt::NAME, Receipt::schema()),
+        (Log::NAME, Log::schema()),
+    ]);
+    schema
+}
+
+/// The Etheno events the crate emits, an `init.json` for Echidna's `initialize`.
+pub fn etheno_events_schema() -> Value {
+    json!({
+        "$schema": JSON_SCHEMA_DIALECT,
+        "title": "Etheno events emitted by foundry2echidna",
+        "type": "array",
+        "items": reference::<EthenoEvent>(),
+        "$defs": definitions(vec![(EthenoEvent::NAME, EthenoEvent::schema())]),
+    })
+}
+
+fn definitions(schemas: Vec<(&str, Value)>) -> Value {
+    Value::Object(
+        schemas
+            .into_iter()
+            .map(|(name, schema)| (name.to_string(), schema))
+            .collect(),
+    )
+}
+
+fn reference<T: JsonSchema>() -> Value {
+    json!({ "$ref": format!("#/$defs/{}", T::NAME) })
+}
+
+/// An object with the `required` properties and the `optional` ones.
+fn object(required: Vec<(&str, Value)>, optional: Vec<(&str, Value)>) -> Value {
+    let mut properties = Map::new();
+    let mut names = vec![];
+    for (name, schema) in required {
+        names.push(json!(name));
+        properties.insert(name.to_string(), schema);
+    }
+    for (name, schema) in optional {
+        properties.insert(name.to_string(), schema);
+    }
+    json!({ "type": "object", "properties": properties, "required": names })
+}
+
+fn string(description: &str) -> Value {
+    json!({ "type": "string", "description": description })
+}
+
+/// The pattern of the fields of a kind, as the Etheno event validation checks them.
+fn field_pattern(kind: FieldKind) -> Option<&'static str> {
+    match kind {
+        FieldKind::Address => Some("^(0x)?[0-9a-fA-F]{40}$"),
+        FieldKind::Quantity => Some("^(0x)?[0-9a-fA-F]{1,64}$"),
+        FieldKind::Data => Some("^(0x)?([0-9a-fA-F]{2})*$"),
+        FieldKind::Integer => None,
+    }
+}
+
+/// A field of a `kind`, described by `description`.
+fn field(kind: FieldKind, description: &str) -> Value {
+    match field_pattern(kind) {
+        Some(pattern) => {
+            json!({ "type": "string", "pattern": pattern, "description": description })
+        }
+        None => json!({ "type": "integer", "minimum": 0, "description": description }),
+    }
+}
+
+fn nullable(schema_type: &str) -> Value {
+    json!({ "type": [schema_type, "null"] })
+}
+
+fn array_of(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+/// An Etheno event: an object tagged by `event`, without other fields.
+fn event(name: &str, required: Fields, optional: Fields) -> Value {
+    let fields = |fields: Fields| {
+        fields
+            .iter()
+            .map(|(field_name, kind)| (*field_name, field(*kind, field_description(*kind))))
+            .collect::<Vec<_>>()
+    };
+    let mut required = fields(required);
+    required.insert(0, ("event", json!({ "const": name })));
+    let mut schema = object(required, fields(optional));
+    schema["additionalProperties"] = json!(false);
+    schema
+}
+
+fn field_description(kind: FieldKind) -> &'static str {
+    match kind {
+        FieldKind::Address => "Address.",
+        FieldKind::Quantity => "Hex quantity.",
+        FieldKind::Data => "Hex bytes.",
+        FieldKind::Integer => "Unsigned integer.",
+    }
+}
+
+impl JsonSchema for Broadcast {
+    const NAME: &'static str = "Broadcast";
+    fn schema() -> Value {
+        object(
+            vec![
+                ("transactions", array_of(reference::<Transaction>())),
+                ("receipts", array_of(reference::<Receipt>())),
+            ],
+            vec![
+                (
+                    "libraries",
+                    array_of(string("Linked library, as `path:Name:0xaddress`.")),
+                ),
+                (
+                    "chain",
+                    json!({ "type": ["integer", "null"], "minimum": 0 }),
+                ),
+                ("commit", nullable("string")),
+                (
+                    "timestamp",
+                    json!({ "type": ["integer", "null"], "minimum": 0 }),
+                ),
+            ],
+        )
+    }
+}
+
+impl JsonSchema for Transaction {
+    const NAME: &'static str = "Transaction";
+    fn schema() -> Value {
+        object(
+            vec![
+                ("transactionType", string("`CREATE`, `CREATE2` or `CALL`.")),
+                ("contractAddress", string("Created or called address.")),
+                ("transaction", reference::<TransactionDetails>()),
+            ],
+            vec![
+                ("contractName", nullable("string")),
+                ("function", nullable("string")),
+                (
+                    "arguments",
+                    json!({ "type": ["array", "null"], "items": { "type": "string" } }),
+                ),
+                (
+                    "additionalContracts",
+                    array_of(reference::<AdditionalContract>()),
+                ),
+            ],
+        )
+    }
+}
+
+impl JsonSchema for TransactionDetails {
+    const NAME: &'static str = "TransactionDetails";
+    fn schema() -> Value {
+        object(
+            vec![
+                ("from", string("Sender address.")),
+                ("value", string("Hex quantity of wei.")),
+                ("data", string("Hex calldata or init code.")),
+            ],
+            vec![(
+                "to",
+                json!({ "type": ["string", "null"], "description": "Called address." }),
+            )],
+        )
+    }
+}
+
+impl JsonSchema for AdditionalContract {
+    const NAME: &'static str = "AdditionalContract";
+    fn schema() -> Value {
+        object(
+            vec![
+                ("transactionType", string("`CREATE` or `CREATE2`.")),
+                ("address", string("Created address.")),
+                ("initCode", string("Hex init code.")),
+            ],
+            vec![("contractName", nullable("string"))],
+        )
+    }
+}
+
+impl JsonSchema for Receipt {
+    const NAME: &'static str = "Receipt";
+    fn schema() -> Value {
+        object(
+            vec![
+                ("gasUsed", string("Hex quantity.")),
+                ("effectiveGasPrice", string("Hex quantity.")),
+            ],
+            vec![("logs", array_of(reference::<Log>()))],
+        )
+    }
+}
+
+impl JsonSchema for Log {
+    const NAME: &'static str = "Log";
+    fn schema() -> Value {
+        object(
+            vec![
+                ("address", string("Emitting contract.")),
+                ("topics", array_of(string("Topic word."))),
+                ("data", string("Hex data.")),
+            ],
+            vec![],
+        )
+    }
+}
+
+impl JsonSchema for EthenoEvent {
+    const NAME: &'static str = "EthenoEvent";
+    fn schema() -> Value {
+        let events: Vec<Value> = EVENT_SCHEMA
+            .iter()
+            .map(|(name, required, optional)| event(name, required, optional))
+            .collect();
+        json!({ "oneOf": events })
+    }
+}
+
+#[cfg(test)]
+mod schema_tests {
+    use super::*;
+    use crate::validation::{check_field, validate_etheno_events};
+    use jsonschema::validator_for;
+
+    /// The failures of a fixture against the schema, with their JSON pointers.
+    fn fixture_violations(schema: &Value, path: &str) -> Vec<String> {
+        let fixture: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        validator_for(schema)
+            .unwrap()
+            .iter_errors(&fixture)
+            .map(|error| format!("{}: {error}", error.instance_path))
+            .collect()
+    }
+
+    /// Indexes of the events of an Etheno fixture that fail the schema.
+    fn invalid_events(path: &str) -> Vec<usize> {
+        let mut indexes: Vec<usize> = fixture_violations(&etheno_events_schema(), path)
+            .iter()
+            .map(|failure| {
+                failure[1..]
+                    .split(['/', ':'])
+                    .next()
+                    .unwrap()
+                    .parse()
+                    .unwrap()
+            })
+            .collect();
+        indexes.dedup();
+        indexes
+    }
+
+    #[test]
+    fn it_should_match_the_field_validation() {
+        let samples = [
+            "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+            "90F79BF6EB2C4F870365E785982E1F101E93B906",
+            "0x90f79bf6eb2c4f870365e785982e1f101e93b9",
+            "0x0",
+            "0x",
+            "0x6080",
+            "0x6080604",
+            "0x0é0",
+            "0x+f+f",
+            "-1",
+        ];
+        for kind in [FieldKind::Address, FieldKind::Quantity, FieldKind::Data] {
+            let validator = validator_for(&field(kind, "")).unwrap();
+            for sample in samples {
+                assert_eq!(
+                    check_field(&json!(sample), kind).is_ok(),
+                    validator.is_valid(&json!(sample)),
+                    "{kind:?} {sample}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_accept_the_fixtures_the_crate_reads_and_writes() {
+        for path in [
+            "test_json_files/test_broadcast.json",
+            "test_json_files/tx_receipt_broadcast.json",
+            "tests/data/input.json",
+        ] {
+            assert_eq!(
+                Vec::<String>::new(),
+                fixture_violations(&broadcast_schema(), path),
+                "{path}"
+            );
+        }
+        // The fixtures truncate some init code to an odd number of digits, which both the schema
+        // and the validation reject.
+        for path in ["test_json_files/test_etheno.json", "tests/data/output.json"] {
+            let mut issues: Vec<usize> =
+                validate_etheno_events(&std::fs::read_to_string(path).unwrap())
+                    .unwrap()
+                    .iter()
+                    .filter(|issue| !issue.warning)
+                    .map(|issue| issue.index)
+                    .collect();
+            issues.dedup();
+            assert!(!issues.is_empty(), "{path}");
+            assert_eq!(issues, invalid_events(path), "{path}");
+        }
+    }
+
+    #[test]
+    fn it_should_reject_the_legacy_fixtures() {
+        // Written before receipts were read and before `FunctionCall` and `gas_price`.
+        assert_eq!(
+            vec![": \"receipts\" is a required property"],
+            fixture_violations(
+                &broadcast_schema(),
+                "test_json_files/simple_broadcast_test.json"
+            )
+        );
+        assert!(invalid_events("test_json_files/output.json").contains(&0));
+    }
+}
@@ -0,0 +1,376 @@
+use crate::data_model::Broadcast;
+use crate::echidna::DEFAULT_ECHIDNA_SENDERS;
+use crate::evm::create_address;
+use crate::primitives::{
+    create2_address, decode_hex, encode_hex, keccak256, normalize_address, pad_short_address,
+    parse_address, to_checksum_address,
+};
+use crate::registry::{ContractRegistry, CREATE2_DEPLOYER};
+use std::collections::HashMap;
+
+/*//////////////////////////////////////////////////////////////
+                        SENDER REMAPPING
+////////////////////////////////////////////////////////////// */
+/// Parses `OLD=NEW` sender pairs. Without pairs, the senders of the broadcast are mapped to
+/// Echidna's default senders in the order they first appear.
+pub fn sender_map(
+    broadcast: &Broadcast,
+    pairs: &[String],
+) -> Result<Vec<(String, String)>, String> {
+    if !pairs.is_empty() {
+        return pairs
+            .iter()
+            .map(|pair| {
+                let (old, new) = pair
+                    .split_once('=')
+                    .ok_or(format!("Invalid sender mapping {pair}: expected OLD=NEW"))?;
+                Ok((
+                    normalize_address(old)?,
+                    normalize_address(&pad_short_address(new))?,
+                ))
+            })
+            .collect();
+    }
+    let mut senders: Vec<String> = vec![];
+    for transaction in &broadcast.transactions {
+        let sender = normalize_address(&transaction.transaction.from)?;
+        if !senders.contains(&sender) {
+            senders.push(sender);
+        }
+    }
+    if senders.len() > DEFAULT_ECHIDNA_SENDERS.len() {
+        return Err(format!(
+            "The broadcast has {} senders but Echidna has {} default senders, map them with OLD=NEW",
+            senders.len(),
+            DEFAULT_ECHIDNA_SENDERS.len()
+        ));
+    }
+    senders
+        .into_iter()
+        .zip(DEFAULT_ECHIDNA_SENDERS)
+        .map(|(old, new)| Ok((old, normalize_address(&pad_short_address(new))?)))
+        .collect()
+}
+
+/// Rewrites the broadcast as if the `senders` (`(old, new)` pairs) had sent it.
+///
+/// The `from` of every transaction and every occurrence of a remapped address in the calldata
+/// (ABI-encoded arguments, constructor arguments, linked library addresses) are replaced. The
+/// addresses of the contracts are recomputed from the nonces of the new senders, starting at 0,
+/// and remapped too, in transaction order, so later calls and deployments use the new addresses.
+///
+/// Returns every remapped address, senders first, as `(old, new)` lowercase pairs.
+pub fn remap_senders(
+    broadcast: &mut Broadcast,
+    senders: &[(String, String)],
+) -> Result<Vec<(String, String)>, String> {
+    let registry = ContractRegistry::from_broadcast(broadcast)?;
+    let mut remapped: Vec<(String, String)> = senders.to_vec();
+    let mut nonces: HashMap<String, u64> = HashMap::new();
+    // CREATE children of a factory, which has nonce 1 after its deployment (EIP-161).
+    let mut factory_nonces: HashMap<String, u64> = HashMap::new();
+
+    for (i, transaction) in broadcast.transactions.iter_mut().enumerate() {
+        let details = &mut transaction.transaction;
+        let original_data = details.data.clone();
+        details.from = remapped_address(&details.from, &remapped)?;
+        details.data = replace_addresses(&details.data, &remapped);
+        if let Some(to) = &details.to {
+            details.to = Some(remapped_address(to, &remapped)?);
+        }
+        let nonce = nonces.entry(details.from.clone()).or_default();
+        let sender_nonce = *nonce;
+        *nonce += 1;
+
+        let new_address = match transaction.transaction_type.as_str() {
+            "CREATE" if senders.iter().any(|(_, new)| *new == details.from) => {
+                Some(create_address(&parse_address(&details.from)?, sender_nonce))
+            }
+            "CREATE2" => {
+                let deployer = details.to.clone().unwrap_or(CREATE2_DEPLOYER.to_string());
+                let data = decode_hex(&details.data)?;
+                if data.len() < 32 {
+                    return Err(format!(
+                        "CREATE2 transaction {i} has no salt in its calldata"
+                    ));
+                }
+                let salt: [u8; 32] = data[..32].try_into().unwrap_or_default();
+                Some(create2_address(
+                    &parse_address(&deployer)?,
+                    &salt,
+                    &keccak256(&data[32..]),
+                ))
+            }
+            _ => None,
+        };
+        transaction.contract_address = match new_address {
+            Some(new_address) => {
+                add_remapping(&mut remapped, &transaction.contract_address, &new_address)?
+            }
+            None => {
+                to_checksum_address(&remapped_address(&transaction.contract_address, &remapped)?)?
+            }
+        };
+
+        let children = registry
+            .contracts
+            .iter()
+            .filter(|contract| contract.from_factory && contract.transaction_index == i);
+        for (child, record) in transaction.additional_contracts.iter_mut().zip(children) {
+            let deployer = normalize_address(&record.deployer)?;
+            let new_deployer = parse_address(&remapped_address(&deployer, &remapped)?)?;
+            let original_init_code = child.init_code.clone();
+            child.init_code = replace_addresses(&child.init_code, &remapped);
+            let same_deployer = encode_hex(&new_deployer) == deployer;
+            let new_address = if child.transaction_type == "CREATE2" {
+                if same_deployer
+                    && same_hex(&child.init_code, &original_init_code)
+                    && same_hex(&transaction.transaction.data, &original_data)
+                {
+                    continue;
+                }
+                let salt = remapped_create2_salt(
+                    &child.address,
+                    &deployer,
+                    &original_data,
+                    &transaction.transaction.data,
+                    &original_init_code,
+                )?
+                .ok_or(format!(
+                    "Can't remap the CREATE2 child {} of transaction {i}: its salt was not found in the calldata",
+                    child.address
+                ))?;
+                create2_address(
+                    &new_deployer,
+                    &salt,
+                    &keccak256(&decode_hex(&child.init_code)?),
+                )
+            } else {
+                let factory_nonce = factory_nonces.entry(deployer).or_insert(1);
+                *factory_nonce += 1;
+                if same_deployer {
+                    continue;
+                }
+                create_address(&new_deployer, *factory_nonce - 1)
+            };
+            child.address = add_remapping(&mut remapped, &child.address, &new_address)?;
+        }
+    }
+
+    for library in &mut broadcast.libraries {
+        if let Some((path_and_name, address)) = library.rsplit_once(':') {
+            let address = to_checksum_address(&remapped_address(address, &remapped)?)?;
+            *library = format!("{path_and_name}:{address}");
+        }
+    }
+    Ok(remapped)
+}
+
+/// Records that `old` is now `new`, unless it didn't move, and returns the checksummed `new`.
+fn add_remapping(
+    remapped: &mut Vec<(String, String)>,
+    old: &str,
+    new: &[u8; 20],
+) -> Result<String, String> {
+    let old = normalize_address(old)?;
+    let new = encode_hex(new);
+    if old != new
+        && !remapped
+            .iter()
+            .any(|(remapped_old, _)| *remapped_old == old)
+    {
+        remapped.push((old, new.clone()));
+    }
+    to_checksum_address(&new)
+}
+
+fn same_hex(a: &str, b: &str) -> bool {
+    a.trim_start_matches("0x")
+        .eq_ignore_ascii_case(b.trim_start_matches("0x"))
+}
+
+/// The new form of `address`, lowercase, or `address` itself if it was not remapped.
+fn remapped_address(address: &str, remapped: &[(String, String)]) -> Result<String, String> {
+    let address = normalize_address(address)?;
+    Ok(remapped
+        .iter()
+        .find(|(old, _)| *old == address)
+        .map(|(_, new)| new.clone())
+        .unwrap_or(address))
+}
+
+/// Replaces the remapped addresses in hex `data`, wherever they start on a byte boundary.
+fn replace_addresses(data: &str, remapped: &[(String, String)]) -> String {
+    let hex = data.strip_prefix("0x").unwrap_or(data).to_lowercase();
+    let mut replaced = String::with_capacity(hex.len() + 2);
+    replaced.push_str("0x");
+    let mut position = 0;
+    while position < hex.len() {
+        let replacement = hex.get(position..position + 40).and_then(|window| {
+            remapped
+                .iter()
+                .find(|(old, _)| old[2..] == *window)
+                .map(|(_, new)| &new[2..])
+        });
+        match replacement {
+            Some(new) => {
+                replaced.push_str(new);
+                position += 40;
+            }
+            None => {
+                replaced.push_str(&hex[position..(position + 2).min(hex.len())]);
+                position += 2;
+            }
+        }
+    }
+    replaced
+}
+
+/// Finds where the salt of a CREATE2 child is in the original calldata, and returns the word at
+/// the same position of the remapped calldata, since the salt may contain a remapped address.
+fn remapped_create2_salt(
+    address: &str,
+    deployer: &str,
+    original_data: &str,
+    remapped_data: &str,
+    original_init_code: &str,
+) -> Result<Option<[u8; 32]>, String> {
+    let address = parse_address(address)?;
+    let deployer = parse_address(deployer)?;
+    let init_code_hash = keccak256(&decode_hex(original_init_code)?);
+    let original_data = decode_hex(original_data)?;
+    let remapped_data = decode_hex(remapped_data)?;
+    Ok((0..original_data.len().saturating_sub(31))
+        .find(|start| {
+            let salt: [u8; 32] = original_data[*start..start + 32]
+                .try_into()
+                .unwrap_or_default();
+            create2_address(&deployer, &salt, &init_code_hash) == address
+        })
+        .map(|start| {
+            remapped_data[start..start + 32]
+                .try_into()
+                .unwrap_or_default()
+        }))
+}
+
+#[cfg(test)]
+mod sender_remap_tests {
+    use super::*;
+    use crate::data_model::AdditionalContract;
+    use crate::deserialization::deserialize_broadcast;
+    use crate::file_handling::read_broadcast_file;
+
+    #[test]
+    fn it_should_remap_senders_and_created_addresses() {
+        let mut broadcast = deserialize_broadcast(
+            &read_broadcast_file("test_json_files/test_broadcast.json").unwrap(),
+        )
+        .unwrap();
+        let senders = sender_map(&broadcast, &[]).unwrap();
+        let remapped = remap_senders(&mut broadcast, &senders).unwrap();
+
+        let echidna_sender = parse_address(DEFAULT_ECHIDNA_SENDERS[0]).unwrap();
+        let ethernaut = create_address(&echidna_sender, 0);
+        let privacy_factory = create_address(&echidna_sender, 1);
+        assert_eq!(
+            vec![
+                (
+                    "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
+                    encode_hex(&echidna_sender)
+                ),
+                (
+                    "0x057ef64e23666f000b34ae31332854acbd1c8544".to_string(),
+                    encode_hex(&ethernaut)
+                ),
+                (
+                    "0x261d8c5e9742e6f7f1076fa1f560894524e19cad".to_string(),
+                    encode_hex(&privacy_factory)
+                ),
+                (
+                    "0xf3dfb0a70010735b0a14b4a69afc242b19600049".to_string(),
+                    encode_hex(&create_address(&ethernaut, 1))
+                ),
+            ],
+            remapped
+        );
+        let level_instance = &broadcast.transactions[3];
+        assert_eq!(encode_hex(&echidna_sender), level_instance.transaction.from);
+        assert_eq!(Some(encode_hex(&ethernaut)), level_instance.transaction.to);
+        assert_eq!(
+            format!("0xdfc86b17{:0>64}", &encode_hex(&privacy_factory)[2..]),
+            level_instance.transaction.data
+        );
+    }
+
+    #[test]
+    fn it_should_remap_explicit_pairs_and_create2_children() {
+        let mut broadcast = deserialize_broadcast(
+            &read_broadcast_file("test_json_files/test_broadcast.json").unwrap(),
+        )
+        .unwrap();
+        let old_sender = "0x90f79bf6eb2c4f870365e785982e1f101e93b906";
+        let old_ethernaut = parse_address("0x057ef64e23666f000b34ae31332854acbd1c8544").unwrap();
+        // The second call makes Ethernaut CREATE2 a child salted with the sender's address.
+        let salt = format!("{:0>64}", &old_sender[2..]);
+        let init_code = "0x6080604052";
+        let child_address = |factory: &[u8; 20], salt: &str| {
+            let salt: [u8; 32] = decode_hex(salt).unwrap().try_into().unwrap();
+            create2_address(factory, &salt, &keccak256(&decode_hex(init_code).unwrap()))
+        };
+        let old_child = child_address(&old_ethernaut, &salt);
+        let call = &mut broadcast.transactions[3];
+        call.transaction.data = format!("0xdfc86b17{salt}");
+        call.additional_contracts = vec![AdditionalContract {
+            transaction_type: "CREATE2".to_string(),
+            contract_name: None,
+            address: to_checksum_address(&encode_hex(&old_child)).unwrap(),
+            init_code: init_code.to_string(),
+        }];
+
+        let senders = sender_map(&broadcast, &[format!("{old_sender}=0x20000")]).unwrap();
+        let new_sender = "0x0000000000000000000000000000000000020000";
+        assert_eq!(
+            vec![(old_sender.to_string(), new_sender.to_string())],
+            senders
+        );
+        let remapped = remap_senders(&mut broadcast, &senders).unwrap();
+
+        let ethernaut = create_address(&parse_address(new_sender).unwrap(), 0);
+        let new_salt = format!("{:0>64}", &new_sender[2..]);
+        let child = child_address(&ethernaut, &new_salt);
+        assert!(remapped.contains(&(encode_hex(&old_child), encode_hex(&child))));
+        let call = &broadcast.transactions[3];
+        assert_eq!(new_sender, call.transaction.from);
+        assert_eq!(format!("0xdfc86b17{new_salt}"), call.transaction.data);
+        assert_eq!(
+            to_checksum_address(&encode_hex(&child)).unwrap(),
+            call.additional_contracts[0].address
+        );
+    }
+
+    #[test]
+    fn it_should_reject_invalid_sender_maps() {
+        let mut broadcast = deserialize_broadcast(
+            &read_broadcast_file("test_json_files/test_broadcast.json").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            Err("Invalid sender mapping 0x10000: expected OLD=NEW".to_string()),
+            sender_map(&broadcast, &["0x10000".to_string()])
+        );
+        for (transaction, sender) in broadcast.transactions[1..]
+            .iter_mut()
+            .zip(["0x1", "0x2", "0x3"])
+        {
+            transaction.transaction.from = pad_short_address(sender);
+        }
+        assert_eq!(
+            Err(
+                "The broadcast has 4 senders but Echidna has 3 default senders, map them with OLD=NEW"
+                    .to_string()
+            ),
+            sender_map(&broadcast, &[])
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::data_model::{Broadcast, Receipt, Transaction};
+use crate::primitives::normalize_address;
 use serde::Serialize;
 use serde_json::{json, Value};
 
@@ -64,32 +65,55 @@ fn serialize_transaction(transaction: Transaction, receipt: Receipt) -> Result<V
 
 pub fn serialize_broadcast(broadcast: Broadcast) -> Result<Vec<serde_json::Value>, String> {
     let mut serialized_tx_and_receipts = vec![];
-    for (tx, receipt) in broadcast
-        .transactions
-        .into_iter()
-        .zip(broadcast.receipts.into_iter())
-    {
+    for (tx, receipt) in broadcast.transactions.into_iter().zip(broadcast.receipts) {
         serialized_tx_and_receipts.push(serialize_transaction(tx, receipt)?);
     }
     Ok(serialized_tx_and_receipts)
 }
 
+/// Balance of anvil's default accounts, 10000 ETH. Also given to senders that have none when
+/// the events are replayed.
+pub const ANVIL_ACCOUNT_BALANCE: &str = "0x21e19e0c9bab2400000";
+
+/// Accounts of anvil's default mnemonic, which Foundry scripts broadcast from.
+pub const ANVIL_ACCOUNTS: [&str; 10] = [
+    "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+    "0x70997970c51812dc3a010c7d01b50e0d17dc79c8",
+    "0x3c44cdddb6a900fa2b585dd299e03d12fa4293bc",
+    "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+    "0x15d34aaf54267db7d7c367839aaf71a00a2c6a65",
+    "0x9965507d1a55bcc2695c58ba16fb37d819b0a4dc",
+    "0x976ea74026e726554db657fa54763abd0c3a0aa9",
+    "0x14dC79964da2C08b23698B3D3cc7Ca32193d9955",
+    "0x23618e81e3f5cdf7f54c3d65f7fbc0abf5b21e8f",
+    "0xa0ee7a142d267c1f36714e4a8f75612f20a79720",
+];
+
+/// Adds the `AccountCreated` events of anvil's accounts, followed by the ones of the other
+/// senders of the events, like remapped senders or the accounts of a fork.
 pub fn add_account_created_events(
     serialized_broadcast: Vec<serde_json::Value>,
 ) -> Result<Vec<serde_json::Value>, String> {
-    let account_created_objects = vec![
-        json!({"event":"AccountCreated", "address": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"}),
-        json!({"event":"AccountCreated", "address": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8"}),
-        json!({"event":"AccountCreated", "address": "0x3c44cdddb6a900fa2b585dd299e03d12fa4293bc"}),
-        json!({"event":"AccountCreated", "address": "0x90f79bf6eb2c4f870365e785982e1f101e93b906"}),
-        json!({"event":"AccountCreated", "address": "0x15d34aaf54267db7d7c367839aaf71a00a2c6a65"}),
-        json!({"event":"AccountCreated", "address": "0x9965507d1a55bcc2695c58ba16fb37d819b0a4dc"}),
-        json!({"event":"AccountCreated", "address": "0x976ea74026e726554db657fa54763abd0c3a0aa9"}),
-        json!({"event":"AccountCreated", "address": "0x14dC79964da2C08b23698B3D3cc7Ca32193d9955"}),
-        json!({"event":"AccountCreated", "address": "0x23618e81e3f5cdf7f54c3d65f7fbc0abf5b21e8f"}),
-        json!({"event":"AccountCreated", "address": "0xa0ee7a142d267c1f36714e4a8f75612f20a79720"}),
-    ];
-    let mut etheno_like_broadcast = account_created_objects;
+    let mut addresses: Vec<String> = ANVIL_ACCOUNTS
+        .iter()
+        .map(|address| address.to_string())
+        .collect();
+    for sender in serialized_broadcast
+        .iter()
+        .filter_map(|event| event["from"].as_str())
+    {
+        let sender = normalize_address(sender)?;
+        if !addresses
+            .iter()
+            .any(|address| address.eq_ignore_ascii_case(&sender))
+        {
+            addresses.push(sender);
+        }
+    }
+    let account_created_objects = addresses
+        .iter()
+        .map(|address| json!({"event":"AccountCreated", "address": address}));
+    let mut etheno_like_broadcast: Vec<serde_json::Value> = account_created_objects.collect();
     etheno_like_broadcast.extend(serialized_broadcast);
     Ok(etheno_like_broadcast)
 }
@@ -106,6 +130,10 @@ mod serialization_tests {
         let transaction_to_serialize = Transaction {
             transaction_type: "CREATE".to_string(),
             contract_address: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+            contract_name: None,
+            additional_contracts: vec![],
+            function: None,
+            arguments: None,
             transaction: TransactionDetails {
                 from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
                 to: None,
@@ -117,6 +145,7 @@ mod serialization_tests {
         let receipt_to_serialize = Receipt {
             gas_used: "0x6e675".to_string(),
             effective_gas_price: "0xe0fed783".to_string(),
+            logs: vec![],
         };
 
         let expected_serialization_result: serde_json::Value = serde_json::from_str(
@@ -134,6 +163,10 @@ mod serialization_tests {
         let transaction_to_serialize = Transaction {
             transaction_type: "CALL".to_string(),
             contract_address: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+            contract_name: None,
+            additional_contracts: vec![],
+            function: None,
+            arguments: None,
             transaction: TransactionDetails {
                 from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
                 to: Some("0x057ef64e23666f000b34ae31332854acbd1c8544".to_string()),
@@ -145,6 +178,7 @@ mod serialization_tests {
         let receipt_to_serialize = Receipt {
             gas_used: "0xb3bd".to_string(),
             effective_gas_price: "0xe0fed783".to_string(),
+            logs: vec![],
         };
 
         let expected_serialization_result: serde_json::Value = serde_json::from_str(r#"{"event":"FunctionCall","from":"0x90f79bf6eb2c4f870365e785982e1f101e93b906","to":"0x057ef64e23666f000b34ae31332854acbd1c8544","gas_used":"0xb3bd","gas_price":"0xe0fed783","data":"0x202023","value":"0x0"}"#).unwrap();
@@ -160,6 +194,10 @@ mod serialization_tests {
         let tx1 = Transaction {
             transaction_type: "CALL".to_string(),
             contract_address: "0x057ef64E23666F000b34aE31332854aCBd1c8544".to_string(),
+            contract_name: None,
+            additional_contracts: vec![],
+            function: None,
+            arguments: None,
             transaction: TransactionDetails {
                 from: "0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string(),
                 to: Some("0x057ef64e23666f000b34ae31332854acbd1c8544".to_string()),
@@ -170,6 +208,7 @@ mod serialization_tests {
         let rcp1 = Receipt {
             gas_used: "0xb3bd".to_string(),
             effective_gas_price: "0xe0fed783".to_string(),
+            logs: vec![],
         };
 
         let transactions = vec![tx1];
@@ -178,6 +217,10 @@ mod serialization_tests {
         let broadcast_to_serialize = Broadcast {
             transactions,
             receipts,
+            libraries: vec![],
+            chain: None,
+            commit: None,
+            timestamp: None,
         };
 
         let expected_serialization_result = vec![json!(
@@ -435,4 +478,20 @@ mod serialization_tests {
         let addition_result = add_account_created_events(serialized_broadcast_events).unwrap();
         assert_eq!(expected_result, addition_result);
     }
+
+    #[test]
+    fn it_should_create_accounts_for_other_senders() {
+        let fork_sender = "0x0000000000000000000000000000000000010000";
+        let events = add_account_created_events(vec![
+            json!({"event":"FunctionCall","from":"0x0000000000000000000000000000000000010000","to":"0x057ef64e23666f000b34ae31332854acbd1c8544","gas_used":"0x0","gas_price":"0x0","data":"0x","value":"0x0"}),
+            json!({"event":"FunctionCall","from":"0x90F79bf6EB2c4f870365E785982E1f101E93b906","to":"0x057ef64e23666f000b34ae31332854acbd1c8544","gas_used":"0x0","gas_price":"0x0","data":"0x","value":"0x0"}),
+        ])
+        .unwrap();
+
+        assert_eq!(13, events.len());
+        assert_eq!(
+            json!({"event":"AccountCreated", "address": fork_sender}),
+            events[ANVIL_ACCOUNTS.len()]
+        );
+    }
 }
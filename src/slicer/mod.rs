@@ -0,0 +1,272 @@
+use crate::data_model::{Broadcast, Receipt, Transaction, TransactionDetails};
+use crate::primitives::normalize_address;
+use crate::registry::ContractRegistry;
+
+/*//////////////////////////////////////////////////////////////
+                        DEPENDENCY SLICING
+////////////////////////////////////////////////////////////// */
+/// Gas used by a plain transfer, recorded for the transactions that only bump a nonce.
+const NONCE_FILLER_GAS: &str = "0x5208";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BroadcastSlice {
+    /// The kept transactions and nonce fillers, in broadcast order.
+    pub broadcast: Broadcast,
+    /// Indexes of the kept transactions in the original broadcast.
+    pub kept: Vec<usize>,
+    /// Indexes of the dropped transactions replaced by a nonce filler.
+    pub nonce_fillers: Vec<usize>,
+    /// EIP-55 checksummed addresses of the contracts the target depends on, the target first.
+    pub contracts: Vec<String>,
+}
+
+/// Keeps the transactions that the `target` contract (a name or an address) depends on.
+///
+/// The target's creation and every call to it are kept. Contracts whose address appears in the
+/// calldata of a kept transaction, and the factories of kept children, are dependencies: their
+/// creation is kept too, until no new dependency is found. Earlier calls to a kept factory that
+/// created children are kept as well, so that the factory's nonce is unchanged.
+///
+/// A dropped transaction that precedes a kept `CREATE` of the same sender is replaced by a
+/// self-transfer from that sender, so the sender's nonce, and the addresses of its contracts,
+/// stay as recorded.
+pub fn slice_broadcast(
+    broadcast: Broadcast,
+    registry: &ContractRegistry,
+    target: &str,
+) -> Result<BroadcastSlice, String> {
+    let target_record = registry
+        .contracts
+        .iter()
+        .find(|contract| {
+            contract.name.as_deref() == Some(target)
+                || normalize_address(target).ok() == normalize_address(&contract.address).ok()
+        })
+        .ok_or(format!("No contract named {target} in the broadcast"))?;
+    let target_address = normalize_address(&target_record.address)?;
+
+    let callees: Vec<Option<String>> = broadcast
+        .transactions
+        .iter()
+        .map(|transaction| match transaction.transaction_type.as_str() {
+            "CALL" => transaction
+                .transaction
+                .to
+                .as_deref()
+                .map(normalize_address)
+                .transpose(),
+            _ => Ok(None),
+        })
+        .collect::<Result<_, String>>()?;
+    let mut kept = vec![false; broadcast.transactions.len()];
+    let mut contracts = vec![target_address.clone()];
+    for (i, callee) in callees.iter().enumerate() {
+        kept[i] = callee.as_ref() == Some(&target_address);
+    }
+
+    let mut next = 0;
+    while next < contracts.len() {
+        let address = contracts[next].clone();
+        next += 1;
+        let Some(record) = registry
+            .contracts
+            .iter()
+            .find(|contract| normalize_address(&contract.address).ok() == Some(address.clone()))
+        else {
+            continue;
+        };
+        kept[record.transaction_index] = true;
+        if record.from_factory {
+            // The addresses of CREATE children depend on the number of contracts the factory
+            // created before.
+            let factory = &callees[record.transaction_index];
+            for (i, transaction) in broadcast.transactions.iter().enumerate() {
+                if i < record.transaction_index
+                    && factory.is_some()
+                    && callees[i] == *factory
+                    && !transaction.additional_contracts.is_empty()
+                {
+                    kept[i] = true;
+                }
+            }
+        }
+
+        for (i, transaction) in broadcast.transactions.iter().enumerate() {
+            if !kept[i] {
+                continue;
+            }
+            let data = transaction.transaction.data.to_lowercase();
+            for contract in &registry.contracts {
+                let dependency = normalize_address(&contract.address)?;
+                let referenced =
+                    data.contains(&dependency[2..]) || callees[i] == Some(dependency.clone());
+                if referenced && !contracts.contains(&dependency) {
+                    contracts.push(dependency);
+                }
+            }
+        }
+    }
+
+    let mut slice = BroadcastSlice {
+        broadcast: Broadcast {
+            transactions: vec![],
+            receipts: vec![],
+            libraries: broadcast.libraries,
+            chain: broadcast.chain,
+            commit: broadcast.commit,
+            timestamp: broadcast.timestamp,
+        },
+        kept: vec![],
+        nonce_fillers: vec![],
+        contracts: vec![],
+    };
+    let last_kept_creations: Vec<(String, usize)> = broadcast
+        .transactions
+        .iter()
+        .enumerate()
+        .filter(|(i, transaction)| kept[*i] && transaction.transaction_type == "CREATE")
+        .map(|(i, transaction)| Ok((normalize_address(&transaction.transaction.from)?, i)))
+        .collect::<Result<_, String>>()?;
+    for (i, (transaction, receipt)) in broadcast
+        .transactions
+        .into_iter()
+        .zip(broadcast.receipts)
+        .enumerate()
+    {
+        if kept[i] {
+            slice.kept.push(i);
+            slice.broadcast.transactions.push(transaction);
+            slice.broadcast.receipts.push(receipt);
+            continue;
+        }
+        let sender = normalize_address(&transaction.transaction.from)?;
+        if last_kept_creations
+            .iter()
+            .any(|(creator, creation)| *creator == sender && *creation > i)
+        {
+            slice.nonce_fillers.push(i);
+            slice.broadcast.transactions.push(nonce_filler(&sender));
+            slice.broadcast.receipts.push(Receipt {
+                gas_used: NONCE_FILLER_GAS.to_string(),
+                effective_gas_price: receipt.effective_gas_price,
+                logs: vec![],
+            });
+        }
+    }
+    slice.contracts = registry
+        .contracts
+        .iter()
+        .filter(|contract| {
+            contracts.contains(&normalize_address(&contract.address).unwrap_or_default())
+        })
+        .map(|contract| contract.address.clone())
+        .collect();
+    slice
+        .contracts
+        .sort_by_key(|address| *address != target_record.address);
+    Ok(slice)
+}
+
+/// An empty transfer from the sender to itself, which only increments its nonce.
+fn nonce_filler(sender: &str) -> Transaction {
+    Transaction {
+        transaction_type: "CALL".to_string(),
+        contract_address: sender.to_string(),
+        contract_name: None,
+        function: None,
+        arguments: None,
+        transaction: TransactionDetails {
+            from: sender.to_string(),
+            to: Some(sender.to_string()),
+            value: "0x0".to_string(),
+            data: "0x".to_string(),
+        },
+        additional_contracts: vec![],
+    }
+}
+
+impl BroadcastSlice {
+    /// Human readable summary of the slice, naming the contracts after the registry.
+    pub fn report(&self, registry: &ContractRegistry) -> String {
+        let mut lines: Vec<String> = self
+            .contracts
+            .iter()
+            .map(|address| {
+                let name = registry
+                    .contracts
+                    .iter()
+                    .find(|contract| contract.address == *address)
+                    .and_then(|contract| contract.name.as_deref())
+                    .unwrap_or("<unnamed>");
+                format!("contract {name} {address}")
+            })
+            .collect();
+        let indexes = |indexes: &[usize]| {
+            indexes
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        lines.push(format!("kept transactions: {}", indexes(&self.kept)));
+        if !self.nonce_fillers.is_empty() {
+            lines.push(format!(
+                "nonce fillers for transactions: {}",
+                indexes(&self.nonce_fillers)
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod slicer_tests {
+    use super::*;
+    use crate::deserialization::deserialize_broadcast;
+    use crate::file_handling::read_broadcast_file;
+
+    fn test_broadcast() -> Broadcast {
+        deserialize_broadcast(&read_broadcast_file("test_json_files/test_broadcast.json").unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn it_should_keep_dependencies_of_factory_child() {
+        let broadcast = test_broadcast();
+        let registry = ContractRegistry::from_broadcast(&broadcast).unwrap();
+        let slice = slice_broadcast(
+            broadcast,
+            &registry,
+            "0xf3dfb0a70010735b0a14b4a69afc242b19600049",
+        )
+        .unwrap();
+
+        assert_eq!(vec![0, 1, 3], slice.kept);
+        assert!(slice.nonce_fillers.is_empty());
+        assert_eq!(
+            vec![
+                "0xF3dfB0A70010735B0A14B4A69aFC242b19600049",
+                "0x057ef64E23666F000b34aE31332854aCBd1c8544",
+                "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad",
+            ],
+            slice.contracts
+        );
+    }
+
+    #[test]
+    fn it_should_fill_nonces_of_dropped_transactions() {
+        let broadcast = test_broadcast();
+        let registry = ContractRegistry::from_broadcast(&broadcast).unwrap();
+        let slice = slice_broadcast(broadcast, &registry, "PrivacyFactory").unwrap();
+
+        assert_eq!(vec![1], slice.kept);
+        assert_eq!(vec![0], slice.nonce_fillers);
+        let filler = &slice.broadcast.transactions[0].transaction;
+        assert_eq!(filler.to.as_deref(), Some(filler.from.as_str()));
+        assert_eq!("0x", filler.data);
+        assert_eq!(
+            "0x261D8c5e9742e6f7f1076Fa1F560894524e19cad",
+            slice.broadcast.transactions[1].contract_address
+        );
+    }
+}
@@ -0,0 +1,383 @@
+use crate::abi::{decode, AbiType, AbiValue};
+use crate::data_model::{Broadcast, Log};
+use crate::primitives::{
+    checked_add_words, checked_sub_words, decode_hex, encode_hex, keccak256, normalize_address,
+    pad_short_address, to_checksum_address, ZERO_ADDRESS,
+};
+use crate::registry::ContractRegistry;
+use std::fmt;
+
+/*//////////////////////////////////////////////////////////////
+                    TOKEN BALANCE SNAPSHOT
+////////////////////////////////////////////////////////////// */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenStandard {
+    Erc20,
+    Erc721,
+    Erc1155,
+}
+
+impl fmt::Display for TokenStandard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenStandard::Erc20 => write!(f, "ERC-20"),
+            TokenStandard::Erc721 => write!(f, "ERC-721"),
+            TokenStandard::Erc1155 => write!(f, "ERC-1155"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TokenBalances {
+    /// EIP-55 checksummed address of the token contract.
+    pub address: String,
+    pub name: Option<String>,
+    pub standard: TokenStandard,
+    /// Balances per holder and token id, in the order holders first received or sent tokens.
+    pub balances: Vec<HolderBalance>,
+}
+
+/// The tokens of one id a holder received and sent, tracked apart so that amounts of 2^255 and
+/// more don't look negative.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HolderBalance {
+    pub holder: String,
+    /// `None` for ERC-20.
+    pub id: Option<[u8; 32]>,
+    pub received: [u8; 32],
+    pub sent: [u8; 32],
+}
+
+impl HolderBalance {
+    /// `None` if the logs send more tokens than they give, e.g. tokens minted without an event.
+    pub fn balance(&self) -> Option<[u8; 32]> {
+        checked_sub_words(&self.received, &self.sent)
+    }
+}
+
+impl TokenBalances {
+    fn add(
+        &mut self,
+        holder: &str,
+        id: Option<[u8; 32]>,
+        amount: [u8; 32],
+        received: bool,
+    ) -> Result<(), String> {
+        let index = match self
+            .balances
+            .iter()
+            .position(|balance| balance.holder == holder && balance.id == id)
+        {
+            Some(index) => index,
+            None => {
+                self.balances.push(HolderBalance {
+                    holder: holder.to_string(),
+                    id,
+                    received: [0u8; 32],
+                    sent: [0u8; 32],
+                });
+                self.balances.len() - 1
+            }
+        };
+        let balance = &mut self.balances[index];
+        let total = if received {
+            &mut balance.received
+        } else {
+            &mut balance.sent
+        };
+        *total = checked_add_words(total, &amount).ok_or(format!(
+            "Transfers of {holder} overflow 256 bits in token {}",
+            self.address
+        ))?;
+        Ok(())
+    }
+
+    fn holds_anything(&self, holder: &str) -> bool {
+        self.balances.iter().any(|balance| {
+            balance.holder == holder && balance.balance().is_some_and(|word| word != [0u8; 32])
+        })
+    }
+}
+
+/// Balances of every token after the broadcast, from the `Transfer`, `TransferSingle` and
+/// `TransferBatch` logs of its receipts. Mints and burns are transfers from and to the zero
+/// address, which holds nothing.
+pub fn token_balances(broadcast: &Broadcast) -> Result<Vec<TokenBalances>, String> {
+    let registry = ContractRegistry::from_broadcast(broadcast)?;
+    let transfer_topic = encode_hex(&keccak256(b"Transfer(address,address,uint256)"));
+    let transfer_single_topic = encode_hex(&keccak256(
+        b"TransferSingle(address,address,address,uint256,uint256)",
+    ));
+    let transfer_batch_topic = encode_hex(&keccak256(
+        b"TransferBatch(address,address,address,uint256[],uint256[])",
+    ));
+
+    let mut tokens: Vec<TokenBalances> = vec![];
+    for log in broadcast.receipts.iter().flat_map(|receipt| &receipt.logs) {
+        let Some(topic) = log.topics.first() else {
+            continue;
+        };
+        let topic = topic.to_lowercase();
+        // ERC-20 and ERC-721 share the `Transfer` signature, but the token id is indexed.
+        let (standard, from, to, amounts) = if topic == transfer_topic && log.topics.len() == 3 {
+            let amount = decode_words(log, &[AbiType::Uint(256)])?;
+            (
+                TokenStandard::Erc20,
+                topic_address(log, 1)?,
+                topic_address(log, 2)?,
+                vec![(None, amount[0])],
+            )
+        } else if topic == transfer_topic && log.topics.len() == 4 {
+            (
+                TokenStandard::Erc721,
+                topic_address(log, 1)?,
+                topic_address(log, 2)?,
+                vec![(Some(topic_word(log, 3)?), one_word())],
+            )
+        } else if topic == transfer_single_topic {
+            let values = decode_words(log, &[AbiType::Uint(256), AbiType::Uint(256)])?;
+            (
+                TokenStandard::Erc1155,
+                topic_address(log, 2)?,
+                topic_address(log, 3)?,
+                vec![(Some(values[0]), values[1])],
+            )
+        } else if topic == transfer_batch_topic {
+            let array = AbiType::Array(Box::new(AbiType::Uint(256)));
+            let values = decode(&[array.clone(), array], &decode_hex(&log.data)?)
+                .map_err(|err| format!("Invalid TransferBatch event of {}: {err}", log.address))?;
+            let (AbiValue::Array(_, ids), AbiValue::Array(_, amounts)) = (&values[0], &values[1])
+            else {
+                return Err(format!("Invalid TransferBatch event of {}", log.address));
+            };
+            let amounts = ids
+                .iter()
+                .zip(amounts)
+                .filter_map(|(id, amount)| match (id, amount) {
+                    (AbiValue::Uint(_, id), AbiValue::Uint(_, amount)) => {
+                        Some((Some(*id), *amount))
+                    }
+                    _ => None,
+                })
+                .collect();
+            (
+                TokenStandard::Erc1155,
+                topic_address(log, 2)?,
+                topic_address(log, 3)?,
+                amounts,
+            )
+        } else {
+            continue;
+        };
+
+        let address = to_checksum_address(&log.address)?;
+        let index = match tokens.iter().position(|token| token.address == address) {
+            Some(index) => index,
+            None => {
+                let name = registry
+                    .contracts
+                    .iter()
+                    .find(|contract| contract.address == address)
+                    .and_then(|contract| contract.name.clone());
+                tokens.push(TokenBalances {
+                    address,
+                    name,
+                    standard,
+                    balances: vec![],
+                });
+                tokens.len() - 1
+            }
+        };
+        let token = &mut tokens[index];
+        for (id, amount) in amounts {
+            if from != ZERO_ADDRESS {
+                token.add(&to_checksum_address(&from)?, id, amount, false)?;
+            }
+            if to != ZERO_ADDRESS {
+                token.add(&to_checksum_address(&to)?, id, amount, true)?;
+            }
+        }
+    }
+    for token in &mut tokens {
+        token
+            .balances
+            .retain(|balance| balance.balance() != Some([0u8; 32]));
+    }
+    Ok(tokens)
+}
+
+/// Balance table per token, followed by a warning for every sender that holds nothing of some
+/// of the tokens.
+pub fn balances_report(tokens: &[TokenBalances], senders: &[String]) -> Result<String, String> {
+    if tokens.is_empty() {
+        return Ok("No token transfers found in the receipts".to_string());
+    }
+    let mut lines = vec![];
+    for token in tokens {
+        lines.push(format!(
+            "{} {} ({})",
+            token.name.as_deref().unwrap_or("<unnamed>"),
+            token.address,
+            token.standard
+        ));
+        for HolderBalance {
+            holder,
+            id,
+            received,
+            sent,
+        } in &token.balances
+        {
+            let balance = match checked_sub_words(received, sent) {
+                Some(balance) => AbiValue::Uint(256, balance).display(),
+                // Sent tokens the logs never gave.
+                None => format!(
+                    "-{}",
+                    AbiValue::Uint(256, checked_sub_words(sent, received).unwrap_or_default())
+                        .display()
+                ),
+            };
+            lines.push(match (token.standard, id) {
+                (TokenStandard::Erc721, Some(id)) => {
+                    format!("  {holder}: #{}", AbiValue::Uint(256, *id).display())
+                }
+                (_, Some(id)) => format!(
+                    "  {holder}: {balance} of #{}",
+                    AbiValue::Uint(256, *id).display()
+                ),
+                (_, None) => format!("  {holder}: {balance}"),
+            });
+        }
+    }
+    for sender in senders {
+        let sender = to_checksum_address(&pad_short_address(sender))?;
+        let empty_tokens: Vec<&str> = tokens
+            .iter()
+            .filter(|token| !token.holds_anything(&sender))
+            .map(|token| token.name.as_deref().unwrap_or(&token.address))
+            .collect();
+        if empty_tokens.len() == tokens.len() {
+            lines.push(format!("warning: sender {sender} holds no tokens"));
+        } else if !empty_tokens.is_empty() {
+            lines.push(format!(
+                "warning: sender {sender} holds no {}",
+                empty_tokens.join(", ")
+            ));
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+fn one_word() -> [u8; 32] {
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    one
+}
+
+fn topic_word(log: &Log, index: usize) -> Result<[u8; 32], String> {
+    let topic = log
+        .topics
+        .get(index)
+        .ok_or(format!("Event of {} is missing topic {index}", log.address))?;
+    decode_hex(topic)?
+        .try_into()
+        .map_err(|_| format!("Invalid topic {topic} of {}", log.address))
+}
+
+/// Lowercase address stored in an indexed topic.
+fn topic_address(log: &Log, index: usize) -> Result<String, String> {
+    normalize_address(&encode_hex(&topic_word(log, index)?[12..]))
+}
+
+fn decode_words(log: &Log, types: &[AbiType]) -> Result<Vec<[u8; 32]>, String> {
+    decode(types, &decode_hex(&log.data)?)
+        .map_err(|err| format!("Invalid transfer event of {}: {err}", log.address))?
+        .into_iter()
+        .map(|value| match value {
+            AbiValue::Uint(_, word) => Ok(word),
+            _ => Err(format!("Invalid transfer event of {}", log.address)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod token_balances_tests {
+    use super::*;
+    use crate::deserialization::deserialize_broadcast;
+
+    const TOKEN: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+    const NFT: &str = "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512";
+    const MULTI_TOKEN: &str = "0x9fE46736679d2D9a65F0992F2272dE9f3c7fa6e0";
+    const DEPLOYER: &str = "0x90F79bf6EB2c4f870365E785982E1f101E93b906";
+    const SENDER: &str = "0x0000000000000000000000000000000000010000";
+
+    fn word(value: &str) -> String {
+        format!("{:0>64}", value.trim_start_matches("0x").to_lowercase())
+    }
+
+    fn log(address: &str, signature: &str, topics: &[&str], data: &str) -> String {
+        let topics: Vec<String> = [encode_hex(&keccak256(signature.as_bytes()))]
+            .into_iter()
+            .chain(topics.iter().map(|topic| format!("0x{}", word(topic))))
+            .map(|topic| format!("\"{topic}\""))
+            .collect();
+        format!(
+            r#"{{"address": "{address}", "topics": [{}], "data": "0x{data}"}}"#,
+            topics.join(", ")
+        )
+    }
+
+    #[test]
+    fn it_should_compute_balances_and_flag_empty_senders() {
+        let erc20 = "Transfer(address,address,uint256)";
+        let logs = [
+            log(TOKEN, erc20, &[ZERO_ADDRESS, DEPLOYER], &word("0x3e8")),
+            log(TOKEN, erc20, &[DEPLOYER, SENDER], &word("0x64")),
+            log(
+                TOKEN,
+                erc20,
+                &[ZERO_ADDRESS, DEPLOYER],
+                &word(&format!("8{:0>63}", "")),
+            ),
+            log(NFT, erc20, &[ZERO_ADDRESS, DEPLOYER, "0x7"], ""),
+            log(NFT, erc20, &[DEPLOYER, ZERO_ADDRESS, "0x7"], ""),
+            log(NFT, erc20, &[ZERO_ADDRESS, SENDER, "0x8"], ""),
+            log(
+                MULTI_TOKEN,
+                "TransferBatch(address,address,address,uint256[],uint256[])",
+                &[DEPLOYER, ZERO_ADDRESS, DEPLOYER],
+                &["0x40", "0xa0", "0x2", "0x1", "0x2", "0x2", "0x2", "0x5"]
+                    .map(word)
+                    .concat(),
+            ),
+        ];
+        let broadcast = deserialize_broadcast(&format!(
+            r#"{{
+                "transactions": [{{"transactionType": "CREATE", "contractName": "Token", "contractAddress": "{TOKEN}",
+                    "transaction": {{"from": "{DEPLOYER}", "to": null, "value": "0x0", "data": "0x6080"}}}}],
+                "receipts": [{{"gasUsed": "0x1", "effectiveGasPrice": "0x1", "logs": [{}]}}]
+            }}"#,
+            logs.join(", ")
+        ))
+        .unwrap();
+
+        let tokens = token_balances(&broadcast).unwrap();
+        let report =
+            balances_report(&tokens, &[SENDER.to_string(), "0x20000".to_string()]).unwrap();
+
+        assert_eq!(
+            vec![
+                "Token 0x5FbDB2315678afecb367f032d93F642f64180aa3 (ERC-20)",
+                "  0x90F79bf6EB2c4f870365E785982E1f101E93b906: 57896044618658097711785492504343953926634992332820282019728792003956564820868",
+                "  0x0000000000000000000000000000000000010000: 100",
+                "<unnamed> 0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512 (ERC-721)",
+                "  0x0000000000000000000000000000000000010000: #8",
+                "<unnamed> 0x9fE46736679d2D9a65F0992F2272dE9f3c7fa6e0 (ERC-1155)",
+                "  0x90F79bf6EB2c4f870365E785982E1f101E93b906: 2 of #1",
+                "  0x90F79bf6EB2c4f870365E785982E1f101E93b906: 5 of #2",
+                "warning: sender 0x0000000000000000000000000000000000010000 holds no 0x9fE46736679d2D9a65F0992F2272dE9f3c7fa6e0",
+                "warning: sender 0x0000000000000000000000000000000000020000 holds no tokens",
+            ],
+            report.lines().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use crate::data_model::EthenoEvent;
+use crate::primitives::{decode_hex, normalize_address};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/*//////////////////////////////////////////////////////////////
+                    ETHENO EVENT VALIDATION
+////////////////////////////////////////////////////////////// */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Address,
+    /// Hex quantity, like `gas_used` or `value`.
+    Quantity,
+    /// Hex bytes, with an even number of digits.
+    Data,
+    /// JSON integer, like the block `number`.
+    Integer,
+}
+
+pub type Fields = &'static [(&'static str, FieldKind)];
+
+/// The events Echidna reads, with their required and optional fields. The JSON Schema of the
+/// emitted events is generated from it.
+pub const EVENT_SCHEMA: [(&str, Fields, Fields); 4] = [
+    (
+        "AccountCreated",
+        &[("address", FieldKind::Address)],
+        &[("balance", FieldKind::Quantity)],
+    ),
+    (
+        "ContractCreated",
+        &[
+            ("from", FieldKind::Address),
+            ("contract_address", FieldKind::Address),
+            ("gas_used", FieldKind::Quantity),
+            ("gas_price", FieldKind::Quantity),
+            ("data", FieldKind::Data),
+            ("value", FieldKind::Quantity),
+        ],
+        &[],
+    ),
+    (
+        "FunctionCall",
+        &[
+            ("from", FieldKind::Address),
+            ("to", FieldKind::Address),
+            ("gas_used", FieldKind::Quantity),
+            ("gas_price", FieldKind::Quantity),
+            ("data", FieldKind::Data),
+            ("value", FieldKind::Quantity),
+        ],
+        &[],
+    ),
+    (
+        "BlockMined",
+        &[
+            ("number", FieldKind::Integer),
+            ("timestamp", FieldKind::Integer),
+        ],
+        &[],
+    ),
+];
+
+/// A problem of one event, located by its index in the array and its JSON path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub index: usize,
+    pub path: String,
+    pub message: String,
+    /// Set for what may still be valid, like calls to contracts an earlier call may create.
+    pub warning: bool,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = if self.warning { "warning: " } else { "" };
+        write!(
+            f,
+            "[{}] {}: {severity}{}",
+            self.index, self.path, self.message
+        )
+    }
+}
+
+/// Checks an Etheno `init.json` event by event: unknown events, missing fields, malformed
+/// addresses, quantities and data, and duplicate `AccountCreated` events. Calls to addresses no
+/// earlier event creates are warnings, since factory children are created inside the calls and
+/// have no event of their own. Fails only if the file is not a JSON array.
+pub fn validate_etheno_events(content: &str) -> Result<Vec<ValidationIssue>, String> {
+    let json: Value = serde_json::from_str(content)
+        .map_err(|err| format!("Failed to parse the Etheno events as JSON: {err}"))?;
+    let Value::Array(events) = json else {
+        return Err("The Etheno events must be a JSON array".to_string());
+    };
+
+    let mut issues = vec![];
+    let mut accounts: HashMap<String, usize> = HashMap::new();
+    let mut created: Vec<String> = vec![];
+    for (i, event) in events.iter().enumerate() {
+        let mut report = |path: String, message: String, warning: bool| {
+            issues.push(ValidationIssue {
+                index: i,
+                path,
+                message,
+                warning,
+            })
+        };
+        let mut issue = |path: String, message: String| report(path, message, false);
+        let Value::Object(fields) = event else {
+            issue(format!("$[{i}]"), "expected an event object".to_string());
+            continue;
+        };
+        let Some(name) = fields.get("event") else {
+            issue(format!("$[{i}].event"), "missing field".to_string());
+            continue;
+        };
+        let Some((_, required, optional)) = EVENT_SCHEMA
+            .iter()
+            .find(|(event_name, _, _)| Some(*event_name) == name.as_str())
+        else {
+            issue(format!("$[{i}].event"), format!("unknown event {name}"));
+            continue;
+        };
+
+        let mut valid = true;
+        for (field, kind) in required.iter() {
+            match fields.get(*field) {
+                None => {
+                    issue(format!("$[{i}].{field}"), "missing field".to_string());
+                    valid = false;
+                }
+                Some(value) => {
+                    if let Err(message) = check_field(value, *kind) {
+                        issue(format!("$[{i}].{field}"), message);
+                        valid = false;
+                    }
+                }
+            }
+        }
+        for (field, kind) in optional.iter() {
+            if let Some(Err(message)) = fields.get(*field).map(|value| check_field(value, *kind)) {
+                issue(format!("$[{i}].{field}"), message);
+                valid = false;
+            }
+        }
+        if !valid {
+            // Later calls to a contract whose creation is malformed are not reported again.
+            if let Some(Ok(address)) = fields
+                .get("contract_address")
+                .and_then(Value::as_str)
+                .map(normalize_address)
+            {
+                created.push(address);
+            }
+            continue;
+        }
+
+        match typed_event(fields) {
+            Err(message) => issue(format!("$[{i}]"), message),
+            Ok(EthenoEvent::AccountCreated { address, .. }) => {
+                let address = normalize_address(&address)?;
+                match accounts.get(&address) {
+                    Some(first) => issue(
+                        format!("$[{i}].address"),
+                        format!(
+                            "duplicate AccountCreated for {address}, first created at [{first}]"
+                        ),
+                    ),
+                    None => {
+                        accounts.insert(address, i);
+                    }
+                }
+            }
+            Ok(EthenoEvent::ContractCreated {
+                contract_address, ..
+            }) => created.push(normalize_address(&contract_address)?),
+            Ok(EthenoEvent::FunctionCall { to, .. }) => {
+                let to = normalize_address(&to)?;
+                if !created.contains(&to) && !accounts.contains_key(&to) {
+                    report(
+                        format!("$[{i}].to"),
+                        format!("call to {to}, which no earlier event creates, unless an earlier call or creation deploys it"),
+                        true,
+                    );
+                }
+            }
+            Ok(EthenoEvent::BlockMined { .. }) => {}
+        }
+    }
+    Ok(issues)
+}
+
+fn typed_event(fields: &Map<String, Value>) -> Result<EthenoEvent, String> {
+    serde_json::from_value(Value::Object(fields.clone()))
+        .map_err(|err| format!("Failed to deserialize Etheno event: {err}"))
+}
+
+pub fn check_field(value: &Value, kind: FieldKind) -> Result<(), String> {
+    if kind == FieldKind::Integer {
+        return match value.as_u64() {
+            Some(_) => Ok(()),
+            None => Err(format!("expected an unsigned integer, got {value}")),
+        };
+    }
+    let Some(text) = value.as_str() else {
+        return Err(format!("expected a hex string, got {value}"));
+    };
+    match kind {
+        FieldKind::Address => normalize_address(text).map(|_| ()),
+        // Values and balances can exceed 64 bits, so quantities are checked digit by digit.
+        FieldKind::Quantity => {
+            let digits = text.strip_prefix("0x").unwrap_or(text);
+            if digits.is_empty()
+                || digits.len() > 64
+                || !digits.chars().all(|digit| digit.is_ascii_hexdigit())
+            {
+                return Err(format!("Invalid hex quantity {text}"));
+            }
+            Ok(())
+        }
+        FieldKind::Data => decode_hex(text).map(|_| ()),
+        FieldKind::Integer => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn it_should_report_issues_with_index_and_json_path() {
+        let content = r#"[
+            {"event": "AccountCreated", "address": "0x90f79bf6eb2c4f870365e785982e1f101e93b906"},
+            {"event": "ContractCreated", "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+             "contract_address": "0x057ef64e23666f000b34ae31332854acbd1c8544",
+             "gas_used": "0x1", "gas_price": "0x1", "data": "0x6080604", "value": "0x0"},
+            {"event": "FunctionCall", "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+             "to": "0x261d8c5e9742e6f7f1076fa1f560894524e19cad",
+             "gas_used": "0x1", "gas_price": "0x1", "data": "0x", "value": "0x0"},
+            {"event": "BlockMined", "number": 1},
+            {"event": "AccountCreated", "address": "90F79BF6EB2C4F870365E785982E1F101E93B906"},
+            {"event": "ContractDestroyed"},
+            {"event": "FunctionCall", "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+             "to": "0x057ef64e23666f000b34ae31332854acbd1c8544",
+             "gas_used": "0x1", "gas_price": "0x1", "data": "0x0é0", "value": "0x0"},
+            {"event": "FunctionCall", "from": "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+             "to": "0x057ef64e23666f000b34ae31332854acbd1c8544",
+             "gas_used": "0x1", "gas_price": "0x1", "data": "0x+f+f", "value": "0x0"}
+        ]"#;
+
+        let issues: Vec<String> = validate_etheno_events(content)
+            .unwrap()
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+
+        assert_eq!(
+            vec![
+                "[1] $[1].data: Hex string has an odd length: 0x6080604",
+                "[2] $[2].to: warning: call to 0x261d8c5e9742e6f7f1076fa1f560894524e19cad, which no earlier event creates, unless an earlier call or creation deploys it",
+                "[3] $[3].timestamp: missing field",
+                "[4] $[4].address: duplicate AccountCreated for 0x90f79bf6eb2c4f870365e785982e1f101e93b906, first created at [0]",
+                "[5] $[5].event: unknown event \"ContractDestroyed\"",
+                "[6] $[6].data: Invalid hex string 0x0é0: invalid character 'é'",
+                "[7] $[7].data: Invalid hex string 0x+f+f: invalid character '+'",
+            ],
+            issues
+        );
+    }
+}